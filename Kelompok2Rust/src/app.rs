@@ -0,0 +1,264 @@
+//! Aplikasi kontrol lengkap di atas trait `hal`: polling sensor → kalibrasi → lifecycle → setpoint →
+//! kontrol → output fase → interlock → output. Dipakai binary simulasi host dan integration test; firmware
+//! memakai komponen yang sama tetapi dipecah ke beberapa task Embassy.
+
+use crate::calib::CalibrationSet;
+use crate::control::{apply, ActuatorCmd, Controller};
+use crate::hal::{Clock, DigitalOutput, ModbusPort, Servo};
+use crate::interlock::InterlockTable;
+use crate::lifecycle::{Command, FaultCause, Lifecycle, Phase, Rejected, Transition};
+use crate::modbus::diag::BusDiagnostics;
+use crate::modbus::Master;
+use crate::post::{Outcome, Post, PostPlan, Report};
+use crate::retain::Snapshot;
+use crate::sched::Scheduler;
+use crate::servo::{ServoCal, ServoCalError};
+use crate::setpoint::SetpointGen;
+use crate::sht20::{Measurement, Sht20};
+use crate::timesync::{SyncQuality, SyncReport, TimeSync};
+
+pub const POLL_PERIOD_MS: u64 = 1000;
+
+/// Satu baris data hasil satu putaran polling.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sample {
+    /// Waktu akuisisi, monotonic.
+    pub t_ms:        u64,
+    /// Waktu akuisisi dalam epoch host (None sebelum `sync_time`).
+    pub epoch_ms:    Option<u64>,
+    pub sync:        SyncQuality,
+    /// Nilai sensor sebelum kalibrasi.
+    pub raw:         Measurement,
+    /// Nilai terkalibrasi: yang dipakai kontrol & interlock.
+    pub measurement: Measurement,
+    /// Setpoint RH yang dipakai controller di putaran ini.
+    pub setpoint:    f32,
+    pub cmd:         ActuatorCmd,
+    pub phase:       Phase,
+}
+
+pub struct App<P, F, S, C> {
+    bus:        Master<P, C>,
+    sensor:     Sht20,
+    calib:      CalibrationSet,
+    servo_cal:  ServoCal,
+    controller: Controller,
+    setpoint:   SetpointGen,
+    interlocks: InterlockTable,
+    lifecycle:  Lifecycle,
+    /// Plan POST + hasil cek config saat boot (None = POST nonaktif).
+    post_plan:  Option<(PostPlan, Outcome)>,
+    post:       Option<Post>,
+    time:       TimeSync,
+    /// Warm restart berturut-turut (ikut di snapshot).
+    restarts:   u32,
+    /// Restart cepat berturut-turut (`Snapshot::quick`).
+    quick:      u32,
+    cmd:        ActuatorCmd,
+    fan:        F,
+    servo:      S,
+    clock:      C,
+    /// Satu job: polling sensor + kontrol tiap `POLL_PERIOD_MS`.
+    sched:      Scheduler<1>,
+}
+
+impl<P, F, S, C> App<P, F, S, C>
+where
+    P: ModbusPort,
+    F: DigitalOutput,
+    S: Servo,
+    C: Clock + Clone,
+{
+    /// Output langsung diset ke kondisi awal (servo 0°, kipas OFF) lalu masuk
+    /// SELF-TEST. Setelah sensor terbaca, RUN otomatis (seperti firmware).
+    pub fn new(port: P, sid: u8, mut fan: F, mut servo: S, clock: C) -> Self {
+        let controller = Controller::new();
+        apply(&controller.cmd(), &ServoCal::DEFAULT, &mut fan, &mut servo);
+        let mut lifecycle = Lifecycle::new(true);
+        lifecycle.boot(clock.now_ms());
+        let mut sched = Scheduler::new(clock.now_ms());
+        sched.register("poll", POLL_PERIOD_MS, 0).expect("Scheduler<1> kosong, periode valid");
+        Self {
+            bus: Master::new(port, clock.clone()),
+            sensor: Sht20::new(sid),
+            calib: CalibrationSet::IDENTITY,
+            servo_cal: ServoCal::DEFAULT,
+            cmd: controller.cmd(),
+            controller,
+            setpoint: SetpointGen::default(),
+            interlocks: InterlockTable::defaults(),
+            lifecycle,
+            post_plan: None,
+            post: None,
+            time: TimeSync::new(),
+            restarts: 0,
+            quick: 0,
+            fan,
+            servo,
+            clock,
+            sched,
+        }
+    }
+
+    /// Panggil sesering mungkin; polling sensor hanya jalan tiap `POLL_PERIOD_MS`.
+    pub async fn step(&mut self) -> Option<Sample> {
+        let now = self.clock.now_ms();
+        self.sched.poll(now)?;
+
+        let raw = self.sensor.read(&mut self.bus).await;
+        let m = self.calib.apply(&raw);
+        let post_cmd = self.step_post(&m, now);
+        self.lifecycle.update(&m, now);
+        self.controller.set_setpoint(self.setpoint.update(now));
+        // Tanpa RH valid controller menahan output terakhir
+        let run = self.controller.update(&m).unwrap_or(self.controller.cmd());
+        // Output fase (aman / purge / POST) lalu interlock terakhir: aturan keselamatan selalu menang
+        let phase_out = post_cmd.unwrap_or(self.lifecycle.outputs(run));
+        self.cmd = self.interlocks.evaluate(&(&m).into(), phase_out);
+        apply(&self.cmd, &self.servo_cal, &mut self.fan, &mut self.servo);
+
+        Some(Sample {
+            t_ms: now,
+            epoch_ms: self.time.epoch_ms(now),
+            sync: self.time.quality(now),
+            raw,
+            measurement: m,
+            setpoint: self.controller.setpoint(),
+            cmd: self.cmd,
+            phase: self.lifecycle.phase(),
+        })
+    }
+
+    /// Perintah operator; output fase baru berlaku di `step` berikutnya.
+    /// `reset` menjalankan POST lagi (kalau aktif).
+    pub fn command(&mut self, cmd: Command) -> Result<Transition, Rejected> {
+        let t = self.lifecycle.command(cmd, self.clock.now_ms())?;
+        if t.to == Phase::SelfTest { self.start_post(); }
+        Ok(t)
+    }
+
+    /// Aktifkan POST di SELF-TEST sekarang dan tiap `reset`. `config` = hasil
+    /// validasi config saat boot.
+    pub fn enable_post(&mut self, plan: PostPlan, config: Outcome) {
+        self.post_plan = Some((plan, config));
+        if self.lifecycle.phase() == Phase::SelfTest { self.start_post(); }
+    }
+
+    /// Hasil POST terakhir (sedang berjalan atau selesai).
+    pub fn post_report(&self) -> Option<&Report> {
+        self.post.as_ref().map(|p| p.report())
+    }
+
+    fn start_post(&mut self) {
+        let Some((plan, config)) = self.post_plan else { return };
+        self.post = Some(Post::new(plan, config, &self.servo_cal, self.clock.now_ms()));
+        self.lifecycle.set_post(true);
+    }
+
+    /// Output POST selama berjalan; saat selesai, check kritis yang gagal → FAULT.
+    fn step_post(&mut self, m: &Measurement, now: u64) -> Option<ActuatorCmd> {
+        if !self.lifecycle.post_running() { return None; }
+        let post = self.post.as_mut()?;
+        // Simulasi tidak punya input feedback kipas
+        let cmd = post.step(m, None, now);
+        if cmd.is_none() {
+            self.lifecycle.set_post(false);
+            if let Some(check) = post.report().blocking(post.plan()) {
+                self.lifecycle.raise(FaultCause::SelfTest(check), now);
+            }
+        }
+        cmd
+    }
+
+    /// State untuk warm restart; firmware menyimpannya tiap putaran kontrol.
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            phase: self.lifecycle.phase(),
+            cmd: self.cmd,
+            controller: self.controller.cmd(),
+            setpoint: self.setpoint,
+            uptime_ms: self.clock.now_ms(),
+            restarts: self.restarts,
+            quick: self.quick,
+        }
+    }
+
+    /// Pulihkan setelah warm restart: output terakhir langsung di-drive lagi,
+    /// fase STANDBY/RUN dilanjutkan tanpa SELF-TEST/POST. Fase lain: hanya
+    /// setpoint & controller yang dipulihkan. Panggil sebelum `step` pertama;
+    /// snapshot dengan `boot_loop()` jangan diteruskan ke sini (cold start).
+    pub fn warm_start(&mut self, s: &Snapshot) {
+        let now = self.clock.now_ms();
+        let mut lc = Lifecycle::new(true);
+        lc.set_park(self.servo_cal.park_deg);
+        if lc.warm_start(s.phase, now).is_some() {
+            self.lifecycle = lc;
+            self.cmd = s.cmd;
+            apply(&self.cmd, &self.servo_cal, &mut self.fan, &mut self.servo);
+        }
+        self.controller.restore(s.controller);
+        self.setpoint = s.setpoint;
+        self.restarts = s.restarts + 1;
+        self.quick = s.next_quick();
+    }
+
+    /// Pesan sync dari host (epoch ms).
+    pub fn sync_time(&mut self, epoch_ms: u64) -> SyncReport {
+        self.time.sync(epoch_ms, self.clock.now_ms())
+    }
+
+    pub fn calibration(&self) -> &CalibrationSet {
+        &self.calib
+    }
+
+    pub fn calibration_mut(&mut self) -> &mut CalibrationSet {
+        &mut self.calib
+    }
+
+    pub fn servo_cal(&self) -> &ServoCal {
+        &self.servo_cal
+    }
+
+    /// Kalibrasi servo baru (harus lolos `validate`); park berlaku di fase aman.
+    pub fn set_servo_cal(&mut self, cal: ServoCal) -> Result<(), ServoCalError> {
+        cal.validate()?;
+        self.servo_cal = cal;
+        self.lifecycle.set_park(cal.park_deg);
+        Ok(())
+    }
+
+    pub fn setpoint(&self) -> &SetpointGen {
+        &self.setpoint
+    }
+
+    /// Target manual, laju ramp, dan profil setpoint.
+    pub fn setpoint_mut(&mut self) -> &mut SetpointGen {
+        &mut self.setpoint
+    }
+
+    pub fn lifecycle(&self) -> &Lifecycle {
+        &self.lifecycle
+    }
+
+    /// Output akhir (setelah interlock).
+    pub fn cmd(&self) -> ActuatorCmd {
+        self.cmd
+    }
+
+    pub fn interlocks(&self) -> &InterlockTable {
+        &self.interlocks
+    }
+
+    pub fn interlocks_mut(&mut self) -> &mut InterlockTable {
+        &mut self.interlocks
+    }
+
+    pub fn bus_diag(&self) -> &BusDiagnostics {
+        self.bus.diag()
+    }
+
+    /// Jadwal polling (counter rilis & overrun).
+    pub fn sched(&self) -> &Scheduler<1> {
+        &self.sched
+    }
+}
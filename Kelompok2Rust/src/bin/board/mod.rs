@@ -0,0 +1,326 @@
+//! Implementasi trait `hello_rust::hal` untuk ESP32-S3.
+
+use embassy_time::{with_timeout, Duration, Instant, Timer};
+use esp_hal::{
+    analog::adc::{Adc, AdcConfig, AdcPin, Attenuation},
+    gpio::{Input, Output},
+    i2c::master::I2c,
+    peripherals::{ADC1, GPIO17, GPIO18, GPIO6, UART1},
+    rtc_cntl::SocResetReason,
+    system::reset_reason,
+    uart::{Config as UartConfig, RxConfig, Uart, UartRx},
+    Async, Blocking,
+};
+use esp_bootloader_esp_idf::partitions;
+use esp_storage::FlashStorage;
+use hello_rust::config::ConfigStore;
+use hello_rust::hal::{Clock, ModbusPort, PortError, RawInput, RawOutput, Servo, SetBaud};
+use hello_rust::retain::{Snapshot, RETAIN_LEN};
+use hello_rust::rs485::DirectionConfig;
+use hello_rust::servo::SERVO_MIN_US;
+use hello_rust::store::FlashSpill;
+
+use crate::tasks::{REMOTE_DI, REMOTE_DO};
+
+const RESPONSE_TIMEOUT: Duration = Duration::from_millis(300); // tunggu byte pertama
+const INTERBYTE_GAP:    Duration = Duration::from_millis(20);  // jeda antar byte -> end-of-frame
+
+/// Pin DE/RE transceiver manual beserta polaritas & guard time-nya.
+pub struct DirectionPin {
+    pin:      Output<'static>,
+    cfg:      DirectionConfig,
+    guard_us: u32,
+}
+
+impl DirectionPin {
+    /// Pin langsung diset ke mode receive.
+    pub fn new(mut pin: Output<'static>, cfg: DirectionConfig, baud: u32) -> Self {
+        pin.set_level(cfg.level(false).into());
+        Self { pin, cfg, guard_us: cfg.guard_us_for(baud) }
+    }
+
+    fn transmit(&mut self, tx: bool) {
+        self.pin.set_level(self.cfg.level(tx).into());
+    }
+}
+
+/// Jam monoton dari time driver Embassy (resolusi 1 µs).
+#[derive(Clone, Copy)]
+pub struct EmbassyClock;
+
+impl Clock for EmbassyClock {
+    fn now_us(&self) -> u64 {
+        Instant::now().as_micros()
+    }
+}
+
+/// Bahan `Rs485Port`. UART async tidak `Send`, jadi port baru dirakit di
+/// dalam task yang memakainya (bisa di executor prioritas tinggi).
+pub struct Rs485Pins {
+    pub uart: UART1<'static>,
+    pub tx:   GPIO17<'static>,
+    pub rx:   GPIO18<'static>,
+    pub baud: u32,
+    pub de:   Option<DirectionPin>,
+}
+
+impl Rs485Pins {
+    pub fn into_port(self) -> Rs485Port {
+        let uart = Uart::new(self.uart, UartConfig::default().with_baudrate(self.baud))
+            .expect("UART1 init failed")
+            .with_tx(self.tx)
+            .with_rx(self.rx)
+            .into_async();
+        Rs485Port { uart, de: self.de }
+    }
+
+    /// Hanya dengar (mode sniffer): TX tidak dipasang, DE tetap di level
+    /// receive. RX timeout UART dibuat < t3.5 supaya satu chunk tidak
+    /// melewati batas frame.
+    pub fn into_listener(self) -> Rs485Listener {
+        let cfg = UartConfig::default()
+            .with_baudrate(self.baud)
+            .with_rx(RxConfig::default().with_timeout(LISTEN_RX_TIMEOUT_SYMBOLS));
+        let rx = Uart::new(self.uart, cfg)
+            .expect("UART1 init failed")
+            .with_rx(self.rx)
+            .into_async()
+            .split()
+            .0;
+        Rs485Listener { rx, _de: self.de }
+    }
+}
+
+// Dalam waktu karakter; t3.5 = 3.5 karakter
+pub const LISTEN_RX_TIMEOUT_SYMBOLS: u8 = 2;
+
+pub struct Rs485Listener {
+    pub rx: UartRx<'static, Async>,
+    // Dipegang supaya pin tetap di level receive
+    _de:    Option<DirectionPin>,
+}
+
+/// UART async ke transceiver RS485. Tanpa `DirectionPin` = modul auto-direction.
+pub struct Rs485Port {
+    uart: Uart<'static, Async>,
+    de:   Option<DirectionPin>,
+}
+
+async fn send(uart: &mut Uart<'static, Async>, request: &[u8]) -> Result<(), PortError> {
+    uart.write_async(request).await.map_err(|_| PortError::Io)?;
+    // flush_async baru selesai saat UART lapor TX done (stop bit terakhir keluar)
+    uart.flush_async().await.map_err(|_| PortError::Io)
+}
+
+impl ModbusPort for Rs485Port {
+    async fn transact(&mut self, request: &[u8], response: &mut [u8]) -> Result<usize, PortError> {
+        // Buang sisa byte (reply telat / noise) dari transaksi sebelumnya
+        let mut junk = [0u8; 16];
+        while matches!(self.uart.read_buffered(&mut junk), Ok(n) if n > 0) {}
+
+        match self.de.as_mut() {
+            Some(de) => {
+                de.transmit(true);
+                let sent = send(&mut self.uart, request).await;
+                if de.guard_us > 0 { Timer::after_micros(de.guard_us as u64).await; }
+                de.transmit(false);
+                sent?;
+            }
+            None => send(&mut self.uart, request).await?,
+        }
+
+        // Kumpulkan reply sampai bus diam selama INTERBYTE_GAP (interrupt-driven)
+        let mut n = 0usize;
+        while n < response.len() {
+            let wait = if n == 0 { RESPONSE_TIMEOUT } else { INTERBYTE_GAP };
+            match with_timeout(wait, self.uart.read_async(&mut response[n..])).await {
+                Ok(Ok(k)) => n += k,
+                Ok(Err(_)) => return Err(PortError::Io),
+                Err(_) => break,
+            }
+        }
+        if n == 0 { Err(PortError::Timeout) } else { Ok(n) }
+    }
+}
+
+impl SetBaud for Rs485Port {
+    fn set_baud(&mut self, baud: u32) -> Result<(), PortError> {
+        self.uart.apply_config(&UartConfig::default().with_baudrate(baud)).map_err(|_| PortError::Io)?;
+        if let Some(de) = self.de.as_mut() { de.guard_us = de.cfg.guard_us_for(baud); }
+        Ok(())
+    }
+}
+
+/// GPIO sebagai pin output mentah untuk `hello_rust::dout` (polaritas diurus di sana).
+pub struct GpioOut(pub Output<'static>);
+
+impl RawOutput for GpioOut {
+    fn set_high(&mut self, high: bool) {
+        if high { self.0.set_high(); } else { self.0.set_low(); }
+    }
+
+    fn is_set_high(&self) -> bool {
+        self.0.is_set_high()
+    }
+}
+
+/// GPIO sebagai pin input mentah untuk `hello_rust::din`.
+pub struct GpioIn(pub Input<'static>);
+
+impl RawInput for GpioIn {
+    fn is_high(&self) -> bool {
+        self.0.is_high()
+    }
+}
+
+/// I2C0 untuk SHT2x native (`SensorSource::I2c`). Blocking: transaksi SHT2x
+/// hanya beberapa byte, waktu ukur ditunggu dengan Timer async.
+pub type SensorI2c = I2c<'static, Blocking>;
+
+/// Pin output kanal DO: GPIO di board atau coil remote (image `REMOTE_DO`,
+/// disinkronkan task sensor). Readback remote = level coil terakhir yang terbaca.
+pub enum IoOut {
+    Gpio(GpioOut),
+    Remote(usize),
+}
+
+impl RawOutput for IoOut {
+    fn set_high(&mut self, high: bool) {
+        match self {
+            IoOut::Gpio(pin) => pin.set_high(high),
+            IoOut::Remote(i) => REMOTE_DO.lock(|r| {
+                let mut img = r.get();
+                img[*i].desired = high;
+                r.set(img);
+            }),
+        }
+    }
+
+    fn is_set_high(&self) -> bool {
+        match self {
+            IoOut::Gpio(pin) => pin.is_set_high(),
+            IoOut::Remote(i) => REMOTE_DO.lock(|r| r.get()[*i].actual).unwrap_or(false),
+        }
+    }
+}
+
+/// Pin input kanal DI: GPIO di board atau discrete input remote (image `REMOTE_DI`).
+/// Sebelum terbaca pertama kali, dan setelah modul tidak menjawab `INPUT_STALE_FAILS`
+/// kali, input remote di level tidak aktif (`idle_high`): feedback dari output
+/// yang ON lalu terdeteksi tidak sesuai → FAULT lewat monitor feedback.
+pub enum IoIn {
+    Gpio(GpioIn),
+    Remote { idx: usize, idle_high: bool },
+}
+
+impl RawInput for IoIn {
+    fn is_high(&self) -> bool {
+        match self {
+            IoIn::Gpio(pin) => pin.is_high(),
+            IoIn::Remote { idx, idle_high } => REMOTE_DI.lock(|r| r.get()[*idx].level).unwrap_or(*idle_high),
+        }
+    }
+}
+
+/// Servo bit-bang: `set_pulse_us` hanya menyimpan lebar pulsa,
+/// `pulse()` dipanggil tiap periode oleh task actuator.
+pub struct ServoPwm {
+    pin:      Output<'static>,
+    pulse_us: u32,
+}
+
+impl ServoPwm {
+    pub fn new(pin: Output<'static>) -> Self {
+        Self { pin, pulse_us: SERVO_MIN_US }
+    }
+
+    /// HIGH selama pulse µs lalu LOW (sisa periode diatur pemanggil).
+    pub async fn pulse(&mut self) {
+        self.pin.set_high();
+        Timer::after_micros(self.pulse_us as u64).await;
+        self.pin.set_low();
+    }
+}
+
+impl Servo for ServoPwm {
+    fn set_pulse_us(&mut self, us: u32) {
+        self.pulse_us = us;
+    }
+}
+
+/// Batas tunggu satu konversi ADC feedback (normalnya ±puluhan µs).
+const ADC_TIMEOUT: Duration = Duration::from_micros(500);
+
+/// Potensiometer servo di ADC1 (GPIO6, atenuasi 11 dB ≈ 0..3.1 V), one-shot mentah 12-bit.
+pub struct ServoFeedback {
+    adc: Adc<'static, ADC1<'static>, Blocking>,
+    pin: AdcPin<GPIO6<'static>, ADC1<'static>>,
+}
+
+impl ServoFeedback {
+    pub fn new(adc: ADC1<'static>, pin: GPIO6<'static>) -> Self {
+        let mut cfg = AdcConfig::new();
+        let pin = cfg.enable_pin(pin, Attenuation::_11dB);
+        Self { adc: Adc::new(adc, cfg), pin }
+    }
+
+    /// Satu konversi (±puluhan µs, busy-wait). None kalau belum selesai dalam
+    /// `ADC_TIMEOUT`: task actuator tidak boleh tertahan di sini.
+    pub fn read(&mut self) -> Option<u16> {
+        let start = Instant::now();
+        loop {
+            if let Ok(raw) = self.adc.read_oneshot(&mut self.pin) { return Some(raw); }
+            if start.elapsed() >= ADC_TIMEOUT { return None; }
+        }
+    }
+}
+
+/// Partisi data berlabel `label` di tabel partisi flash: `(flash, offset, len)`.
+/// None kalau tabel tidak terbaca atau partisi tidak ada (firmware di-flash tanpa partitions.csv).
+fn data_partition(label: &str) -> Option<(FlashStorage, u32, u32)> {
+    let mut flash = FlashStorage::new();
+    let mut buf = [0u8; partitions::PARTITION_TABLE_MAX_LEN];
+    let table = partitions::read_partition_table(&mut flash, &mut buf).ok()?;
+    // Cocokkan lewat label + raw type: sub-type custom tidak dikenal oleh `partition_type()`
+    let (offset, len) = (0..table.len())
+        .filter_map(|i| table.get_partition(i).ok())
+        .find(|p| p.raw_type() == 1 && p.label_as_str() == label)
+        .map(|p| (p.offset(), p.len()))?;
+    Some((flash, offset, len))
+}
+
+/// Partisi `label` sebagai luberan store-and-forward.
+pub fn flash_spill(label: &str) -> Option<FlashSpill<FlashStorage>> {
+    let (flash, offset, len) = data_partition(label)?;
+    FlashSpill::new(flash, offset, len)
+}
+
+/// Partisi `label` sebagai penyimpanan `Config` A/B.
+pub fn config_store(label: &str) -> Option<ConfigStore<FlashStorage>> {
+    let (flash, offset, len) = data_partition(label)?;
+    ConfigStore::new(flash, offset, len)
+}
+
+/// Snapshot warm restart di RTC fast RAM: bertahan melewati reset watchdog /
+/// software, di-nol-kan setelah power-on.
+#[esp_hal::ram(rtc_fast, persistent)]
+static mut RETAINED: [u8; RETAIN_LEN] = [0; RETAIN_LEN];
+
+/// Snapshot sebelum reset, kalau boot ini warm restart dan isinya valid.
+/// Power-on dan brown-out selalu cold (tegangan turun: RTC RAM tidak bisa dipercaya).
+/// Boot loop (`Snapshot::boot_loop`) dicek pemanggil supaya bisa dilaporkan.
+pub fn retained() -> Option<Snapshot> {
+    if matches!(reset_reason(), None | Some(SocResetReason::ChipPowerOn | SocResetReason::SysBrownOut)) { return None; }
+    // SAFETY: hanya dibaca sekali di main sebelum task mana pun jalan
+    let buf = unsafe { core::ptr::read_volatile(&raw const RETAINED) };
+    Snapshot::decode(&buf)
+}
+
+/// Tulis snapshot (dipanggil task control tiap putaran). Reset di tengah
+/// penulisan menghasilkan blob dengan CRC salah → cold start.
+pub fn retain(s: &Snapshot) {
+    let mut buf = [0u8; RETAIN_LEN];
+    s.encode(&mut buf);
+    // SAFETY: satu-satunya penulis adalah task control
+    unsafe { core::ptr::write_volatile(&raw mut RETAINED, buf) };
+}
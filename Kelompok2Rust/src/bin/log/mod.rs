@@ -0,0 +1,71 @@
+//! Log diagnostik berlevel. Data proses (frame RH/T, `[rec]`) dan balasan
+//! konsol tetap `println!` tanpa filter; yang lewat sini hanya diagnostik.
+//!
+//! Filter global + per modul (`hello_rust::log::Filter`) diubah dari konsol
+//! (`log ...`). Backend: teks lewat esp-println, atau defmt dengan feature
+//! `defmt` (monitor: `espflash monitor --log-format defmt`).
+
+use core::cell::Cell;
+use core::fmt;
+
+use embassy_sync::blocking_mutex::{raw::CriticalSectionRawMutex, Mutex};
+use hello_rust::log::{module_name, Filter, Level};
+
+/// Nama modul yang bisa diberi override dari konsol (segmen terakhir `module_path!()`).
+pub const MODULES: [&str; 11] = [
+    "hello_rust", "board", "sensor", "control", "actuator", "inputs", "console", "telemetry", "reader", "sniffer", "probe",
+];
+
+pub static LOG_FILTER: Mutex<CriticalSectionRawMutex, Cell<Filter>> = Mutex::new(Cell::new(Filter::new(Level::Info)));
+
+pub fn enabled(path: &str, level: Level) -> bool {
+    LOG_FILTER.lock(|f| f.get().enabled(path, level))
+}
+
+#[cfg(not(feature = "defmt"))]
+pub fn emit(level: Level, path: &str, args: fmt::Arguments<'_>) {
+    let tag = match level {
+        Level::Error => "❌ E",
+        Level::Warn  => "⚠️  W",
+        Level::Info  => "ℹ️  I",
+        Level::Debug => "🐞 D",
+        Level::Trace => "·  T",
+    };
+    esp_println::println!("{} [{}] {}", tag, module_name(path), args);
+}
+
+#[cfg(feature = "defmt")]
+defmt::timestamp!("{=u64:ms}", embassy_time::Instant::now().as_millis());
+
+// Format string tetap di-render di target (Display2Format); defmt hanya
+// membawa level, timestamp, dan modul.
+#[cfg(feature = "defmt")]
+pub fn emit(level: Level, path: &str, args: fmt::Arguments<'_>) {
+    let m = module_name(path);
+    let msg = defmt::Display2Format(&args);
+    match level {
+        Level::Error => defmt::error!("[{=str}] {}", m, msg),
+        Level::Warn  => defmt::warn!("[{=str}] {}", m, msg),
+        Level::Info  => defmt::info!("[{=str}] {}", m, msg),
+        Level::Debug => defmt::debug!("[{=str}] {}", m, msg),
+        Level::Trace => defmt::trace!("[{=str}] {}", m, msg),
+    }
+}
+
+macro_rules! log_enabled {
+    ($lvl:expr) => { $crate::log::enabled(module_path!(), $lvl) };
+}
+
+macro_rules! log {
+    ($lvl:expr, $($arg:tt)*) => {
+        if $crate::log::enabled(module_path!(), $lvl) {
+            $crate::log::emit($lvl, module_path!(), format_args!($($arg)*));
+        }
+    };
+}
+
+macro_rules! error { ($($arg:tt)*) => { log!(hello_rust::log::Level::Error, $($arg)*) }; }
+macro_rules! warn  { ($($arg:tt)*) => { log!(hello_rust::log::Level::Warn,  $($arg)*) }; }
+macro_rules! info  { ($($arg:tt)*) => { log!(hello_rust::log::Level::Info,  $($arg)*) }; }
+macro_rules! debug { ($($arg:tt)*) => { log!(hello_rust::log::Level::Debug, $($arg)*) }; }
+macro_rules! trace { ($($arg:tt)*) => { log!(hello_rust::log::Level::Trace, $($arg)*) }; }
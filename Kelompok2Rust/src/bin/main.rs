@@ -2,196 +2,200 @@
 #![no_main]
 
 use panic_halt as _;
+use embassy_executor::Spawner;
 use esp_hal::{
     Config,
     uart::{Uart, Config as UartConfig},
-    time::{Instant, Duration},
-    gpio::{Output, Level, OutputConfig}
+    i2c::master::{I2c, Config as I2cConfig},
+    time::Rate,
+    gpio::{AnyPin, Input, InputConfig, Output, Level, OutputConfig, Pull},
+    interrupt::{Priority, software::SoftwareInterruptControl},
+    timer::timg::TimerGroup,
 };
+use esp_hal_embassy::InterruptExecutor;
 use esp_println::println;
+use static_cell::StaticCell;
+
+#[macro_use]
+mod log;
+mod board;
+mod tasks;
+
+use board::{DirectionPin, GpioIn, GpioOut, IoIn, IoOut, Rs485Pins, ServoFeedback, ServoPwm};
+use hello_rust::config::ConfigError;
+use hello_rust::din::DiChannel;
+use hello_rust::dout::{DoBank, DoChannel, DoConfig};
+use hello_rust::post::Outcome;
+use hello_rust::remote::IoPoint;
+use hello_rust::retain::{MAX_QUICK_RESTARTS, QUICK_RESTART_MS};
+use hello_rust::store::{SampleBuffer, Spill};
+use tasks::{BAUD, SID, DI_CHANNELS, DO_CHANNELS, RS485_DIR, RS485_MANUAL_DIR, SPILL_PARTITION, SPILL_TO_FLASH, STORE};
+use tasks::{apply_config, Mode, SensorSource, ACTUATOR_CMD, CONFIG_PARTITION, CONFIG_STORE, FAN_DO, POST_CONFIG, MODE, SENSOR_SOURCE, SERVO_FB, SERVO_FEEDBACK};
 
 esp_bootloader_esp_idf::esp_app_desc!();
 
-const BAUD: u32 = 9_600;
-const SID:  u8  = 1;
+// Baudrate konsol (UART0, sama dengan serial monitor / script Python)
+const CONSOLE_BAUD: u32 = 115_200;
 
-// Timing Modbus (kasar)
-const TURNAROUND_SPINS: u32 = 50_000;
-const TIMEOUT_SPINS:    u32 = 200_000;
+// Executor prioritas tinggi untuk pulsa servo dan bus RS485, supaya println
+// tidak menggeser timing 50 Hz maupun pelepasan DE
+static HIGH_PRIO_EXECUTOR: StaticCell<InterruptExecutor<2>> = StaticCell::new();
 
-// Servo timing
-const SERVO_PERIOD_US: u32 = 20_000;   // 50 Hz = 20 ms
-const SERVO_MIN_US:    u32 = 500;      // ~0°
-const SERVO_MAX_US:    u32 = 2500;     // ~120° (tuning kalau perlu)
-
-// ===== [TAMBAHAN] Konfigurasi relay kipas =====
-const RELAY_ACTIVE_LOW: bool = true; // ubah ke false jika modul relay aktif-HIGH
-
-// Sudut target (0/60/120)
-fn deg_to_pulse_us(deg: i32) -> u32 {
-    let d = if deg < 0 { 0 } else if deg > 120 { 120 } else { deg } as u32;
-    // linear: 0° -> MIN, 120° -> MAX
-    SERVO_MIN_US + (d * (SERVO_MAX_US - SERVO_MIN_US) / 120)
-}
-
-#[esp_hal::main]
-fn main() -> ! {
+#[esp_hal_embassy::main]
+async fn main(spawner: Spawner) {
     let p = esp_hal::init(Config::default());
 
-    // UART1: TX=GPIO17, RX=GPIO18 (ubah sesuai wiring)
-    let mut uart = Uart::new(p.UART1, UartConfig::default().with_baudrate(BAUD))
-        .expect("UART1 init failed")
-        .with_tx(p.GPIO17)
-        .with_rx(p.GPIO18);
-
-    // GPIO4 sebagai pin servo (output)
-    let mut servo = Output::new(p.GPIO4, Level::Low, OutputConfig::default());
-
-    // ===== [TAMBAHAN] GPIO10 sebagai relay kipas (start OFF) =====
-    let mut fan = Output::new(
-        p.GPIO10,
-        if RELAY_ACTIVE_LOW { Level::High } else { Level::Low }, // OFF awal
-        OutputConfig::default()
-    );
-
-    println!("\n=== SHT20 (RS485) + SERVO @GPIO4 + RELAY KIPAS @GPIO10 ===");
-    println!("Baudrate: {} bps | Slave ID: {}", BAUD, SID);
-    println!("-----------------------------------------------------------");
+    let timg0 = TimerGroup::new(p.TIMG0);
+    esp_hal_embassy::init(timg0.timer0);
+
+    // Warm restart (watchdog / software reset): output terakhir dari RTC RAM.
+    // Reset berulang tak lama setelah boot: cold start, jangan ulangi state yang sama
+    let retained = board::retained();
+    let warm = retained.filter(|s| !s.boot_loop());
+    let resume = warm.filter(|s| s.resumes()).map(|s| s.cmd);
+
+    // GPIO5 = DE/RE untuk transceiver manual (ubah sesuai wiring)
+    let de = RS485_MANUAL_DIR.then(|| {
+        let idle = Level::from(RS485_DIR.level(false));
+        DirectionPin::new(Output::new(p.GPIO5, idle, OutputConfig::default()), RS485_DIR, BAUD)
+    });
+
+    // UART1: TX=GPIO17, RX=GPIO18 (ubah sesuai wiring), mode async (interrupt)
+    let rs485 = Rs485Pins { uart: p.UART1, tx: p.GPIO17, rx: p.GPIO18, baud: BAUD, de };
+
+    // Mode diagnostik (cargo feature): hanya bus RS485, tanpa kontrol/aktuator/konsol
+    if MODE != Mode::Controller {
+        println!("\n=== SHT20 (RS485) mode {:?} | {} bps | slave {} | RS485 {} ===", MODE, BAUD, SID,
+            if RS485_MANUAL_DIR { "DE/RE manual @GPIO5" } else { "auto-direction" });
+        match MODE {
+            Mode::Probe => spawner.must_spawn(tasks::probe::probe_task(rs485)),
+            Mode::Sniffer => spawner.must_spawn(tasks::sniffer::sniffer_task(rs485)),
+            _ => spawner.must_spawn(tasks::reader::reader_task(rs485)),
+        }
+        return;
+    }
 
-    // state
-    let mut target_deg: i32 = 0;
-    let mut last_sensor_poll = Instant::now();
-
-    // ===== [TAMBAHAN] state kipas =====
-    let mut fan_on: bool = false;
-    
-    
-
-    loop {
-        // --------- 1) Baca sensor tiap ~1 detik ---------
-        if last_sensor_poll.elapsed() >= Duration::from_millis(1000) {
-            last_sensor_poll = Instant::now();
-
-            println!("\n[Polling Sensor] -----------------------------");
-
-            // ---------- BACA RH @ 0x0002 ----------
-            let mut req = [0u8; 8];
-            req[0] = SID;
-            req[1] = 0x04;
-            req[2..4].copy_from_slice(&0x0002u16.to_be_bytes()); // addr RH
-            req[4..6].copy_from_slice(&1u16.to_be_bytes());
-            let crc = crc16(&req[..6]);
-            req[6] = (crc & 0xFF) as u8;
-            req[7] = (crc >> 8) as u8;
-
-            let _ = uart.write(&req);
-            let _ = uart.flush();
-            short_spin(TURNAROUND_SPINS);
-
-            let mut rx = [0u8; 32];
-            let mut n = 0usize;
-            let mut spins = 0u32;
-            while spins < TIMEOUT_SPINS && n < rx.len() {
-                let mut b = [0u8; 1];
-                match uart.read(&mut b) {
-                    Ok(1) => { rx[n] = b[0]; n += 1; if n >= 7 { break; } }
-                    _ => { short_spin(1_000); spins += 1; }
-                }
-            }
+    // UART0 RX (GPIO44) untuk perintah konsol; TX tetap dipakai esp-println
+    let console_rx = Uart::new(p.UART0, UartConfig::default().with_baudrate(CONSOLE_BAUD))
+        .expect("UART0 init failed")
+        .with_rx(p.GPIO44)
+        .into_async()
+        .split()
+        .0;
+
+    // I2C0 untuk SHT2x native: SDA=GPIO8, SCL=GPIO9, 100 kHz (hanya kalau dipilih sebagai sumber)
+    let sensor_i2c = (SENSOR_SOURCE == SensorSource::I2c).then(|| {
+        I2c::new(p.I2C0, I2cConfig::default().with_frequency(Rate::from_khz(100)))
+            .expect("I2C0 init failed")
+            .with_sda(p.GPIO8)
+            .with_scl(p.GPIO9)
+    });
 
-            let mut rh_opt: Option<f32> = None;
-            if n >= 7 && (rx[1] & 0x80) == 0 && rx[2] == 2 && check_crc(&rx[..n]) {
-                let raw_rh = u16::from_be_bytes([rx[3], rx[4]]);
-                let rh = raw_rh as f32 / 10.0;
-                rh_opt = Some(rh);
-                println!("✅ RH = {:.1} %", rh);
-            } else {
-                println!("⚠️  No/invalid reply for 0x0002 (RH)");
+    // GPIO4 sebagai pin servo (output)
+    let servo = Output::new(p.GPIO4, Level::Low, OutputConfig::default());
+    // Wiper potensiometer servo di ADC1 @GPIO6 (opsional)
+    let servo_fb = SERVO_FEEDBACK.then(|| ServoFeedback::new(p.ADC1, p.GPIO6));
+
+    // Kanal output digital, urut sesuai `DO_CHANNELS`; pin dibuat langsung di level power-on
+    // (warm restart: kanal kipas langsung di state sebelum reset)
+    let do_channel = |i: usize, pin: AnyPin<'static>| {
+        let cfg = match resume {
+            Some(cmd) if DO_CHANNELS[i].name == FAN_DO => DoConfig { power_on: cmd.fan_on, ..DO_CHANNELS[i] },
+            _ => DO_CHANNELS[i],
+        };
+        let out = match cfg.point {
+            IoPoint::Gpio => IoOut::Gpio(GpioOut(Output::new(pin, Level::from(cfg.power_on_level()), OutputConfig::default()))),
+            IoPoint::Modbus { .. } => IoOut::Remote(i),
+        };
+        DoChannel::new(cfg, out)
+    };
+    let outputs = DoBank::new([
+        do_channel(0, p.GPIO10.into()), // fan
+    ]);
+
+    // Kanal input digital, urut sesuai `DI_CHANNELS`; pull-up untuk kontak ke GND
+    let di_channel = |i: usize, pin: AnyPin<'static>| {
+        let cfg = DI_CHANNELS[i];
+        let pull = if cfg.active_low { Pull::Up } else { Pull::Down };
+        let input = match cfg.point {
+            IoPoint::Gpio => IoIn::Gpio(GpioIn(Input::new(pin, InputConfig::default().with_pull(pull)))),
+            IoPoint::Modbus { .. } => IoIn::Remote { idx: i, idle_high: cfg.active_low },
+        };
+        DiChannel::new(cfg, input)
+    };
+    let inputs = [
+        di_channel(0, p.GPIO11.into()), // fan_run
+    ];
+
+    match DO_CHANNELS[0].point {
+        IoPoint::Gpio => println!("\n=== SHT20 (RS485) + SERVO @GPIO4 + RELAY KIPAS @GPIO10 ==="),
+        IoPoint::Modbus { sid, addr } => println!("\n=== SHT20 (RS485) + SERVO @GPIO4 + RELAY KIPAS @Modbus ID {} coil {} ===", sid, addr),
+    }
+    println!("Baudrate: {} bps | Slave ID: {} | RS485 {}", BAUD, SID,
+        if RS485_MANUAL_DIR { "DE/RE manual @GPIO5" } else { "auto-direction" });
+    if SENSOR_SOURCE == SensorSource::I2c { println!("Sensor: SHT2x I2C @GPIO8 (SDA) / GPIO9 (SCL), RS485 untuk I/O remote"); }
+    if SERVO_FEEDBACK { println!("Feedback servo: potensiometer @GPIO6 (ADC1)"); }
+    if SPILL_TO_FLASH {
+        match board::flash_spill(SPILL_PARTITION) {
+            Some(spill) => {
+                info!("Store-and-forward: RAM + flash '{}' ({} record, {} belum di-ack dari sebelum reset)",
+                    SPILL_PARTITION, spill.capacity(), spill.len());
+                STORE.lock(|s| *s.borrow_mut() = SampleBuffer::resume(Some(spill)));
             }
-
-            // ---------- (opsional) BACA T @ 0x0001 ----------
-            req[2..4].copy_from_slice(&0x0001u16.to_be_bytes());
-            let crc2 = crc16(&req[..6]);
-            req[6] = (crc2 & 0xFF) as u8;
-            req[7] = (crc2 >> 8) as u8;
-
-            let _ = uart.write(&req);
-            let _ = uart.flush();
-            short_spin(TURNAROUND_SPINS);
-
-            n = 0; spins = 0;
-            while spins < TIMEOUT_SPINS && n < rx.len() {
-                let mut b = [0u8; 1];
-                match uart.read(&mut b) {
-                    Ok(1) => { rx[n] = b[0]; n += 1; if n >= 7 { break; } }
-                    _ => { short_spin(1_000); spins += 1; }
+            None => warn!("Partisi '{}' tidak ditemukan, store-and-forward hanya RAM", SPILL_PARTITION),
+        }
+    }
+    // Konfigurasi persisten (kalibrasi) sebelum task sensor jalan
+    match board::config_store(CONFIG_PARTITION) {
+        Some(mut store) => {
+            match store.load() {
+                Ok(cfg) => {
+                    apply_config(&cfg);
+                    POST_CONFIG.lock(|c| c.set(Outcome::Pass));
+                    info!("Config: dimuat dari flash '{}' (gen {})", CONFIG_PARTITION, store.generation().unwrap_or(0));
                 }
-            }
-            if n >= 7 && (rx[1] & 0x80) == 0 && rx[2] == 2 && check_crc(&rx[..n]) {
-                let raw_t = u16::from_be_bytes([rx[3], rx[4]]);
-                println!("🌡️  T  = {:.1} °C", raw_t as f32 / 10.0);
-            } else {
-                println!("⚠️  No/invalid reply for 0x0001 (Temp)");
-            }
-
-            // --------- Update target_deg berdasar RH ---------
-            if let Some(rh) = rh_opt {
-                target_deg = if rh > 70.0 { 120 } else if rh < 60.0 { 0 } else { 60 };
-
-                // ===== [TAMBAHAN] Logika kipas dengan hysteresis =====
-                if rh > 80.0 { fan_on = true; }
-                if rh < 80.0 { fan_on = false; }
-
-                // ===== [TAMBAHAN] Tulis state ke pin relay =====
-                if RELAY_ACTIVE_LOW {
-                    if fan_on { fan.set_low(); } else { fan.set_high(); }
-                } else {
-                    if fan_on { fan.set_high(); } else { fan.set_low(); }
+                Err(ConfigError::Empty) => {
+                    POST_CONFIG.lock(|c| c.set(Outcome::Pass));
+                    info!("Config: belum ada, pakai default");
+                }
+                Err(e) => {
+                    POST_CONFIG.lock(|c| c.set(Outcome::Fail(match e {
+                        ConfigError::Io => "flash tidak terbaca",
+                        _ => "CRC/isi rusak, pakai default",
+                    })));
+                    warn!("Config: {} di '{}', pakai default", e, CONFIG_PARTITION);
                 }
-
-                println!("🔧 Servo target → {}°", target_deg);
-                println!("💨 Fan state     → {}", if fan_on { "ON" } else { "OFF" });
-                println!("-----------------------------------------------");
             }
+            CONFIG_STORE.lock(|s| *s.borrow_mut() = Some(store));
         }
-
-        // --------- 2) Satu periode PWM (50 Hz) untuk servo ---------
-        let pulse = deg_to_pulse_us(target_deg);
-        // HIGH selama pulse µs
-        servo.set_high();
-        sleep_us(pulse);
-        // LOW sisa periode
-        servo.set_low();
-        sleep_us(SERVO_PERIOD_US - pulse);
-        // Ulang loop → hasilnya 50 Hz stabil
+        None => warn!("Partisi '{}' tidak ditemukan, kalibrasi tidak persisten", CONFIG_PARTITION),
     }
-}
-
-// ===== Timing helpers =====
-fn sleep_us(us: u32) {
-    // pakai Instant + busy loop
-    let start = Instant::now();
-    let target = Duration::from_micros(us as u64);
-    while start.elapsed() < target {
-        core::hint::spin_loop();
+    if SERVO_FEEDBACK && SERVO_FB.lock(|c| c.get()).is_none() {
+        warn!("Feedback servo belum dikalibrasi ('servo fb cal 0 120'), stall tidak dievaluasi");
     }
-}
-
-fn short_spin(iter: u32) { for _ in 0..iter { core::hint::spin_loop(); } }
-
-// ===== CRC Modbus =====
-fn crc16(data: &[u8]) -> u16 {
-    let mut crc = 0xFFFFu16;
-    for &b in data {
-        crc ^= b as u16;
-        for _ in 0..8 {
-            crc = if (crc & 1) != 0 { (crc >> 1) ^ 0xA001 } else { crc >> 1 };
+    match warm {
+        Some(s) => {
+            warn!("Warm restart #{} setelah {} s jalan: fase {}{}", s.restarts + 1, s.uptime_ms / 1000, s.phase,
+                if s.resumes() { " dilanjutkan" } else { ", boot normal" });
+            if s.resumes() { info!("Output dipulihkan: servo {}° | kipas {}", s.cmd.servo_deg, if s.cmd.fan_on { "ON" } else { "OFF" }); }
         }
+        None if retained.is_some() => warn!("{} warm restart cepat berturut-turut (< {} s): cold start, output & mode dari default",
+            MAX_QUICK_RESTARTS + 1, QUICK_RESTART_MS / 1000),
+        None => info!("Cold start: output & mode dari default"),
     }
-    crc
-}
-fn check_crc(frame: &[u8]) -> bool {
-    if frame.len() < 3 { return false; }
-    let calc = crc16(&frame[..frame.len() - 2]);
-    frame[frame.len() - 2] == (calc & 0xFF) as u8 && frame[frame.len() - 1] == (calc >> 8) as u8
+    // Dibaca task actuator sebelum pulsa servo pertama
+    if let Some(cmd) = resume { ACTUATOR_CMD.signal(cmd); }
+    println!("Ketik 'help' di konsol untuk daftar perintah");
+    println!("-----------------------------------------------------------");
+
+    let sw_ints = SoftwareInterruptControl::new(p.SW_INTERRUPT);
+    let high_prio_executor = HIGH_PRIO_EXECUTOR.init(InterruptExecutor::new(sw_ints.software_interrupt2));
+    let high_prio = high_prio_executor.start(Priority::Priority3);
+    high_prio.must_spawn(tasks::actuator::actuator_task(ServoPwm::new(servo), servo_fb, outputs));
+    high_prio.must_spawn(tasks::sensor::sensor_task(rs485, sensor_i2c));
+
+    spawner.must_spawn(tasks::control::control_task(warm));
+    spawner.must_spawn(tasks::console::console_task(console_rx));
+    spawner.must_spawn(tasks::inputs::inputs_task(inputs));
+    spawner.must_spawn(tasks::telemetry::telemetry_task());
 }
@@ -0,0 +1,42 @@
+//! Simulasi di host (Linux/Windows): aplikasi kontrol yang sama dengan
+//! firmware, dijalankan terhadap SHT20 simulasi dan model ruangan.
+//!
+//!   cargo +stable run --target <host-triple> --no-default-features --features sim --bin sim -- [detik] [uap %RH/s]
+
+use std::env;
+
+use hello_rust::app::App;
+use hello_rust::sim::{block_on, Room, SimWorld};
+
+const SID:     u8  = 1;
+const TICK_MS: u64 = 100;
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let duration_s: u64 = args.get(1).and_then(|s| s.parse().ok()).unwrap_or(600);
+    let moisture:   f32 = args.get(2).and_then(|s| s.parse().ok()).unwrap_or(0.5);
+
+    let world = SimWorld::new(Room { moisture_in: moisture, ..Room::default() });
+    let mut app = App::new(world.sensor(SID), SID, world.fan(), world.servo(), world.clock());
+
+    println!("=== SIMULASI: SHT20 + SERVO + RELAY KIPAS ({} s, uap {} %RH/s) ===", duration_s, moisture);
+
+    while world.now_ms() <= duration_s * 1000 {
+        if let Some(s) = block_on(app.step()) {
+            let rh = s.measurement.rh.map_or(f32::NAN, |v| v);
+            let t  = s.measurement.temp.map_or(f32::NAN, |v| v);
+            println!("[{:>5} s] RH = {:.1} % | T = {:.1} °C | SP {:.1} % | servo {:>3}° | fan {:<3} | {}",
+                s.t_ms / 1000, rh, t, s.setpoint, s.cmd.servo_deg, if s.cmd.fan_on { "ON" } else { "OFF" }, s.phase);
+        }
+        world.advance(TICK_MS);
+    }
+
+    for (sid, c) in app.bus_diag().iter() {
+        println!("[diag] slave {}: req {} ok {} timeout {} crc {} exc {} badid {} short {} other {}",
+            sid, c.requests, c.ok, c.timeouts, c.crc_errors, c.exceptions,
+            c.unexpected_id, c.short_frames, c.other);
+    }
+    for (_, name, period_ms, _, st) in app.sched().iter() {
+        println!("[sched] {} tiap {} ms: jalan {} overrun {} (lompat {})", name, period_ms, st.runs, st.overruns, st.skipped);
+    }
+}
@@ -0,0 +1,81 @@
+//! Task output: pulsa servo 50 Hz + kanal output digital (relay). Jalan di
+//! InterruptExecutor (prioritas tinggi) supaya lebar pulsa tidak terganggu task lain.
+//! Kalau ada feedback potensiometer, posisi servo dibaca tiap periode dan dibandingkan
+//! dengan perintah (stall → event + FAULT).
+
+use embassy_time::{Duration, Instant, Ticker};
+use hello_rust::control::ActuatorCmd;
+use hello_rust::dout::DoBank;
+use hello_rust::hal::Servo;
+use hello_rust::lifecycle::{FaultCause, SAFE_OUTPUTS};
+use hello_rust::servo::SERVO_PERIOD_US;
+use hello_rust::servo_fb::{PositionMonitor, StallEvent};
+
+use crate::board::{IoOut, ServoFeedback, ServoPwm};
+use super::{publish, DoRequest, TelemetryEvent, ACTUATOR_CMD, DO_COUNT, DO_REQUEST, DO_STATE, FAN_DO, LIFECYCLE, SERVO_CAL, SERVO_FB, SERVO_POS, SERVO_RAW, SERVO_STALL, SERVO_STALL_FAULT, SERVO_TIMING};
+
+#[embassy_executor::task]
+pub async fn actuator_task(mut servo: ServoPwm, mut feedback: Option<ServoFeedback>, mut outputs: DoBank<IoOut, DO_COUNT>) {
+    let fan = outputs.find(FAN_DO);
+    // Sampai perintah pertama: servo di posisi park (warm restart: perintah dari main sudah menunggu)
+    let park = ActuatorCmd { servo_deg: SERVO_CAL.lock(|c| c.get()).park_deg, ..SAFE_OUTPUTS };
+    let mut cmd = ACTUATOR_CMD.try_take().unwrap_or(park);
+    write_fan(&cmd, fan, &mut outputs);
+    let mut ticker = Ticker::every(Duration::from_micros(SERVO_PERIOD_US as u64));
+    let mut last_pulse: Option<Instant> = None;
+    let mut monitor = PositionMonitor::new(SERVO_STALL);
+
+    loop {
+        if let Some(new) = ACTUATOR_CMD.try_take() {
+            cmd = new;
+            write_fan(&cmd, fan, &mut outputs);
+        }
+        let now = Instant::now().as_millis();
+        if let Ok(req) = DO_REQUEST.try_receive() {
+            match req {
+                DoRequest::Set(i, on) => if let Some(ch) = outputs.get_mut(i) { ch.set(on) },
+                DoRequest::Pulse(i, ms) => if let Some(ch) = outputs.get_mut(i) { ch.pulse(ms, now) },
+                DoRequest::Momentary(i) => if let Some(ch) = outputs.get_mut(i) { ch.momentary(now) },
+            }
+        }
+        outputs.tick(now);
+        DO_STATE.lock(|s| s.set(outputs.states()));
+
+        // Kalibrasi dibaca tiap periode supaya perubahan dari konsol langsung terlihat
+        let cal = SERVO_CAL.lock(|c| c.get());
+        let raw = SERVO_RAW.lock(|r| r.get());
+        servo.set_pulse_us(raw.unwrap_or_else(|| cal.pulse_us(cmd.servo_deg)));
+        // Periode sebenarnya antar awal pulsa (jitter karena task/interrupt lain)
+        let start = Instant::now();
+        if let Some(last) = last_pulse {
+            let period = (start - last).as_micros().min(u32::MAX as u64) as u32;
+            SERVO_TIMING.lock(|s| s.borrow_mut().record(period));
+        }
+        last_pulse = Some(start);
+        servo.pulse().await;
+
+        // Posisi dibandingkan dengan sudut setelah clamp batas lunak; pulsa mentah (kalibrasi) tidak dievaluasi
+        if let Some(fb) = feedback.as_mut() {
+            monitor.set_cal(SERVO_FB.lock(|c| c.get()));
+            let target = raw.is_none().then(|| cal.clamp(cmd.servo_deg));
+            // Konversi gagal / timeout: sample dilewati, monitor menahan nilai terfilter
+            if let Some(event) = fb.read().and_then(|adc| monitor.update(adc, target, now)) { on_stall(event, now); }
+            SERVO_POS.lock(|p| p.set(Some(monitor)));
+        }
+        ticker.next().await;
+    }
+}
+
+fn on_stall(event: StallEvent, now: u64) {
+    publish(TelemetryEvent::ServoStall(event));
+    if SERVO_STALL_FAULT && matches!(event, StallEvent::Stalled { .. }) {
+        if let Some(t) = LIFECYCLE.lock(|lc| lc.borrow_mut().raise(FaultCause::ServoStall, now)) {
+            publish(TelemetryEvent::Phase(t));
+        }
+    }
+}
+
+/// Kanal kipas (kalau `FAN_DO` ada di tabel).
+fn write_fan(cmd: &ActuatorCmd, fan: Option<usize>, outputs: &mut DoBank<IoOut, DO_COUNT>) {
+    if let Some(fan) = fan.and_then(|i| outputs.get_mut(i)) { fan.set(cmd.fan_on); }
+}
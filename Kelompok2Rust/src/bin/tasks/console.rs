@@ -0,0 +1,796 @@
+//! Task konsol: baca perintah per baris dari UART0 (interrupt-driven).
+
+use core::cell::Cell;
+use embassy_sync::blocking_mutex::{raw::CriticalSectionRawMutex, Mutex};
+use embassy_time::Instant;
+use esp_hal::{uart::UartRx, Async};
+use esp_println::{print, println};
+use hello_rust::calib::{Calibration, Channel};
+use hello_rust::control::{LoopConfig, Strategy, Variable};
+use hello_rust::dout::MOMENTARY_MS;
+use hello_rust::interlock::{Interlock, MAX_INTERLOCKS};
+use hello_rust::lifecycle::{Command, Phase, Rejected};
+use hello_rust::log::Level;
+use hello_rust::modbus::diag::BusDiagnostics;
+use hello_rust::modbus::discover::ScanConfig;
+use hello_rust::servo::{CalRoutine, CalStep, ServoCal};
+use hello_rust::servo_fb::FbRoutine;
+use hello_rust::setpoint::{End, Segment, MAX_SEGMENTS};
+use hello_rust::timesync::civil_date;
+
+use crate::log::{LOG_FILTER, MODULES};
+
+use super::telemetry::{print_bus_diag, print_post, print_servo_pos, print_timing};
+use super::{publish, save_config, set_servo_cal, DoRequest, TelemetryEvent, BAUD, BUS_DIAG, CALIBRATION, DIAG_RESET, DISCOVER, DISCOVER_MAX_REGS, DI_CHANNELS, DI_STATE, DO_CHANNELS, DO_REQUEST, DO_STATE, FAN_DO, INTERLOCKS, LIFECYCLE, POST, POST_ENABLED, POST_REPORT, REMOTE_DI, REMOTE_DO, SCAN_TIMING, SENSOR_SCHED, SERVO_CAL, SERVO_FB, SERVO_POS, SERVO_RAW, SERVO_TIMING, SETPOINT, STATUS, STORE, STRATEGY, TIME_SYNC};
+
+const LINE_MAX: usize = 64;
+
+/// (raw, referensi) satu titik kalibrasi.
+type CalPoint = (f32, f32);
+
+/// Titik pertama kalibrasi dua titik per kanal, menunggu `p2`.
+static CAL_P1: Mutex<CriticalSectionRawMutex, Cell<[Option<CalPoint>; 2]>> = Mutex::new(Cell::new([None; 2]));
+
+/// Rutin kalibrasi ujung servo yang sedang berjalan (`servo cal`).
+static SERVO_ROUTINE: Mutex<CriticalSectionRawMutex, Cell<Option<CalRoutine>>> = Mutex::new(Cell::new(None));
+/// Rutin kalibrasi feedback posisi yang sedang berjalan (`servo fb cal`).
+static FB_ROUTINE: Mutex<CriticalSectionRawMutex, Cell<Option<FbRoutine>>> = Mutex::new(Cell::new(None));
+
+#[embassy_executor::task]
+pub async fn console_task(mut rx: UartRx<'static, Async>) {
+    let mut line = [0u8; LINE_MAX];
+    let mut len = 0usize;
+
+    loop {
+        let mut buf = [0u8; 16];
+        let Ok(n) = rx.read_async(&mut buf).await else { continue };
+
+        for &b in &buf[..n] {
+            match b {
+                b'\r' | b'\n' if len > 0 => {
+                    if let Ok(cmd) = core::str::from_utf8(&line[..len]) { handle(cmd.trim()); }
+                    len = 0;
+                }
+                b'\r' | b'\n' => {}
+                _ if len < LINE_MAX => { line[len] = b; len += 1; }
+                _ => {} // baris kepanjangan: sisanya dibuang
+            }
+        }
+    }
+}
+
+fn handle(cmd: &str) {
+    let mut tok = cmd.split_whitespace();
+    match (tok.next(), tok.next()) {
+        (Some("help"), None) => {
+            println!("Perintah:");
+            println!("  help    - daftar perintah");
+            println!("  status  - fase, state sensor & aktuator terakhir");
+            println!("  start | stop | reset - STANDBY→RUN | RUN→SHUTDOWN | FAULT→SELF-TEST");
+            println!("  post    - hasil self-test boot (POST); 'reset' dari FAULT menjalankannya lagi");
+            println!("  diag    - counter bus RS485 per slave");
+            println!("  diag reset - nolkan counter bus");
+            println!("  discover <id1> <id2> [all|<baud>] [<addr> <jumlah>] - cari slave ID & baca register (bukan saat RUN)");
+            println!("  scan    - durasi scan (min/avg/max, overrun, histogram), jitter servo, jadwal task");
+            println!("  scan reset - nolkan statistik waktu");
+            println!("  sync <epoch_ms> - sinkron waktu dari host");
+            println!("  time    - status sinkron waktu (offset, drift, kualitas)");
+            println!("  listen  - host siap: kirim ulang sample yang belum di-ack");
+            println!("  ack <seq> - host sudah menerima sampai seq");
+            println!("  store   - isi buffer store-and-forward");
+            println!("  sp      - setpoint RH & mode (manual/profil)");
+            println!("  sp <nilai> | sp rate <%/menit> - target manual, laju ramp (0 = langsung)");
+            println!("  prof    - daftar segmen profil");
+            println!("  prof add <detik> <target> | clear | end hold|loop");
+            println!("  prof start | pause | resume | abort");
+            println!("  ctl     - strategi kontrol: variabel, SP & band tiap loop, feedforward T");
+            println!("  ctl servo|fan rh|t|dp [<sp>] | mix <w_rh> <w_t> [<sp>] - variabel loop (selain rh wajib SP tetap)");
+            println!("  ctl servo|fan sp <nilai>|auto | band <nilai> - auto = ikut setpoint RH");
+            println!("  ctl ff <%RH/°C> [<T ref>] | ff off | reset - feedforward T ke loop rh, kembali ke bawaan");
+            println!("  do      - kanal output digital (perintah, level pin, mode)");
+            println!("  do <nama> on|off | pulse <ms> | jog  - jog = ON {} ms (momentary)", MOMENTARY_MS);
+            println!("  di      - kanal input digital + alarm feedback");
+            println!("  cal     - koefisien kalibrasi sensor (raw * gain + offset)");
+            println!("  cal rh|t ref <nilai> - satu titik: geser offset ke nilai referensi");
+            println!("  cal rh|t p1 <nilai> lalu p2 <nilai> - dua titik (gain + offset)");
+            println!("  cal rh|t set <gain> <offset> | reset");
+            println!("  servo   - kalibrasi servo (ujung, tempuh, batas, park)");
+            println!("  servo cal - rutin ujung: jog <±µs> sampai tertutup → mark, terbuka → mark");
+            println!("  servo jog <±µs> | mark | cal abort");
+            println!("  servo range <deg> | limit <lo> <hi> | park <deg> | invert on|off");
+            println!("  servo set <min_us> <max_us> | reset");
+            println!("  servo fb - posisi aktual dari potensiometer vs perintah");
+            println!("  servo fb cal <deg1> <deg2> - ke deg1, tunggu diam → 'servo fb mark', ke deg2 → mark | cal abort | off");
+            println!("  log     - level log global + override per modul");
+            println!("  log <level> | log <modul> <level>|default - error/warn/info/debug/trace");
+            println!("  ilk     - tabel interlock");
+            println!("  ilk <n> on|off|bypass|unbypass|clear");
+            println!("  ilk <n> set <aturan>  - mis. 'ilk 3 set fan on if rh > 90 & t < 40'");
+        }
+        (Some("status"), None) => {
+            let st = STATUS.lock(|s| s.get());
+            println!("[status] fase={} polls={} sp={:.1}% servo={}° fan={}",
+                st.phase, st.polls, st.setpoint, st.cmd.servo_deg, if st.cmd.fan_on { "ON" } else { "OFF" });
+            if let Some(cause) = LIFECYCLE.lock(|lc| lc.borrow().fault()) { println!("[status] fault: {}", cause); }
+            match (st.measurement.rh, st.measurement.temp) {
+                (Some(rh), Some(t)) => println!("[status] RH {:.1} % | T {:.1} °C", rh, t),
+                _ => println!("[status] sensor belum/tidak terbaca"),
+            }
+            if let (Some(rh), Some(t)) = (st.raw.rh, st.raw.temp) {
+                println!("[status] raw RH {:.1} % | T {:.1} °C", rh, t);
+            }
+        }
+        (Some("post"), None) => match (POST_REPORT.lock(|r| r.get()), POST.lock(|p| p.get())) {
+            (Some(report), _) => print_post(&report),
+            (None, Some(_)) => println!("[post] sedang berjalan"),
+            (None, None) if POST_ENABLED => println!("[post] belum dijalankan"),
+            (None, None) => println!("[post] nonaktif (lihat POST_ENABLED)"),
+        },
+        (Some("diag"), None) => print_bus_diag(&BUS_DIAG.lock(|d| d.get())),
+        (Some("diag"), Some("reset")) => {
+            BUS_DIAG.lock(|d| d.set(BusDiagnostics::new()));
+            DIAG_RESET.signal(());
+            println!("[diag] counter direset");
+        }
+        (Some("discover"), Some(first)) => discover_cmd(first, tok),
+        (Some("scan"), None) => print_timing(),
+        (Some("scan"), Some("reset")) => {
+            SCAN_TIMING.lock(|s| s.borrow_mut().reset());
+            SERVO_TIMING.lock(|s| s.borrow_mut().reset());
+            SENSOR_SCHED.lock(|s| s.borrow_mut().reset_stats());
+            println!("[scan] statistik direset");
+        }
+        (Some("start"), None) => lifecycle_cmd(Command::Start),
+        (Some("stop"), None)  => lifecycle_cmd(Command::Stop),
+        (Some("reset"), None) => lifecycle_cmd(Command::Reset),
+        (Some("sync"), Some(epoch)) => match epoch.parse::<u64>() {
+            Ok(epoch_ms) => {
+                let now = Instant::now().as_millis();
+                let r = TIME_SYNC.lock(|t| {
+                    let mut ts = t.get();
+                    let r = ts.sync(epoch_ms, now);
+                    t.set(ts);
+                    r
+                });
+                match (r.error_ms, r.drift_ppm) {
+                    (Some(err), Some(ppm)) => println!("[sync] ok | koreksi {} ms | drift {:.1} ppm", err, ppm),
+                    (Some(err), None)      => println!("[sync] ok | koreksi {} ms", err),
+                    _                      => println!("[sync] ok | sync pertama"),
+                }
+            }
+            Err(_) => println!("[sync] format: sync <epoch_ms>"),
+        },
+        (Some("time"), None) => {
+            let now = Instant::now().as_millis();
+            let ts = TIME_SYNC.lock(|t| t.get());
+            println!("[time] uptime {} ms | sync {} ({}x)", now, ts.quality(now), ts.syncs());
+            if let (Some(epoch), Some(offset), Some(age)) = (ts.epoch_ms(now), ts.offset_ms(), ts.age_ms(now)) {
+                println!("[time] epoch {} ms | offset {} ms | umur sync {} s", epoch, offset, age / 1000);
+            }
+            if let Some(ppm) = ts.drift_ppm() { println!("[time] drift {:.1} ppm", ppm); }
+        }
+        (Some("listen"), None) => {
+            STORE.lock(|s| s.borrow_mut().listen(Instant::now().as_millis()));
+            publish(TelemetryEvent::Replay);
+        }
+        (Some("ack"), Some(seq)) => match seq.parse::<u32>() {
+            Ok(seq) => {
+                STORE.lock(|s| s.borrow_mut().ack(seq, Instant::now().as_millis()));
+                publish(TelemetryEvent::Replay);
+            }
+            Err(_) => println!("[store] format: ack <seq>"),
+        },
+        (Some("store"), None) => STORE.lock(|s| {
+            let s = s.borrow();
+            println!("[store] pending {} (flash {}) | seq terakhir {} | dibuang {} | host {}",
+                s.pending(), s.spilled(), s.last_seq(), s.dropped,
+                if s.is_listening() { "listen" } else { "offline" });
+        }),
+        (Some("sp"), None) => print_setpoint(),
+        (Some("sp"), Some(arg)) => setpoint_cmd(arg, tok.next()),
+        (Some("prof"), None) => print_profile(),
+        (Some("prof"), Some(action)) => profile_cmd(action, tok.next(), tok.next()),
+        (Some("ctl"), None) => print_strategy(),
+        (Some("ctl"), Some(first)) => strategy_cmd(first, tok),
+        (Some("do"), None) => print_outputs(),
+        (Some("do"), Some(name)) => output_cmd(name, tok.next(), tok.next()),
+        (Some("di"), None) => print_inputs(),
+        (Some("cal"), None) => print_calibration(),
+        (Some("cal"), Some(ch)) => calib_cmd(ch, tok.next(), tok.next(), tok.next()),
+        (Some("servo"), None) => print_servo(),
+        (Some("servo"), Some("fb")) => servo_fb_cmd(tok.next(), tok.next(), tok.next()),
+        (Some("servo"), Some(action)) => servo_cmd(action, tok.next(), tok.next()),
+        (Some("log"), None) => print_log(),
+        (Some("log"), Some(arg)) => log_cmd(arg, tok.next()),
+        (Some("ilk"), None) => print_interlocks(),
+        (Some("ilk"), Some(n)) => interlock_cmd(n, tok.next(), rest_after(cmd, 3)),
+        _ => println!("Perintah tidak dikenal: '{}' (ketik 'help')", cmd),
+    }
+}
+
+fn print_log() {
+    let f = LOG_FILTER.lock(|f| f.get());
+    println!("[log] level global: {}", f.level());
+    for (m, l) in f.overrides() { println!("[log]   {:<10} {}", m, l); }
+    print!("[log] modul:");
+    for m in MODULES { print!(" {}", m); }
+    println!();
+}
+
+/// `log <level>` atau `log <modul> <level>|default`.
+fn log_cmd(a: &str, b: Option<&str>) {
+    let ok = match b {
+        None => match Level::parse(a) {
+            Some(l) => { LOG_FILTER.lock(|f| { let mut v = f.get(); v.set_level(l); f.set(v) }); true }
+            None => { println!("[log] level tidak dikenal: {}", a); false }
+        },
+        Some(level) => {
+            let Some(&module) = MODULES.iter().find(|m| **m == a) else {
+                println!("[log] modul tidak dikenal: {} (lihat 'log')", a);
+                return;
+            };
+            let level = match level {
+                "default" => None,
+                l => match Level::parse(l) {
+                    Some(l) => Some(l),
+                    None => { println!("[log] level tidak dikenal: {}", l); return; }
+                },
+            };
+            let ok = LOG_FILTER.lock(|f| { let mut v = f.get(); let ok = v.set_module(module, level); f.set(v); ok });
+            if !ok { println!("[log] tabel override penuh"); }
+            ok
+        }
+    };
+    if ok { print_log(); }
+}
+
+/// `discover <id1> <id2> [all|<baud>] [<addr> <jumlah>]`; default baud `BAUD`,
+/// register 0x0000 x4. Dijalankan task sensor, hasil dicetak task telemetry.
+fn discover_cmd<'a>(first: &str, mut rest: impl Iterator<Item = &'a str>) {
+    const USAGE: &str = "[disc] format: discover <id1> <id2> [all|<baud>] [<addr> <jumlah>]";
+    if LIFECYCLE.lock(|lc| lc.borrow().phase()) == Phase::Run {
+        println!("[disc] ditolak saat RUN: 'stop' dulu (polling sensor berhenti selama scan)");
+        return;
+    }
+    let id = |s: Option<&str>| s.and_then(|s| s.parse::<u8>().ok()).filter(|id| (1..=247).contains(id));
+    let (Some(lo), Some(hi)) = (id(Some(first)), id(rest.next())) else { println!("{}", USAGE); return };
+    let args = [rest.next(), rest.next(), rest.next()];
+    if lo > hi || rest.next().is_some() { println!("{}", USAGE); return; }
+
+    // 1 atau 3 argumen sisa: yang pertama baudrate; 2 argumen: rentang register
+    let (baud, regs) = match args {
+        [None, ..]                     => (None, [None, None]),
+        [Some(b), None, _]             => (Some(b), [None, None]),
+        [Some(a), Some(n), None]       => (None, [Some(a), Some(n)]),
+        [Some(b), Some(a), Some(n)]    => (Some(b), [Some(a), Some(n)]),
+    };
+    let (addr, count) = match regs {
+        [Some(a), Some(n)] => match (parse_u16(a), n.parse::<u16>()) {
+            (Some(a), Ok(n)) if (1..=DISCOVER_MAX_REGS).contains(&n) => (a, n),
+            _ => { println!("[disc] register: <addr> <jumlah 1..{}>", DISCOVER_MAX_REGS); return }
+        },
+        _ => (0x0000, 4),
+    };
+    let cfg = ScanConfig::new(lo, hi, addr, count);
+    let cfg = match baud {
+        None        => cfg.with_baud(BAUD),
+        Some("all") => cfg.with_common_bauds(),
+        Some(b)     => match b.parse::<u32>() {
+            Ok(b) if b >= 1_200 => cfg.with_baud(b),
+            _ => { println!("{}", USAGE); return }
+        },
+    };
+    print!("[disc] ID {}..{} | register 0x{:04X} x{} | bps", lo, hi, addr, count);
+    for b in cfg.bauds() { print!(" {}", b); }
+    println!();
+    DISCOVER.signal(cfg);
+}
+
+/// Desimal atau heksadesimal `0x..`.
+fn parse_u16(s: &str) -> Option<u16> {
+    match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(h) => u16::from_str_radix(h, 16).ok(),
+        None    => s.parse().ok(),
+    }
+}
+
+fn lifecycle_cmd(cmd: Command) {
+    match LIFECYCLE.lock(|lc| lc.borrow_mut().command(cmd, Instant::now().as_millis())) {
+        Ok(t) => publish(TelemetryEvent::Phase(t)), // dicetak task telemetry
+        Err(Rejected::WrongPhase(phase)) => println!("[fase] ditolak: tidak berlaku di {}", phase),
+        Err(Rejected::SensorNotReady) => println!("[fase] ditolak: sensor belum terbaca valid"),
+        Err(Rejected::Held) => println!("[fase] ditolak: discovery RS485 sedang berjalan"),
+    }
+}
+
+/// Sisa baris setelah `n` token pertama (untuk argumen bebas seperti aturan interlock).
+fn rest_after(line: &str, n: usize) -> &str {
+    let mut rest = line.trim_start();
+    for _ in 0..n {
+        rest = rest.trim_start_matches(|c: char| !c.is_whitespace()).trim_start();
+    }
+    rest
+}
+
+fn print_interlocks() {
+    INTERLOCKS.lock(|t| {
+        let t = t.borrow();
+        println!("[ilk]  # status  trip  aktif  aturan");
+        for (i, il, st) in t.iter() {
+            let status = match (il.enabled, il.bypassed) {
+                (false, _)    => "off",
+                (true, true)  => "BYPASS",
+                (true, false) => "on",
+            };
+            println!("[ilk] {:>2} {:<7} {:<5} {:<6} {}", i + 1, status,
+                if st.tripped { "ya" } else { "-" }, if st.active { "YA" } else { "-" }, il);
+        }
+        if t.iter().next().is_none() { println!("[ilk] (tabel kosong)"); }
+    });
+}
+
+fn interlock_cmd(n: &str, action: Option<&str>, rule: &str) {
+    let Some(idx) = n.parse::<usize>().ok().filter(|&n| (1..=MAX_INTERLOCKS).contains(&n)).map(|n| n - 1) else {
+        println!("[ilk] nomor harus 1..{}", MAX_INTERLOCKS);
+        return;
+    };
+
+    let ok = INTERLOCKS.lock(|t| {
+        let mut t = t.borrow_mut();
+        match action {
+            Some("set") => match Interlock::parse(rule) {
+                Some(il) => t.set(idx, Some(il)),
+                None => { println!("[ilk] format aturan salah: '{}'", rule); false }
+            },
+            Some("clear") => t.set(idx, None),
+            Some(a @ ("on" | "off" | "bypass" | "unbypass")) => {
+                let Some(il) = t.get_mut(idx) else { println!("[ilk] #{} kosong", idx + 1); return false };
+                match a {
+                    "on"     => il.enabled = true,
+                    "off"    => il.enabled = false,
+                    "bypass" => il.bypassed = true,
+                    _        => il.bypassed = false,
+                }
+                true
+            }
+            _ => { println!("[ilk] aksi tidak dikenal (ketik 'help')"); false }
+        }
+    });
+    if ok { print_interlocks(); }
+}
+
+fn print_calibration() {
+    let set = CALIBRATION.lock(|c| c.get());
+    println!("[cal] kanal    gain    offset  tanggal");
+    for ch in [Channel::Rh, Channel::Temp] {
+        let c = set.get(ch);
+        if c.date_ms == 0 {
+            println!("[cal] {:<5} {:>7.4} {:>7.2} {:<2} -", ch.name(), c.gain, c.offset, ch.unit());
+        } else {
+            let (y, m, d) = civil_date(c.date_ms);
+            println!("[cal] {:<5} {:>7.4} {:>7.2} {:<2} {}-{:02}-{:02}", ch.name(), c.gain, c.offset, ch.unit(), y, m, d);
+        }
+    }
+}
+
+fn calib_cmd(ch: &str, action: Option<&str>, a: Option<&str>, b: Option<&str>) {
+    let Some(ch) = Channel::parse(ch) else { println!("[cal] kanal: rh | t"); return };
+    // "nan"/"inf" lolos parse f32: ditolak di sini
+    let num = |s: Option<&str>| s.and_then(|s| s.parse::<f32>().ok()).filter(|v| v.is_finite());
+    let now = Instant::now().as_millis();
+    let date = TIME_SYNC.lock(|t| t.get()).epoch_ms(now).unwrap_or(0);
+    let raw = STATUS.lock(|s| s.get()).raw;
+    let raw = match ch { Channel::Rh => raw.rh, Channel::Temp => raw.temp };
+    let cur = *CALIBRATION.lock(|c| c.get()).get(ch);
+
+    let new = match (action, num(a), num(b)) {
+        (Some("ref"), Some(reference), None) => {
+            let Some(raw) = raw else { println!("[cal] belum ada pembacaan {}", ch.name()); return };
+            cur.one_point(raw, reference, date)
+        }
+        (Some("p1"), Some(reference), None) => {
+            let Some(raw) = raw else { println!("[cal] belum ada pembacaan {}", ch.name()); return };
+            CAL_P1.lock(|p| { let mut v = p.get(); v[ch as usize] = Some((raw, reference)); p.set(v); });
+            println!("[cal] {} p1: raw {:.2} → {:.2}; ubah kondisi lalu 'cal {} p2 <nilai>'", ch.name(), raw, reference, ch.name());
+            return;
+        }
+        (Some("p2"), Some(reference), None) => {
+            let Some(raw) = raw else { println!("[cal] belum ada pembacaan {}", ch.name()); return };
+            let Some(p1) = CAL_P1.lock(|p| p.get()[ch as usize]) else { println!("[cal] jalankan 'cal {} p1 <nilai>' dulu", ch.name()); return };
+            Calibration::two_point(ch, p1, (raw, reference), date)
+        }
+        (Some("set"), Some(gain), Some(offset)) => Calibration::new(gain, offset, date),
+        (Some("reset"), None, None) => Ok(Calibration::IDENTITY),
+        _ => { println!("[cal] format salah (ketik 'help')"); return }
+    };
+    let new = match new {
+        Ok(c) => c,
+        Err(e) => { println!("[cal] ditolak: {}", e); return }
+    };
+
+    CAL_P1.lock(|p| { let mut v = p.get(); v[ch as usize] = None; p.set(v); });
+    CALIBRATION.lock(|c| { let mut set = c.get(); *set.get_mut(ch) = new; c.set(set); });
+    match save_config() {
+        Ok(()) => println!("[cal] {} disimpan", ch.name()),
+        Err(e) => println!("⚠️  [cal] {} berlaku tapi tidak tersimpan: {}", ch.name(), e),
+    }
+    print_calibration();
+}
+
+fn print_servo() {
+    let c = SERVO_CAL.lock(|c| c.get());
+    println!("[servo] ujung {}..{} µs | tempuh {}° | {} | batas {}..{}° | park {}°", c.min_us, c.max_us,
+        c.range_deg, if c.inverted { "terbalik" } else { "normal" }, c.limit_lo, c.limit_hi, c.park_deg);
+    if let Some(r) = SERVO_ROUTINE.lock(|r| r.get()) { print_routine(&r); }
+    print_servo_fb();
+}
+
+fn print_routine(r: &CalRoutine) {
+    match r.step() {
+        CalStep::Closed => println!("[servo] kalibrasi 1/2: pulsa {} µs | 'servo jog <±µs>' sampai damper TERTUTUP, lalu 'servo mark'", r.pulse_us()),
+        CalStep::Open { closed_us } => println!("[servo] kalibrasi 2/2: tertutup {} µs | pulsa {} µs | jog sampai TERBUKA penuh, lalu 'servo mark'", closed_us, r.pulse_us()),
+        CalStep::Done => {}
+    }
+}
+
+fn servo_cmd(action: &str, a: Option<&str>, b: Option<&str>) {
+    let int = |s: Option<&str>| s.and_then(|s| s.parse::<i32>().ok());
+    let routine = SERVO_ROUTINE.lock(|r| r.get());
+    let cur = SERVO_CAL.lock(|c| c.get());
+
+    let new = match (action, a, b) {
+        ("cal", None, None) => {
+            FB_ROUTINE.lock(|r| r.set(None));
+            let r = CalRoutine::new(cur);
+            SERVO_ROUTINE.lock(|s| s.set(Some(r)));
+            SERVO_RAW.lock(|s| s.set(Some(r.pulse_us())));
+            println!("[servo] rutin kalibrasi mulai; perintah controller diabaikan sampai selesai/abort");
+            print_routine(&r);
+            return;
+        }
+        ("cal", Some("abort"), None) => {
+            end_routine();
+            println!("[servo] kalibrasi dibatalkan");
+            return;
+        }
+        ("jog", Some(_), None) | ("mark", None, None) => {
+            let Some(mut r) = routine else { println!("[servo] mulai dulu dengan 'servo cal'"); return };
+            let done = if action == "jog" {
+                let Some(delta) = int(a) else { println!("[servo] format: servo jog <±µs>"); return };
+                r.jog(delta);
+                None
+            } else {
+                r.mark()
+            };
+            SERVO_ROUTINE.lock(|s| s.set(Some(r)));
+            SERVO_RAW.lock(|s| s.set(Some(r.pulse_us())));
+            match done {
+                None => { print_routine(&r); return }
+                Some(res) => { end_routine(); res }
+            }
+        }
+        ("range", Some(_), None) => match int(a) {
+            // Batas & park ikut dipotong ke tempuh baru
+            Some(range) => Ok(ServoCal {
+                range_deg: range,
+                limit_lo: cur.limit_lo.min(range),
+                limit_hi: cur.limit_hi.min(range),
+                park_deg: cur.park_deg.min(range),
+                ..cur
+            }),
+            None => { println!("[servo] format: servo range <deg>"); return }
+        },
+        ("limit", Some(_), Some(_)) => match (int(a), int(b)) {
+            (Some(lo), Some(hi)) => Ok(ServoCal { limit_lo: lo, limit_hi: hi, park_deg: cur.park_deg.clamp(lo, hi.max(lo)), ..cur }),
+            _ => { println!("[servo] format: servo limit <lo> <hi>"); return }
+        },
+        ("park", Some(_), None) => match int(a) {
+            Some(park) => Ok(ServoCal { park_deg: park, ..cur }),
+            None => { println!("[servo] format: servo park <deg>"); return }
+        },
+        ("invert", Some("on"), None)  => Ok(ServoCal { inverted: true, ..cur }),
+        ("invert", Some("off"), None) => Ok(ServoCal { inverted: false, ..cur }),
+        ("set", Some(_), Some(_)) => match (int(a), int(b)) {
+            (Some(min), Some(max)) if min >= 0 && max >= 0 => Ok(ServoCal { min_us: min as u32, max_us: max as u32, ..cur }),
+            _ => { println!("[servo] format: servo set <min_us> <max_us>"); return }
+        },
+        ("reset", None, None) => Ok(ServoCal::DEFAULT),
+        _ => { println!("[servo] format salah (ketik 'help')"); return }
+    };
+
+    if let Err(e) = new.and_then(set_servo_cal) { println!("[servo] ditolak: {}", e); return; }
+    match save_config() {
+        Ok(()) => println!("[servo] disimpan"),
+        Err(e) => println!("⚠️  [servo] berlaku tapi tidak tersimpan: {}", e),
+    }
+    print_servo();
+}
+
+fn print_strategy() {
+    let s = STRATEGY.lock(|s| s.get());
+    let st = STATUS.lock(|s| s.get());
+    for (name, l, now) in [("servo", &s.servo, st.loops[0]), ("fan", &s.fan, st.loops[1])] {
+        print!("[ctl] {:<5} {} | SP ", name, l.var);
+        match l.setpoint {
+            Some(sp) => print!("{:.2}", sp),
+            None     => print!("auto ({:.1})", now.sp),
+        }
+        print!(" | band {:.2}{}", l.band, l.var.unit());
+        match now.pv {
+            Some(pv) => println!(" | PV {:.2}", pv),
+            None     => println!(" | PV -"),
+        }
+    }
+    match s.ff_gain {
+        0.0 => println!("[ctl] feedforward off"),
+        g   => println!("[ctl] feedforward {:+.2} %RH/°C di atas {:.1} °C (loop rh)", g, s.ff_ref_c),
+    }
+    if s == Strategy::DEFAULT { println!("[ctl] (bawaan)"); }
+}
+
+fn strategy_cmd<'a>(first: &str, mut rest: impl Iterator<Item = &'a str>) {
+    let num = |s: Option<&str>| s.and_then(|s| s.parse::<f32>().ok());
+    let cur = STRATEGY.lock(|s| s.get());
+    let mut new = cur;
+
+    match first {
+        "reset" => new = Strategy::DEFAULT,
+        "ff" => match rest.next() {
+            Some("off") => new.ff_gain = 0.0,
+            gain => match (num(gain), rest.next()) {
+                (Some(g), None) => new.ff_gain = g,
+                (Some(g), Some(r)) => match num(Some(r)) {
+                    Some(r) => { new.ff_gain = g; new.ff_ref_c = r; }
+                    None => { println!("[ctl] format: ctl ff <%RH/°C> [<T ref>] | ff off"); return }
+                },
+                _ => { println!("[ctl] format: ctl ff <%RH/°C> [<T ref>] | ff off"); return }
+            },
+        },
+        "servo" | "fan" => {
+            let l: &mut LoopConfig = if first == "servo" { &mut new.servo } else { &mut new.fan };
+            let action = rest.next();
+            let var = match action {
+                Some("rh") => Some(Variable::Rh),
+                Some("t")  => Some(Variable::Temp),
+                Some("dp") => Some(Variable::DewPoint),
+                Some("mix") => match (num(rest.next()), num(rest.next())) {
+                    (Some(w_rh), Some(w_t)) => Some(Variable::Weighted { w_rh, w_t }),
+                    _ => { println!("[ctl] format: ctl {} mix <w_rh> <w_t> [<sp>]", first); return }
+                },
+                _ => None,
+            };
+            match (var, action) {
+                // Ganti variabel: SP lama tidak bermakna di satuan baru → pakai argumen (atau auto)
+                (Some(v), _) => {
+                    l.var = v;
+                    l.setpoint = match rest.next() {
+                        None => None,
+                        sp => match num(sp) {
+                            Some(sp) => Some(sp),
+                            None => { println!("[ctl] SP bukan angka"); return }
+                        },
+                    };
+                }
+                (None, Some("sp")) => match rest.next() {
+                    Some("auto") => l.setpoint = None,
+                    sp => match num(sp) {
+                        Some(sp) => l.setpoint = Some(sp),
+                        None => { println!("[ctl] format: ctl {} sp <nilai>|auto", first); return }
+                    },
+                },
+                (None, Some("band")) => match num(rest.next()) {
+                    Some(b) => l.band = b,
+                    None => { println!("[ctl] format: ctl {} band <nilai>", first); return }
+                },
+                _ => { println!("[ctl] format salah (ketik 'help')"); return }
+            }
+        }
+        _ => { println!("[ctl] format salah (ketik 'help')"); return }
+    }
+
+    if let Err(e) = new.validate() { println!("[ctl] ditolak: {}", e); return; }
+    STRATEGY.lock(|s| s.set(new));
+    match save_config() {
+        Ok(()) => println!("[ctl] disimpan"),
+        Err(e) => println!("⚠️  [ctl] berlaku tapi tidak tersimpan: {}", e),
+    }
+    print_strategy();
+}
+
+fn print_servo_fb() {
+    let Some(pos) = SERVO_POS.lock(|p| p.get()) else { println!("[servo] feedback posisi nonaktif (SERVO_FEEDBACK)"); return };
+    match pos.cal() {
+        Some(c) => println!("[servo] feedback: {}° @ADC {} | {}° @ADC {}", c.deg_a, c.raw_a, c.deg_b, c.raw_b),
+        None    => println!("[servo] feedback belum dikalibrasi ('servo fb cal 0 120')"),
+    }
+    print_servo_pos(&pos);
+    if let Some(r) = FB_ROUTINE.lock(|r| r.get()) {
+        println!("[servo] kalibrasi feedback {}/2: servo ke {}°, tunggu diam lalu 'servo fb mark'", r.step(), r.target_deg());
+    }
+}
+
+fn servo_fb_cmd(action: Option<&str>, a: Option<&str>, b: Option<&str>) {
+    let Some(pos) = SERVO_POS.lock(|p| p.get()) else { println!("[servo] feedback posisi nonaktif (SERVO_FEEDBACK)"); return };
+    let cal = SERVO_CAL.lock(|c| c.get());
+    let int = |s: Option<&str>| s.and_then(|s| s.parse::<i32>().ok());
+
+    let new = match (action, a, b) {
+        (None, ..) => { print_servo_fb(); return }
+        (Some("cal"), Some("abort"), None) => {
+            end_fb_routine();
+            println!("[servo] kalibrasi feedback dibatalkan");
+            return;
+        }
+        (Some("cal"), Some(_), Some(_)) => {
+            if SERVO_ROUTINE.lock(|r| r.get()).is_some() { println!("[servo] selesaikan dulu 'servo cal'"); return; }
+            let (Some(d1), Some(d2)) = (int(a), int(b)) else { println!("[servo] format: servo fb cal <deg1> <deg2>"); return };
+            // Titik di luar batas lunak tidak bisa dicapai → pakai sudut setelah clamp
+            match FbRoutine::new(cal.clamp(d1), cal.clamp(d2)) {
+                Ok(r) => {
+                    FB_ROUTINE.lock(|s| s.set(Some(r)));
+                    SERVO_RAW.lock(|s| s.set(Some(cal.pulse_us(r.target_deg()))));
+                    println!("[servo] kalibrasi feedback mulai; perintah controller diabaikan sampai selesai/abort");
+                    print_servo_fb();
+                }
+                Err(e) => println!("[servo] ditolak: {}", e),
+            }
+            return;
+        }
+        (Some("mark"), None, None) => {
+            let Some(mut r) = FB_ROUTINE.lock(|r| r.get()) else { println!("[servo] mulai dulu dengan 'servo fb cal <deg1> <deg2>'"); return };
+            let Some(raw) = pos.raw() else { println!("[servo] ADC belum terbaca"); return };
+            match r.mark(raw as u16) {
+                None => {
+                    FB_ROUTINE.lock(|s| s.set(Some(r)));
+                    SERVO_RAW.lock(|s| s.set(Some(cal.pulse_us(r.target_deg()))));
+                    print_servo_fb();
+                    return;
+                }
+                Some(res) => {
+                    end_fb_routine();
+                    match res {
+                        Ok(c) => Some(c),
+                        Err(e) => { println!("[servo] kalibrasi feedback gagal: {}", e); return }
+                    }
+                }
+            }
+        }
+        (Some("off"), None, None) => None,
+        _ => { println!("[servo] format salah (ketik 'help')"); return }
+    };
+
+    SERVO_FB.lock(|c| c.set(new));
+    match save_config() {
+        Ok(()) => println!("[servo] feedback disimpan"),
+        Err(e) => println!("⚠️  [servo] feedback berlaku tapi tidak tersimpan: {}", e),
+    }
+    // Monitor mengambil kalibrasi baru di periode servo berikutnya
+    if let Some(c) = new { println!("[servo] feedback: {}° @ADC {} | {}° @ADC {}", c.deg_a, c.raw_a, c.deg_b, c.raw_b); }
+}
+
+fn end_fb_routine() {
+    FB_ROUTINE.lock(|s| s.set(None));
+    SERVO_RAW.lock(|s| s.set(None));
+}
+
+/// Akhiri rutin kalibrasi; servo kembali mengikuti perintah controller.
+fn end_routine() {
+    SERVO_ROUTINE.lock(|s| s.set(None));
+    SERVO_RAW.lock(|s| s.set(None));
+}
+
+fn print_setpoint() {
+    SETPOINT.lock(|g| {
+        let g = g.borrow();
+        println!("[sp] setpoint {:.1} % | target manual {:.1} % | ramp {} | {}", g.value(), g.target(),
+            if g.rate > 0.0 { "terbatas" } else { "langsung" }, g.state());
+        if g.rate > 0.0 { println!("[sp] laju ramp {:.1} %/menit", g.rate); }
+        if let Some(left) = g.remaining_ms() { println!("[sp] sisa segmen {} s", left / 1000); }
+    });
+}
+
+fn setpoint_cmd(arg: &str, value: Option<&str>) {
+    let num = |s: Option<&str>| s.and_then(|s| s.parse::<f32>().ok()).filter(|v| v.is_finite());
+    match (arg, num(value)) {
+        ("rate", Some(rate)) if rate >= 0.0 => SETPOINT.lock(|g| g.borrow_mut().rate = rate),
+        (sp, None) => match sp.parse::<f32>() {
+            Ok(sp) if (0.0..=100.0).contains(&sp) => SETPOINT.lock(|g| g.borrow_mut().set_target(sp)),
+            _ => { println!("[sp] format: sp <0..100> | sp rate <%/menit>"); return }
+        },
+        _ => { println!("[sp] format: sp <0..100> | sp rate <%/menit>"); return }
+    }
+    print_setpoint();
+}
+
+fn print_profile() {
+    SETPOINT.lock(|g| {
+        let g = g.borrow();
+        let p = &g.profile;
+        println!("[prof] {} segmen, total {} s, akhir {} | {}", p.segments().len(), p.total_ms() / 1000,
+            if p.end == End::Loop { "loop" } else { "hold" }, g.state());
+        for (i, seg) in p.segments().iter().enumerate() {
+            println!("[prof] {:>2} {:>6} s → {:.1} %", i + 1, seg.duration_ms / 1000, seg.target);
+        }
+    });
+}
+
+fn profile_cmd(action: &str, a: Option<&str>, b: Option<&str>) {
+    let r = SETPOINT.lock(|g| {
+        let mut g = g.borrow_mut();
+        match (action, a, b) {
+            ("add", Some(dur), Some(target)) => match (dur.parse::<u64>().ok().and_then(|s| s.checked_mul(1000)), target.parse::<f32>()) {
+                (Some(duration_ms), Ok(target)) if (0.0..=100.0).contains(&target) => {
+                    if g.profile.push(Segment { duration_ms, target }) { return Ok(true); }
+                    println!("[prof] penuh (maks. {} segmen), segmen tidak ditambahkan", MAX_SEGMENTS);
+                    Ok(false)
+                }
+                _ => { println!("[prof] format: prof add <detik> <0..100>"); Ok(false) }
+            },
+            ("clear", None, None) => {
+                // Profil yang sedang jalan dihentikan dulu supaya indeks segmen tidak menggantung
+                let _ = g.abort();
+                g.profile.clear();
+                Ok(true)
+            }
+            ("end", Some("hold"), None) => { g.profile.end = End::Hold; Ok(true) }
+            ("end", Some("loop"), None) => { g.profile.end = End::Loop; Ok(true) }
+            ("start", None, None)  => g.start().map(|_| true),
+            ("pause", None, None)  => g.pause().map(|_| true),
+            ("resume", None, None) => g.resume().map(|_| true),
+            ("abort", None, None)  => g.abort().map(|_| true),
+            _ => { println!("[prof] aksi tidak dikenal (ketik 'help')"); Ok(false) }
+        }
+    });
+    match r {
+        Ok(true) => print_profile(),
+        Ok(false) => {}
+        Err(e) => println!("[prof] ditolak: {}", e),
+    }
+}
+
+fn print_outputs() {
+    let states = DO_STATE.lock(|s| s.get());
+    let remote = REMOTE_DO.lock(|r| r.get());
+    println!("[do] kanal    perintah pin   polaritas  mode | lokasi");
+    for ((cfg, st), img) in DO_CHANNELS.iter().zip(states.iter()).zip(remote.iter()) {
+        // Remote: level coil belum terbaca / modul tidak menjawab
+        let pin = match (cfg.point.is_remote() && img.actual.is_none(), st.level_high) {
+            (true, _) => "?",
+            (false, true) => "HIGH",
+            (false, false) => "LOW",
+        };
+        println!("[do] {:<8} {:<8} {:<5} {:<10} {} | {}{}", cfg.name, if st.on { "ON" } else { "OFF" },
+            pin, if cfg.active_low { "aktif-LOW" } else { "aktif-HIGH" }, st.mode, cfg.point,
+            if pin == "?" || st.consistent(cfg) { "" } else { "  ⚠️ pin tidak sesuai" });
+    }
+}
+
+fn print_inputs() {
+    let states = DI_STATE.lock(|s| s.get());
+    let remote = REMOTE_DI.lock(|r| r.get());
+    println!("[di] kanal    state feedback-untuk        alarm | lokasi");
+    for ((cfg, st), img) in DI_CHANNELS.iter().zip(states.iter()).zip(remote.iter()) {
+        let link = cfg.feedback.map_or("-", |f| f.output);
+        let stale = if cfg.point.is_remote() && (img.level.is_none() || img.fails > 0) { " (tidak menjawab)" } else { "" };
+        match st.alarm {
+            Some(d) => println!("[di] {:<8} {:<5} {:<20} {} | {}{}", cfg.name, if st.on { "ON" } else { "OFF" }, link, d, cfg.point, stale),
+            None    => println!("[di] {:<8} {:<5} {:<20} - | {}{}", cfg.name, if st.on { "ON" } else { "OFF" }, link, cfg.point, stale),
+        }
+    }
+}
+
+fn output_cmd(name: &str, action: Option<&str>, arg: Option<&str>) {
+    let Some(idx) = DO_CHANNELS.iter().position(|c| c.name == name) else {
+        println!("[do] kanal '{}' tidak ada (ketik 'do')", name);
+        return;
+    };
+    let req = match (action, arg.map(|a| a.parse::<u64>())) {
+        (Some("on"), None)  => DoRequest::Set(idx, true),
+        (Some("off"), None) => DoRequest::Set(idx, false),
+        (Some("pulse"), Some(Ok(ms))) => DoRequest::Pulse(idx, ms),
+        (Some("jog"), None) => DoRequest::Momentary(idx),
+        _ => { println!("[do] format: do <nama> on|off | pulse <ms> | jog"); return }
+    };
+    if DO_REQUEST.try_send(req).is_err() { println!("[do] antrean penuh, coba lagi"); return; }
+    if name == FAN_DO { println!("[do] catatan: '{}' ditimpa controller di polling berikutnya", name); }
+}
@@ -0,0 +1,133 @@
+//! Task kontrol: tiap measurement baru → (POST) → lifecycle → setpoint → `Controller` → tabel interlock.
+
+use embassy_time::Instant;
+use hello_rust::control::{ActuatorCmd, Controller};
+use hello_rust::interlock::{InterlockTable, ProcessImage, MAX_INTERLOCKS};
+use hello_rust::lifecycle::{FaultCause, Lifecycle, Phase};
+use hello_rust::post::{Post, PostPlan};
+use hello_rust::retain::Snapshot;
+use hello_rust::sht20::Measurement;
+
+use crate::board;
+
+use super::{publish, update_status, TelemetryEvent, ACTUATOR_CMD, DI_CHANNELS, DI_STATE, FAN_DO, INTERLOCKS, LIFECYCLE, MEASUREMENT, POST, POST_CONFIG, POST_ENABLED, POST_PLAN, POST_REPORT, SCAN_BUDGET_US, SCAN_TIMING, SERVO_CAL, SETPOINT, STRATEGY};
+
+/// `warm` = snapshot sebelum reset (None = cold start dari default).
+#[embassy_executor::task]
+pub async fn control_task(warm: Option<Snapshot>) {
+    let mut controller = Controller::new();
+    let mut cmd = controller.cmd();
+    let restarts = warm.map_or(0, |s| s.restarts + 1);
+    let quick = warm.map_or(0, |s| s.next_quick());
+    INTERLOCKS.lock(|t| *t.borrow_mut() = InterlockTable::defaults());
+    // Input feedback kipas untuk POST (kalau ada)
+    let fan_feedback = DI_CHANNELS.iter().position(|c| c.feedback.is_some_and(|f| f.output == FAN_DO));
+    let plan = PostPlan { fan_feedback: fan_feedback.is_some(), ..POST_PLAN };
+    let mut last_phase = Phase::Init;
+
+    // Warm restart: lanjutkan fase, output & state controller sebelum reset.
+    // Selain itu (atau fase tidak bisa dilanjutkan): INIT → SELF-TEST
+    let now = Instant::now().as_millis();
+    if let Some(s) = warm {
+        controller.restore(s.controller);
+        SETPOINT.lock(|g| *g.borrow_mut() = s.setpoint);
+        if s.resumes() { cmd = s.cmd; }
+    }
+    let start = LIFECYCLE.lock(|lc| {
+        let mut lc = lc.borrow_mut();
+        warm.and_then(|s| lc.warm_start(s.phase, now)).or_else(|| lc.boot(now))
+    });
+    if let Some(t) = start { publish(TelemetryEvent::Phase(t)); }
+
+    loop {
+        let reading = MEASUREMENT.wait().await;
+        let m = reading.measurement;
+        let feedback = fan_feedback.map(|i| DI_STATE.lock(|s| s.get())[i].on);
+        let (post_cmd, transition, lifecycle) = LIFECYCLE.lock(|lc| {
+            let mut lc = lc.borrow_mut();
+            // Masuk SELF-TEST (boot / `reset`) → POST baru
+            if POST_ENABLED && lc.phase() == Phase::SelfTest && last_phase != Phase::SelfTest {
+                let post = Post::new(plan, POST_CONFIG.lock(|c| c.get()), &SERVO_CAL.lock(|c| c.get()), reading.t_ms);
+                POST.lock(|p| p.set(Some(post)));
+                POST_REPORT.lock(|r| r.set(None));
+                lc.set_post(true);
+            }
+            let post_cmd = lc.post_running().then(|| step_post(&mut lc, &m, feedback, reading.t_ms)).flatten();
+            (post_cmd, lc.update(&m, reading.t_ms), *lc)
+        });
+        last_phase = lifecycle.phase();
+        if let Some(t) = transition { publish(TelemetryEvent::Phase(t)); }
+        publish(TelemetryEvent::Sample(reading, lifecycle.phase()));
+
+        controller.set_setpoint(SETPOINT.lock(|g| g.borrow_mut().update(reading.t_ms)));
+        controller.set_strategy(STRATEGY.lock(|s| s.get()));
+
+        // Loop tanpa PV valid menahan output terakhirnya
+        let fresh = controller.update(&m);
+        let run = fresh.unwrap_or(controller.cmd());
+        // Di luar RUN output mengikuti fase (aman / purge), atau POST selama berjalan.
+        // Interlock dievaluasi terakhir supaya aturan keselamatan juga berlaku di fase itu.
+        let phase_out = post_cmd.unwrap_or(lifecycle.outputs(run));
+        let image = ProcessImage::from(&m);
+
+        let mut before = [Default::default(); MAX_INTERLOCKS];
+        let mut after = before;
+        let out = INTERLOCKS.lock(|t| {
+            let mut t = t.borrow_mut();
+            for (i, s) in before.iter_mut().enumerate() { *s = t.state(i); }
+            let out = t.evaluate(&image, phase_out);
+            for (i, s) in after.iter_mut().enumerate() { *s = t.state(i); }
+            out
+        });
+        for (idx, (b, a)) in before.iter().zip(after.iter()).enumerate() {
+            if b != a { publish(TelemetryEvent::Interlock { idx, state: *a }); }
+        }
+
+        if fresh.is_some() || out != cmd {
+            cmd = out;
+            ACTUATOR_CMD.signal(cmd);
+            publish(TelemetryEvent::Actuators(cmd));
+        }
+        let scan_us = Instant::now().as_micros().saturating_sub(reading.start_us);
+        let scan_us = scan_us.min(u32::MAX as u64) as u32;
+        if SCAN_TIMING.lock(|s| s.borrow_mut().record(scan_us)) {
+            warn!("scan {} µs melebihi budget {} µs", scan_us, SCAN_BUDGET_US);
+        }
+
+        board::retain(&Snapshot {
+            phase: lifecycle.phase(),
+            cmd,
+            controller: controller.cmd(),
+            setpoint: SETPOINT.lock(|g| *g.borrow()),
+            uptime_ms: reading.t_ms,
+            restarts,
+            quick,
+        });
+
+        update_status(|s| {
+            s.raw = reading.raw;
+            s.measurement = m;
+            s.setpoint = controller.setpoint();
+            s.loops = controller.status();
+            s.cmd = cmd;
+            s.polls = s.polls.wrapping_add(1);
+            s.phase = lifecycle.phase();
+        });
+    }
+}
+
+/// Majukan POST; saat selesai hasil dipublikasikan dan check kritis yang gagal → FAULT.
+fn step_post(lc: &mut Lifecycle, m: &Measurement, feedback: Option<bool>, now: u64) -> Option<ActuatorCmd> {
+    let mut post = POST.lock(|p| p.get())?;
+    let cmd = post.step(m, feedback, now);
+    POST.lock(|p| p.set(Some(post)));
+    if cmd.is_some() { return cmd; }
+
+    lc.set_post(false);
+    POST_REPORT.lock(|r| r.set(Some(*post.report())));
+    publish(TelemetryEvent::SelfTest);
+    if let Some(check) = post.report().blocking(post.plan()) {
+        if let Some(t) = lc.raise(FaultCause::SelfTest(check), now) { publish(TelemetryEvent::Phase(t)); }
+    }
+    None
+}
@@ -0,0 +1,41 @@
+//! Task input digital: sampling tiap `SCAN_PERIOD`, debounce, lalu bandingkan
+//! kanal feedback dengan state output yang diperintah (readback `DO_STATE`).
+
+use embassy_time::{Duration, Instant, Ticker};
+use hello_rust::din::{DiChannel, FeedbackEvent};
+use hello_rust::lifecycle::FaultCause;
+
+use crate::board::IoIn;
+use super::{publish, TelemetryEvent, DI_COUNT, DI_STATE, DO_CHANNELS, DO_STATE, FEEDBACK_FAULT, LIFECYCLE};
+
+// Jauh di bawah debounce terpendek supaya debounce tetap akurat
+const SCAN_PERIOD: Duration = Duration::from_millis(10);
+
+#[embassy_executor::task]
+pub async fn inputs_task(mut inputs: [DiChannel<IoIn>; DI_COUNT]) {
+    // Indeks output yang di-link tiap input (nama → indeks sekali saja)
+    let links: [Option<usize>; DI_COUNT] = core::array::from_fn(|i| {
+        let out = inputs[i].config().feedback?.output;
+        DO_CHANNELS.iter().position(|c| c.name == out)
+    });
+    let mut ticker = Ticker::every(SCAN_PERIOD);
+
+    loop {
+        ticker.next().await;
+        let now = Instant::now().as_millis();
+        let outputs = DO_STATE.lock(|s| s.get());
+
+        for (idx, (ch, link)) in inputs.iter_mut().zip(links.iter()).enumerate() {
+            if let Some(on) = ch.poll(now) { publish(TelemetryEvent::Input { idx, on }); }
+            let Some(event) = link.and_then(|o| ch.check(outputs[o].on, now)) else { continue };
+            publish(TelemetryEvent::Feedback { idx, event });
+            if FEEDBACK_FAULT && matches!(event, FeedbackEvent::Raised(_)) {
+                let cause = FaultCause::Feedback(ch.config().name);
+                if let Some(t) = LIFECYCLE.lock(|lc| lc.borrow_mut().raise(cause, now)) {
+                    publish(TelemetryEvent::Phase(t));
+                }
+            }
+        }
+        DI_STATE.lock(|s| s.set(core::array::from_fn(|i| inputs[i].state())));
+    }
+}
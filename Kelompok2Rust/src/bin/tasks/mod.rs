@@ -0,0 +1,363 @@
+//! Task-task firmware (Embassy). Komunikasi antar task lewat Signal/Channel:
+//!
+//! sensor ──MEASUREMENT──▶ control ──ACTUATOR_CMD──▶ actuator (servo + kanal DO) ◀──DO_REQUEST── console
+//!   │                     ▲     │
+//!   │                     │     └──TELEMETRY──▶ telemetry
+//!   │     INTERLOCKS, LIFECYCLE, SETPOINT (diedit/diperintah dari console)
+//!   │
+//!   ├──BUS_DIAG──▶ telemetry, console (STATUS)
+//!   └──CALIBRATION (diset console, disimpan ke CONFIG_STORE)
+//!
+//! console ──SERVO_CAL / SERVO_RAW (rutin kalibrasi)──▶ actuator
+//!
+//! inputs ──DI_STATE──▶ console; feedback vs DO_STATE ──TELEMETRY / LIFECYCLE (FAULT)
+//!
+//! kanal remote: actuator ──REMOTE_DO──▶ sensor (FC01/05) ; sensor (FC02) ──REMOTE_DI──▶ inputs
+
+use core::cell::{Cell, RefCell};
+use embassy_sync::blocking_mutex::{raw::CriticalSectionRawMutex, Mutex};
+use embassy_sync::channel::Channel;
+use embassy_sync::signal::Signal;
+use esp_storage::FlashStorage;
+
+use hello_rust::calib::CalibrationSet;
+use hello_rust::config::{Config, ConfigError, ConfigStore};
+pub use hello_rust::control::ActuatorCmd;
+use hello_rust::control::{LoopStatus, Strategy, DEFAULT_SETPOINT};
+use hello_rust::din::{DiConfig, DiState, Feedback, FeedbackEvent};
+use hello_rust::dout::{DoConfig, DoState};
+use hello_rust::interlock::{InterlockState, InterlockTable};
+use hello_rust::lifecycle::{Lifecycle, Phase, Transition};
+use hello_rust::modbus::diag::BusDiagnostics;
+use hello_rust::modbus::discover::{ScanConfig, ScanEvent};
+use hello_rust::modbus::FC_READ_INPUT;
+use hello_rust::post::{Outcome, Post, PostPlan, Report};
+use hello_rust::remote::{CoilImage, InputImage, IoPoint};
+use hello_rust::rs485::DirectionConfig;
+use hello_rust::sched::Scheduler;
+use hello_rust::servo::{ServoCal, ServoCalError};
+use hello_rust::servo_fb::{FbCal, PositionMonitor, StallConfig, StallEvent};
+use hello_rust::setpoint::SetpointGen;
+use hello_rust::store::{FlashSpill, SampleBuffer};
+pub use hello_rust::sht20::Measurement;
+use hello_rust::sht20::{REG_RH, REG_TEMP};
+use hello_rust::sht2x::Resolution;
+use hello_rust::timesync::TimeSync;
+use hello_rust::timing::TimingStats;
+
+pub mod actuator;
+pub mod console;
+pub mod control;
+pub mod inputs;
+pub mod probe;
+pub mod reader;
+pub mod sensor;
+pub mod sniffer;
+pub mod telemetry;
+
+pub const BAUD: u32 = 9_600;
+pub const SID:  u8  = 1;
+/// Batas register per FC per slave untuk `discover` (output konsol tetap pendek).
+pub const DISCOVER_MAX_REGS: u16 = 32;
+
+// ===== Mode firmware (cargo feature) =====
+#[cfg(any(all(feature = "reader", feature = "probe"), all(feature = "reader", feature = "sniffer"), all(feature = "probe", feature = "sniffer")))]
+compile_error!("pilih satu saja dari feature `reader`, `probe`, `sniffer`");
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// Controller lengkap (bawaan): sensor → kontrol → servo + relay, konsol, telemetry.
+    Controller,
+    /// `--features reader`: hanya baca sensor tiap detik (FC & register di bawah), tanpa aktuator.
+    Reader,
+    /// `--features probe`: sekali baca RH & T dengan hex dump frame TX/RX, lalu diam.
+    Probe,
+    /// `--features sniffer`: penganalisa jalur Modbus pasif (tidak pernah transmit).
+    Sniffer,
+}
+
+pub const MODE: Mode = if cfg!(feature = "sniffer") {
+    Mode::Sniffer
+} else if cfg!(feature = "probe") {
+    Mode::Probe
+} else if cfg!(feature = "reader") {
+    Mode::Reader
+} else {
+    Mode::Controller
+};
+
+// Mode reader/probe: function code & register (modul lain bisa FC03 / alamat mulai 0x0000)
+pub const READER_FC:     u8  = FC_READ_INPUT;
+pub const READER_REG_RH: u16 = REG_RH;
+pub const READER_REG_T:  u16 = REG_TEMP;
+// Skala register (nilai × 10)
+pub const READER_SCALE:  f32 = 10.0;
+
+// ===== Sumber RH/T =====
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SensorSource {
+    /// Modul SHT20 RS485 (Modbus FC04, slave `SID`).
+    Modbus,
+    /// SHT2x langsung di I2C0 (SDA=GPIO8, SCL=GPIO9, pull-up 4k7); RS485 tetap untuk I/O remote & discovery.
+    I2c,
+}
+
+pub const SENSOR_SOURCE: SensorSource = SensorSource::Modbus;
+// Sumber I2C: resolusi RH/T (lebih rendah = ukur lebih cepat)
+pub const SHT2X_RESOLUTION: Resolution = Resolution::Rh12T14;
+
+// ===== RS485 arah manual (MAX485: DE + /RE dijumper ke satu GPIO) =====
+// false = modul auto-direction. Pin DE/RE dipilih di main.rs (default GPIO5).
+pub const RS485_MANUAL_DIR: bool = false;
+pub const RS485_DIR: DirectionConfig = DirectionConfig {
+    active_high: true, // MAX485: HIGH = transmit
+    guard_us:    100,  // tahan DE setelah TX done
+};
+
+// ===== Lifecycle =====
+// true = STANDBY → RUN otomatis setelah self-test / reset FAULT (perilaku lama; tidak setelah `stop`); false = tunggu `start` di konsol
+pub const AUTO_START: bool = true;
+
+// ===== Statistik waktu =====
+// Scan = polling sensor dimulai → perintah aktuator keluar dari task control
+pub const SCAN_BUDGET_US: u32 = 200_000;
+pub const SCAN_BUCKETS_US: [u32; 7] = [10_000, 20_000, 50_000, 100_000, 200_000, 500_000, 1_000_000];
+// Periode pulsa servo (nominal 20 ms); lebih lama dari ini dihitung telat
+pub const SERVO_LATE_US: u32 = 21_000;
+pub const SERVO_BUCKETS_US: [u32; 7] = [19_500, 19_900, 20_100, 20_500, 21_000, 22_000, 25_000];
+
+// ===== Feedback posisi servo =====
+// true = wiper potensiometer servo dibaca lewat ADC1 @GPIO6 (lewat pembagi supaya ≤ 3.1 V).
+// Stall baru dievaluasi setelah kalibrasi dari konsol (`servo fb cal <deg1> <deg2>`).
+pub const SERVO_FEEDBACK: bool = false;
+pub const SERVO_STALL: StallConfig = StallConfig::DEFAULT;
+// true = servo macet → FAULT; false = hanya dilaporkan
+pub const SERVO_STALL_FAULT: bool = true;
+
+// ===== Self-test saat boot (POST) =====
+// true = di SELF-TEST (boot dan tiap `reset`): cek config & sensor, stroke servo,
+// pulsa kipas (+ feedback kalau ada input yang di-link ke `FAN_DO`). Hasil di konsol/telemetry.
+pub const POST_ENABLED: bool = false;
+pub const POST_PLAN: PostPlan = PostPlan {
+    servo_stroke: true,
+    fan_pulse:    true,
+    fan_feedback: false, // diisi task control dari `DI_CHANNELS`
+    // config, sensor, servo, kipas; gagal di check kritis → FAULT (boot diblokir).
+    // Kipas kritis hanya kalau diskrepansi feedback juga FAULT saat jalan
+    critical:     [false, true, false, FEEDBACK_FAULT],
+};
+
+// ===== Store-and-forward =====
+// Sample di RAM (~10 menit @1 Hz) sebelum dipindah ke flash / dibuang
+pub const STORE_RAM_RECORDS: usize = 600;
+// true = luberan RAM ditulis ke partisi `spill` (butuh partitions.csv, lihat README)
+pub const SPILL_TO_FLASH: bool = false;
+pub const SPILL_PARTITION: &str = "spill";
+// Maks. record yang dikirim ulang per event telemetry (supaya println tidak menumpuk)
+pub const REPLAY_BURST: usize = 20;
+
+// ===== Konfigurasi persisten (kalibrasi sensor & servo) =====
+// Partisi flash A/B untuk `Config` (lihat partitions.csv)
+pub const CONFIG_PARTITION: &str = "config";
+
+// ===== Output digital (relay) =====
+// Tambah kanal = tambah baris di sini + pin di `main.rs` (urutan sama).
+// `point: IoPoint::Modbus { sid, addr }` = coil modul relay RS485 (bus yang sama
+// dengan SHT20, baudrate `BAUD`); pin di `main.rs` tetap diisi tapi tidak dipakai.
+pub const DO_COUNT: usize = 1;
+pub const DO_CHANNELS: [DoConfig; DO_COUNT] = [
+    DoConfig { name: "fan", active_low: true, power_on: false, point: IoPoint::Gpio }, // relay kipas @GPIO10
+    // Alternatif: kipas lewat modul relay Modbus ID 2, relay 1 (coil 0), aktif-HIGH
+    // DoConfig { name: "fan", active_low: false, power_on: false, point: IoPoint::Modbus { sid: 2, addr: 0 } },
+];
+// Kanal yang mengikuti `ActuatorCmd::fan_on` dari controller/interlock
+pub const FAN_DO: &str = "fan";
+
+// ===== Input digital =====
+// Tambah kanal = tambah baris di sini + pin di `main.rs` (urutan sama).
+// `feedback`: output yang harus diikuti input ini dalam `timeout_ms`.
+pub const DI_COUNT: usize = 1;
+pub const DI_CHANNELS: [DiConfig; DI_COUNT] = [
+    // Saklar arus kipas @GPIO11 (pull-up internal, kontak ke GND saat kipas jalan)
+    DiConfig { name: "fan_run", active_low: true, debounce_ms: 50, feedback: Some(Feedback { output: "fan", timeout_ms: 5_000 }), point: IoPoint::Gpio },
+    // Alternatif: discrete input 0 modul relay Modbus ID 2 (debounce efektif ≥ `REMOTE_IO_PERIOD_MS`)
+    // DiConfig { name: "fan_run", active_low: false, debounce_ms: 50, feedback: Some(Feedback { output: "fan", timeout_ms: 5_000 }), point: IoPoint::Modbus { sid: 2, addr: 0 } },
+];
+// Siklus sinkron kanal remote (baca balik + tulis coil, baca discrete input); digeser dari polling
+pub const REMOTE_IO_PERIOD_MS: u64 = 250;
+pub const REMOTE_IO_OFFSET_MS: u64 = 100;
+// true = diskrepansi feedback → FAULT; false = hanya dilaporkan (mis. saklar belum dipasang)
+pub const FEEDBACK_FAULT: bool = false;
+
+/// Hasil satu polling sensor + waktu akuisisi (monotonic, ms sejak boot).
+#[derive(Clone, Copy)]
+pub struct Reading {
+    /// Awal scan (µs), untuk statistik durasi scan.
+    pub start_us:    u64,
+    /// Nilai sensor apa adanya.
+    pub raw:         Measurement,
+    /// Setelah `CALIBRATION`: dipakai kontrol, interlock, telemetry.
+    pub measurement: Measurement,
+    pub t_ms:        u64,
+}
+
+/// Data yang dicetak oleh task telemetry.
+pub enum TelemetryEvent {
+    /// Hasil polling + fase unit saat itu.
+    Sample(Reading, Phase),
+    Actuators(ActuatorCmd),
+    /// Laporan periodik; isinya diambil dari `BUS_DIAG`.
+    BusDiag,
+    /// State aturan interlock `idx` (0-based) berubah.
+    Interlock { idx: usize, state: InterlockState },
+    Phase(Transition),
+    /// Host baru `listen`/`ack`: lanjutkan kirim record dari `STORE`.
+    Replay,
+    /// Input `idx` (indeks `DI_CHANNELS`) berubah setelah debounce.
+    Input { idx: usize, on: bool },
+    /// Alarm diskrepansi feedback input `idx` muncul/hilang.
+    Feedback { idx: usize, event: FeedbackEvent },
+    /// POST selesai; hasil di `POST_REPORT`.
+    SelfTest,
+    /// Laporan periodik `SCAN_TIMING` / `SERVO_TIMING`.
+    Timing,
+    /// Baris hasil scan discovery (`discover` di konsol).
+    Discovery(ScanEvent),
+    /// Posisi servo tidak mengikuti perintah / sudah mengikuti lagi.
+    ServoStall(StallEvent),
+}
+
+/// Perintah manual ke kanal output (indeks `DO_CHANNELS`) dari konsol.
+#[derive(Clone, Copy)]
+pub enum DoRequest {
+    Set(usize, bool),
+    Pulse(usize, u64),
+    Momentary(usize),
+}
+
+pub type Store = SampleBuffer<Option<FlashSpill<FlashStorage>>, STORE_RAM_RECORDS>;
+
+/// Snapshot state terakhir untuk perintah `status` di konsol.
+#[derive(Clone, Copy)]
+pub struct Status {
+    pub raw:         Measurement,
+    pub measurement: Measurement,
+    pub setpoint:    f32,
+    /// PV/SP loop [servo, kipas] menurut strategi aktif.
+    pub loops:       [LoopStatus; 2],
+    pub cmd:         ActuatorCmd,
+    pub polls:       u32,
+    pub phase:       Phase,
+}
+
+pub static MEASUREMENT:  Signal<CriticalSectionRawMutex, Reading> = Signal::new();
+pub static ACTUATOR_CMD: Signal<CriticalSectionRawMutex, ActuatorCmd> = Signal::new();
+pub static DO_REQUEST:   Channel<CriticalSectionRawMutex, DoRequest, 4> = Channel::new();
+/// Readback kanal output, diperbarui task actuator.
+pub static DO_STATE: Mutex<CriticalSectionRawMutex, Cell<[DoState; DO_COUNT]>> = Mutex::new(Cell::new([DoState::OFF; DO_COUNT]));
+/// State input setelah debounce + alarm feedback, diperbarui task inputs.
+pub static DI_STATE: Mutex<CriticalSectionRawMutex, Cell<[DiState; DI_COUNT]>> = Mutex::new(Cell::new([DiState::OFF; DI_COUNT]));
+/// Image coil kanal output remote: `desired` dari task actuator, `actual` dari task sensor.
+pub static REMOTE_DO: Mutex<CriticalSectionRawMutex, Cell<[CoilImage; DO_COUNT]>> = Mutex::new(Cell::new([CoilImage::OFF; DO_COUNT]));
+/// Image discrete input kanal input remote; diisi task sensor, dibaca task inputs.
+pub static REMOTE_DI: Mutex<CriticalSectionRawMutex, Cell<[InputImage; DI_COUNT]>> = Mutex::new(Cell::new([InputImage::UNKNOWN; DI_COUNT]));
+pub static TELEMETRY:    Channel<CriticalSectionRawMutex, TelemetryEvent, 8> = Channel::new();
+/// Salinan counter bus dari task sensor (untuk telemetry & konsol).
+pub static BUS_DIAG:   Mutex<CriticalSectionRawMutex, Cell<BusDiagnostics>> = Mutex::new(Cell::new(BusDiagnostics::new()));
+/// Durasi scan sensor → kontrol → perintah aktuator; diisi task control.
+pub static SCAN_TIMING: Mutex<CriticalSectionRawMutex, RefCell<TimingStats>> = Mutex::new(RefCell::new(TimingStats::new(SCAN_BUDGET_US, SCAN_BUCKETS_US)));
+/// Periode pulsa servo sebenarnya; diisi task actuator.
+pub static SERVO_TIMING: Mutex<CriticalSectionRawMutex, RefCell<TimingStats>> = Mutex::new(RefCell::new(TimingStats::new(SERVO_LATE_US, SERVO_BUCKETS_US)));
+/// Jadwal task sensor (job `poll`, `report`, dan `io` kalau ada kanal remote); counter overrun dibaca konsol/telemetry.
+pub static SENSOR_SCHED: Mutex<CriticalSectionRawMutex, RefCell<Scheduler<3>>> = Mutex::new(RefCell::new(Scheduler::new(0)));
+/// Scan discovery dari konsol; dijalankan task sensor di slot polling berikutnya.
+pub static DISCOVER: Signal<CriticalSectionRawMutex, ScanConfig> = Signal::new();
+/// Permintaan reset counter bus dari konsol.
+pub static DIAG_RESET: Signal<CriticalSectionRawMutex, ()> = Signal::new();
+/// Tabel interlock; dievaluasi task control, diedit dari konsol.
+/// Diisi `InterlockTable::defaults()` saat task control start.
+pub static INTERLOCKS: Mutex<CriticalSectionRawMutex, RefCell<InterlockTable>> = Mutex::new(RefCell::new(InterlockTable::empty()));
+pub static STATUS: Mutex<CriticalSectionRawMutex, Cell<Status>> = Mutex::new(Cell::new(Status {
+    raw:         Measurement { rh: None, temp: None },
+    measurement: Measurement { rh: None, temp: None },
+    setpoint:    DEFAULT_SETPOINT,
+    loops:       [LoopStatus { pv: None, sp: DEFAULT_SETPOINT }; 2],
+    cmd:         ActuatorCmd { servo_deg: 0, fan_on: false },
+    polls:       0,
+    phase:       Phase::Init,
+}));
+/// Offset/drift epoch dari perintah `sync` host; dipakai telemetry untuk cap waktu sample.
+pub static TIME_SYNC: Mutex<CriticalSectionRawMutex, Cell<TimeSync>> = Mutex::new(Cell::new(TimeSync::new()));
+/// Sample yang belum di-ack host; diisi & dikirim task telemetry, `listen`/`ack` dari konsol.
+pub static STORE: Mutex<CriticalSectionRawMutex, RefCell<Store>> = Mutex::new(RefCell::new(SampleBuffer::new(None)));
+/// State machine unit; transisi kondisi di task control, perintah dari konsol.
+pub static LIFECYCLE: Mutex<CriticalSectionRawMutex, RefCell<Lifecycle>> = Mutex::new(RefCell::new(Lifecycle::new(AUTO_START)));
+/// Ramp/profil setpoint RH; dimajukan task control tiap sample, diperintah dari konsol.
+pub static SETPOINT: Mutex<CriticalSectionRawMutex, RefCell<SetpointGen>> = Mutex::new(RefCell::new(SetpointGen::new(DEFAULT_SETPOINT)));
+/// Variabel & setpoint tiap loop + feedforward T; dibaca task control tiap scan, diubah dari konsol.
+pub static STRATEGY: Mutex<CriticalSectionRawMutex, Cell<Strategy>> = Mutex::new(Cell::new(Strategy::DEFAULT));
+/// Koefisien kalibrasi sensor; dibaca task sensor, diubah dari konsol.
+pub static CALIBRATION: Mutex<CriticalSectionRawMutex, Cell<CalibrationSet>> = Mutex::new(Cell::new(CalibrationSet::IDENTITY));
+/// Kalibrasi servo (ujung, tempuh, batas, park); dibaca task actuator tiap periode.
+pub static SERVO_CAL: Mutex<CriticalSectionRawMutex, Cell<ServoCal>> = Mutex::new(Cell::new(ServoCal::DEFAULT));
+/// Kalibrasi potensiometer servo; dibaca task actuator tiap periode.
+pub static SERVO_FB: Mutex<CriticalSectionRawMutex, Cell<Option<FbCal>>> = Mutex::new(Cell::new(None));
+/// Salinan monitor posisi dari task actuator (None = `SERVO_FEEDBACK` mati).
+pub static SERVO_POS: Mutex<CriticalSectionRawMutex, Cell<Option<PositionMonitor>>> = Mutex::new(Cell::new(None));
+/// Pulsa mentah selama rutin kalibrasi servo (menimpa perintah controller); None = normal.
+pub static SERVO_RAW: Mutex<CriticalSectionRawMutex, Cell<Option<u32>>> = Mutex::new(Cell::new(None));
+/// POST yang sedang/terakhir berjalan; dimajukan task control.
+pub static POST: Mutex<CriticalSectionRawMutex, Cell<Option<Post>>> = Mutex::new(Cell::new(None));
+/// Hasil cek config saat boot (masuk ke tiap POST).
+pub static POST_CONFIG: Mutex<CriticalSectionRawMutex, Cell<Outcome>> = Mutex::new(Cell::new(Outcome::Skipped));
+/// Hasil POST terakhir yang selesai (konsol `post`, telemetry).
+pub static POST_REPORT: Mutex<CriticalSectionRawMutex, Cell<Option<Report>>> = Mutex::new(Cell::new(None));
+/// Penyimpanan `Config` di flash (None kalau partisi tidak ada → tidak persisten).
+pub static CONFIG_STORE: Mutex<CriticalSectionRawMutex, RefCell<Option<ConfigStore<FlashStorage>>>> = Mutex::new(RefCell::new(None));
+
+/// Kirim ke telemetry tanpa menunggu; kalau antrean penuh, event dibuang
+/// supaya task pengirim tidak pernah tertahan oleh printing.
+pub fn publish(event: TelemetryEvent) {
+    let _ = TELEMETRY.try_send(event);
+}
+
+pub fn update_status(f: impl FnOnce(&mut Status)) {
+    STATUS.lock(|s| {
+        let mut st = s.get();
+        f(&mut st);
+        s.set(st);
+    });
+}
+
+/// Snapshot semua state persisten.
+pub fn current_config() -> Config {
+    Config {
+        calib:    CALIBRATION.lock(|c| c.get()),
+        servo:    SERVO_CAL.lock(|c| c.get()),
+        strategy: STRATEGY.lock(|s| s.get()),
+        servo_fb: SERVO_FB.lock(|c| c.get()),
+    }
+}
+
+/// Terapkan `Config` hasil load ke state runtime.
+pub fn apply_config(cfg: &Config) {
+    CALIBRATION.lock(|c| c.set(cfg.calib));
+    STRATEGY.lock(|s| s.set(cfg.strategy));
+    SERVO_FB.lock(|c| c.set(cfg.servo_fb));
+    // `Config::decode` sudah memvalidasi kalibrasi servo & strategi
+    let _ = set_servo_cal(cfg.servo);
+}
+
+/// Kalibrasi servo baru (harus lolos `validate`); park ikut ke lifecycle.
+pub fn set_servo_cal(cal: ServoCal) -> Result<(), ServoCalError> {
+    cal.validate()?;
+    SERVO_CAL.lock(|c| c.set(cal));
+    LIFECYCLE.lock(|lc| lc.borrow_mut().set_park(cal.park_deg));
+    Ok(())
+}
+
+/// Simpan state persisten sekarang ke flash.
+pub fn save_config() -> Result<(), ConfigError> {
+    let cfg = current_config();
+    CONFIG_STORE.lock(|s| s.borrow_mut().as_mut().map_or(Err(ConfigError::Io), |s| s.save(&cfg)))
+}
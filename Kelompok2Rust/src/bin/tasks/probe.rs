@@ -0,0 +1,40 @@
+//! Mode `probe` (dulu `changes/main_2.rs`): sekali baca RH & T dengan hex dump
+//! frame TX/RX, untuk cek wiring, slave ID, dan register modul baru.
+
+use esp_println::{print, println};
+use hello_rust::hal::ModbusPort;
+use hello_rust::modbus::{parse_read_response, read_request, read_response_len};
+
+use crate::board::Rs485Pins;
+use super::{READER_FC, READER_REG_RH, READER_REG_T, READER_SCALE, SID};
+
+#[embassy_executor::task]
+pub async fn probe_task(pins: Rs485Pins) {
+    let mut port = pins.into_port();
+    println!("=== probe: slave {} | FC 0x{:02X} ===", SID, READER_FC);
+
+    for (name, reg, unit) in [("RH", READER_REG_RH, "%"), ("T", READER_REG_T, "°C")] {
+        let req = read_request(SID, READER_FC, reg, 1);
+        print_hex("TX", &req);
+        let mut resp = [0u8; 32];
+        let n = match port.transact(&req, &mut resp).await {
+            Ok(n) => n,
+            Err(e) => { println!("{} @0x{:04X}: tidak ada reply ({:?})", name, reg, e); continue; }
+        };
+        print_hex("RX", &resp[..n]);
+        if n < read_response_len(1) { println!("(frame {} byte, kurang dari {})", n, read_response_len(1)); }
+
+        let mut raw = [0u16; 1];
+        match parse_read_response(&resp[..n], SID, READER_FC, &mut raw) {
+            Ok(()) => println!("{} = {:.1} {}  (raw 0x{:04X}, reg 0x{:04X})", name, raw[0] as f32 / READER_SCALE, unit, raw[0], reg),
+            Err(e) => println!("{} @0x{:04X}: reply tidak valid ({:?})", name, reg, e),
+        }
+    }
+    println!("=== probe selesai; reset board untuk mengulang ===");
+}
+
+fn print_hex(tag: &str, data: &[u8]) {
+    print!("{} ({}):", tag, data.len());
+    for b in data { print!(" {:02X}", b); }
+    println!();
+}
@@ -0,0 +1,32 @@
+//! Mode `reader` (dulu `changes/main_1.rs`): baca RH & T tiap detik dengan FC
+//! dan register dari `READER_*`, tanpa kontrol/aktuator. Arah RS485 (DE/RE)
+//! sama dengan controller, lewat `Rs485Pins`.
+
+use embassy_time::{Duration, Ticker};
+use esp_println::println;
+use hello_rust::app::POLL_PERIOD_MS;
+use hello_rust::modbus::Master;
+
+use crate::board::{EmbassyClock, Rs485Pins};
+use super::{READER_FC, READER_REG_RH, READER_REG_T, READER_SCALE, SID};
+
+#[embassy_executor::task]
+pub async fn reader_task(pins: Rs485Pins) {
+    let mut bus = Master::new(pins.into_port(), EmbassyClock);
+    let mut ticker = Ticker::every(Duration::from_millis(POLL_PERIOD_MS));
+
+    loop {
+        ticker.next().await;
+        let rh = bus.read_u16(SID, READER_FC, READER_REG_RH).await;
+        let t = bus.read_u16(SID, READER_FC, READER_REG_T).await;
+        match (rh, t) {
+            (Ok(raw_rh), Ok(raw_t)) => {
+                // Suhu bisa negatif: pakai tafsiran signed kalau masuk akal
+                let t_s = raw_t as i16 as f32 / READER_SCALE;
+                let t = if (-40.0..=125.0).contains(&t_s) { t_s } else { raw_t as f32 / READER_SCALE };
+                println!("RH = {:.1} %  |  T = {:.1} °C", raw_rh as f32 / READER_SCALE, t);
+            }
+            (rh, t) => warn!("gagal baca register: RH {:?} | T {:?}", rh.err(), t.err()),
+        }
+    }
+}
@@ -0,0 +1,139 @@
+//! Task polling SHT20 via RS485 (Modbus RTU, FC04) atau SHT2x native via I2C
+//! (`SENSOR_SOURCE`) tiap ~1 detik, lalu kalibrasi.
+//! Jadwal (polling + laporan berkala + sinkron kanal I/O remote) lewat `Scheduler`
+//! di `SENSOR_SCHED`; task ini satu-satunya pemilik bus RS485.
+//! Jalan di executor prioritas tinggi supaya pin DE dilepas tepat setelah
+//! TX selesai, tidak tertunda println dari task lain.
+
+use embassy_time::{Instant, Timer};
+use hello_rust::app::POLL_PERIOD_MS;
+use hello_rust::hal::SetBaud;
+use hello_rust::modbus::discover::scan;
+use hello_rust::modbus::Master;
+use hello_rust::remote::{poll_inputs, sync_coils, CoilImage, IoPoint};
+use hello_rust::sched::Scheduler;
+use hello_rust::sht20::{Measurement, Sht20};
+use hello_rust::sht2x::{Kind, Sht2x, SOFT_RESET_MS};
+
+use crate::board::{EmbassyClock, Rs485Port, Rs485Pins, SensorI2c};
+use super::{publish, Reading, TelemetryEvent, BAUD, BUS_DIAG, CALIBRATION, DIAG_RESET, DISCOVER, DI_CHANNELS, DO_CHANNELS, LIFECYCLE, MEASUREMENT, REMOTE_DI, REMOTE_DO, REMOTE_IO_OFFSET_MS, REMOTE_IO_PERIOD_MS, SENSOR_SCHED, SHT2X_RESOLUTION, SID, TELEMETRY};
+
+// Laporan diagnostik bus + statistik waktu ke telemetry; digeser dari grid polling
+const REPORT_PERIOD_MS: u64 = 60_000;
+const REPORT_OFFSET_MS: u64 = 500;
+
+#[embassy_executor::task]
+pub async fn sensor_task(pins: Rs485Pins, i2c: Option<SensorI2c>) {
+    let mut bus = Master::new(pins.into_port(), EmbassyClock);
+    let sht20 = Sht20::new(SID);
+    let mut sht2x = i2c.map(Sht2x::new);
+    if let Some(s) = sht2x.as_mut() {
+        // Mulai dari state yang diketahui (heater mati), lalu resolusi dari config
+        if s.soft_reset().is_err() { warn!("SHT2x: soft reset gagal (cek wiring / pull-up I2C)"); }
+        Timer::after_millis(SOFT_RESET_MS as u64).await;
+        if s.set_resolution(SHT2X_RESOLUTION).is_err() { warn!("SHT2x: resolusi tidak bisa diset"); }
+    }
+    let do_points: [IoPoint; DO_CHANNELS.len()] = core::array::from_fn(|i| DO_CHANNELS[i].point);
+    let di_points: [IoPoint; DI_CHANNELS.len()] = core::array::from_fn(|i| DI_CHANNELS[i].point);
+    let remote = do_points.iter().chain(di_points.iter()).any(IoPoint::is_remote);
+    let (poll, report, io) = SENSOR_SCHED.lock(|s| {
+        let mut s = s.borrow_mut();
+        *s = Scheduler::new(Instant::now().as_millis());
+        (s.register("poll", POLL_PERIOD_MS, 0).unwrap(), s.register("report", REPORT_PERIOD_MS, REPORT_OFFSET_MS).unwrap(),
+            remote.then(|| s.register("io", REMOTE_IO_PERIOD_MS, REMOTE_IO_OFFSET_MS).unwrap()))
+    });
+
+    let mut last_overruns = [0u32; 3];
+
+    loop {
+        let next = SENSOR_SCHED.lock(|s| s.borrow().next_release()).unwrap_or(0);
+        Timer::at(Instant::from_millis(next)).await;
+        let Some((job, name, overruns)) = SENSOR_SCHED.lock(|s| {
+            let mut s = s.borrow_mut();
+            s.poll(Instant::now().as_millis()).map(|j| (j, s.name(j), s.stats(j).overruns))
+        }) else { continue };
+        // Counter bisa dinolkan dari konsol (`scan reset`)
+        if overruns != last_overruns[job.index()] {
+            last_overruns[job.index()] = overruns;
+            if overruns > 0 { warn!("job {} telat ≥ 1 periode (overrun #{})", name, overruns); }
+        }
+
+        if job == report {
+            publish(TelemetryEvent::BusDiag);
+            publish(TelemetryEvent::Timing);
+            continue;
+        }
+        if Some(job) == io {
+            sync_remote_io(&mut bus, &do_points, &di_points).await;
+            BUS_DIAG.lock(|d| d.set(*bus.diag()));
+            continue;
+        }
+        debug_assert_eq!(job, poll);
+        if DIAG_RESET.try_take().is_some() { bus.diag_mut().reset(); }
+        // Discovery menggantikan satu slot polling; counter bus & baudrate dipulihkan.
+        // Selama scan tidak ada sample: lifecycle ditahan di luar RUN (auto-start,
+        // sensor-lost), dan tiap baris ditunggu sampai masuk antrean telemetry.
+        if let Some(cfg) = DISCOVER.try_take() {
+            if LIFECYCLE.lock(|lc| lc.borrow_mut().hold()).is_err() {
+                warn!("discovery dibatalkan: unit sudah RUN");
+                continue;
+            }
+            let diag = *bus.diag();
+            scan(&mut bus, &cfg, |e| TELEMETRY.send(TelemetryEvent::Discovery(e))).await;
+            if bus.port_mut().set_baud(BAUD).is_err() { error!("gagal kembali ke {} bps", BAUD); }
+            *bus.diag_mut() = diag;
+            LIFECYCLE.lock(|lc| lc.borrow_mut().release(Instant::now().as_millis()));
+            continue;
+        }
+
+        // Cap waktu = awal transaksi (RH dan T dibaca berurutan dalam ~puluhan ms)
+        let start = Instant::now();
+        let t_ms = start.as_millis();
+        let raw = match sht2x.as_mut() {
+            Some(s) => read_sht2x(s).await,
+            None => sht20.read(&mut bus).await,
+        };
+        let measurement = CALIBRATION.lock(|c| c.get()).apply(&raw);
+        trace!("raw RH {:?} T {:?}", raw.rh, raw.temp);
+        MEASUREMENT.signal(Reading { start_us: start.as_micros(), raw, measurement, t_ms });
+
+        BUS_DIAG.lock(|d| d.set(*bus.diag()));
+    }
+}
+
+/// RH lalu T lewat I2C; task lain jalan selama sensor mengukur.
+async fn read_sht2x(sht: &mut Sht2x<SensorI2c>) -> Measurement {
+    let mut value = [None; 2];
+    for (v, kind) in value.iter_mut().zip([Kind::Humidity, Kind::Temperature]) {
+        if sht.start(kind).is_err() { continue; }
+        Timer::after_millis(sht.measurement_ms(kind) as u64).await;
+        *v = sht.read(kind).map_err(|e| debug!("SHT2x {:?}: {:?}", kind, e)).ok();
+    }
+    Measurement { rh: value[0], temp: value[1] }
+}
+
+/// Satu siklus kanal remote. Image dikopi dulu (tidak ada lock selama transaksi);
+/// `desired` yang diubah task actuator selama itu ikut di siklus berikutnya.
+async fn sync_remote_io(bus: &mut Master<Rs485Port, EmbassyClock>, do_points: &[IoPoint], di_points: &[IoPoint]) {
+    let before = REMOTE_DO.lock(|r| r.get());
+    let mut coils = before;
+    sync_coils(bus, do_points, &mut coils).await;
+    REMOTE_DO.lock(|r| {
+        let mut img = r.get();
+        for (dst, src) in img.iter_mut().zip(coils.iter()) { *dst = CoilImage { desired: dst.desired, ..*src }; }
+        r.set(img);
+    });
+
+    let inputs_before = REMOTE_DI.lock(|r| r.get());
+    let mut inputs = inputs_before;
+    poll_inputs(bus, di_points, &mut inputs).await;
+    REMOTE_DI.lock(|r| r.set(inputs));
+
+    // Lapor hanya saat modul berhenti / kembali menjawab, bukan tiap siklus
+    let changes = before.iter().map(|c| c.fails).zip(coils.iter().map(|c| c.fails)).zip(DO_CHANNELS.iter().map(|c| c.name))
+        .chain(inputs_before.iter().map(|c| c.fails).zip(inputs.iter().map(|c| c.fails)).zip(DI_CHANNELS.iter().map(|c| c.name)));
+    for ((was, now), name) in changes {
+        if was == 0 && now > 0 { warn!("kanal remote {} tidak menjawab", name); }
+        if was > 0 && now == 0 { info!("kanal remote {} menjawab lagi", name); }
+    }
+}
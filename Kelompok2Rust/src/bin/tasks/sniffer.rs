@@ -0,0 +1,52 @@
+//! Mode `sniffer`: dengar pasif bus RS485 (tanpa transmit), potong jadi frame
+//! RTU berdasar jeda t3.5, pasangkan request ↔ response, cetak dengan cap waktu.
+
+use embassy_time::{with_timeout, Duration, Instant};
+use esp_println::{print, println};
+use hello_rust::modbus::sniff::{Frame, FrameSplitter, Sniffer};
+use hello_rust::rs485::{char_time_us, t3_5_us};
+
+use crate::board::Rs485Pins;
+use super::BAUD;
+
+// Byte hex per frame yang dicetak (sisanya "...")
+const HEX_MAX: usize = 32;
+
+#[embassy_executor::task]
+pub async fn sniffer_task(pins: Rs485Pins) {
+    let mut rx = pins.into_listener().rx;
+    let mut splitter = FrameSplitter::new(BAUD);
+    let mut sniffer = Sniffer::new();
+    let char_us = char_time_us(BAUD) as u64;
+    let gap = Duration::from_micros(t3_5_us(BAUD) as u64);
+    println!("=== sniffer: {} bps, jeda frame {} µs (dengar saja) ===", BAUD, t3_5_us(BAUD));
+
+    let mut buf = [0u8; 64];
+    loop {
+        match with_timeout(gap, rx.read_async(&mut buf)).await {
+            Ok(Ok(n)) => {
+                // Waktu per byte direkonstruksi mundur dari waktu chunk diterima
+                // (RX timeout UART < t3.5, jadi satu chunk = satu frame atau bagiannya)
+                let now = Instant::now().as_micros();
+                for (i, &b) in buf[..n].iter().enumerate() {
+                    let t = now.saturating_sub((n - 1 - i) as u64 * char_us);
+                    if let Some(f) = splitter.push(b, t) { print_frame(&f, &mut sniffer); }
+                }
+            }
+            Ok(Err(e)) => warn!("UART RX error {:?}", e),
+            Err(_) => {
+                if let Some(f) = splitter.flush(Instant::now().as_micros()) { print_frame(&f, &mut sniffer); }
+            }
+        }
+    }
+}
+
+fn print_frame(f: &Frame, sniffer: &mut Sniffer) {
+    let d = sniffer.decode(f);
+    let b = f.bytes();
+    print!("[{:>6}.{:06}] {:>3} B |", f.start_us / 1_000_000, f.start_us % 1_000_000, b.len());
+    for x in b.iter().take(HEX_MAX) { print!(" {:02X}", x); }
+    if b.len() > HEX_MAX || f.overflow { print!(" ..."); }
+    println!();
+    println!("                {}", d);
+}
@@ -0,0 +1,255 @@
+//! Task telemetry: satu-satunya tempat data proses dicetak ke serial.
+//! Format baris RH/T dipertahankan supaya `join_dwsim_realsensor.py` tetap bisa parse.
+//! Hanya baris RH/T yang memakai '='; baris lain (raw, loop, `[rec]`, tabel
+//! diag/discover/timing, dst.) sengaja tanpa '=' supaya tidak tertangkap regex
+//! `T = ...` / `RH = ...` script Python.
+
+use embassy_time::Instant;
+use esp_println::{print, println};
+use hello_rust::control::{LoopConfig, LoopStatus, Strategy};
+use hello_rust::din::FeedbackEvent;
+use hello_rust::lifecycle::{Phase, Transition};
+use hello_rust::log::Level;
+use hello_rust::modbus::diag::BusDiagnostics;
+use hello_rust::modbus::discover::ScanEvent;
+use hello_rust::modbus::ModbusError;
+use hello_rust::post::{Check, Outcome, Report};
+use hello_rust::servo_fb::{PositionMonitor, StallEvent};
+use hello_rust::store::Spill;
+use hello_rust::timing::TimingStats;
+
+use super::{TelemetryEvent, BUS_DIAG, CALIBRATION, DI_CHANNELS, INTERLOCKS, LIFECYCLE, POST_REPORT, REPLAY_BURST, SCAN_TIMING, SENSOR_SCHED, SERVO_POS, SERVO_TIMING, SETPOINT, STATUS, STORE, STRATEGY, TELEMETRY, TIME_SYNC};
+
+#[embassy_executor::task]
+pub async fn telemetry_task() {
+    // Ringkasan POST ikut di frame sample pertama setelah POST selesai
+    let mut post_pending = false;
+    loop {
+        match TELEMETRY.receive().await {
+            TelemetryEvent::Sample(r, phase) => {
+                let m = r.measurement;
+                let sync = TIME_SYNC.lock(|t| t.get());
+                println!("\n[Polling Sensor] -----------------------------");
+                match sync.epoch_ms(r.t_ms) {
+                    Some(ts) => println!("🕒 ts {} ms | sync {}", ts, sync.quality(r.t_ms)),
+                    None     => println!("🕒 ts - | uptime {} ms | sync {}", r.t_ms, sync.quality(r.t_ms)),
+                }
+                match m.rh {
+                    Some(rh) => println!("✅ RH = {:.1} %", rh),
+                    None     => println!("⚠️  No/invalid reply for 0x0002 (RH)"),
+                }
+                match m.temp {
+                    Some(t) => println!("🌡️  T  = {:.1} °C", t),
+                    None    => println!("⚠️  No/invalid reply for 0x0001 (Temp)"),
+                }
+                // Nilai mentah hanya dicetak kalau ada kalibrasi
+                if !CALIBRATION.lock(|c| c.get()).is_identity() {
+                    let v = |x: Option<f32>| x.unwrap_or(f32::NAN);
+                    println!("📐 raw RH {:.1} % | T {:.1} °C", v(r.raw.rh), v(r.raw.temp));
+                }
+                let (sp, mode) = SETPOINT.lock(|g| { let g = g.borrow(); (g.value(), g.state()) });
+                println!("🎯 Setpoint      → {:.1} % ({})", sp, mode);
+                // PV per loop hanya kalau strategi bukan bawaan (RH ke dua loop)
+                let strategy = STRATEGY.lock(|s| s.get());
+                if strategy != Strategy::DEFAULT {
+                    let loops = STATUS.lock(|s| s.get().loops);
+                    print_loop("servo", &strategy.servo, loops[0]);
+                    print_loop("kipas", &strategy.fan, loops[1]);
+                }
+                println!("🧭 Fase          → {}", phase);
+                if let Some(pos) = SERVO_POS.lock(|p| p.get()) { print_servo_pos(&pos); }
+                if post_pending {
+                    post_pending = false;
+                    if let Some(report) = POST_REPORT.lock(|r| r.get()) { print_post_summary(&report); }
+                }
+
+                let erase_due = STORE.lock(|s| {
+                    let mut s = s.borrow_mut();
+                    s.push(r.t_ms, sync.epoch_ms(r.t_ms), sync.quality(r.t_ms), m);
+                    s.spill_erase_due()
+                });
+                if erase_due { erase_spill_ahead(); }
+                send_records();
+            }
+            TelemetryEvent::Actuators(cmd) => {
+                println!("🔧 Servo target → {}°", cmd.servo_deg);
+                println!("💨 Fan state     → {}", if cmd.fan_on { "ON" } else { "OFF" });
+                println!("-----------------------------------------------");
+            }
+            // Laporan berkala: tabel penuh hanya kalau level info aktif untuk modul ini
+            TelemetryEvent::BusDiag if log_enabled!(Level::Info) => print_bus_diag(&BUS_DIAG.lock(|d| d.get())),
+            TelemetryEvent::Timing if log_enabled!(Level::Info) => print_timing(),
+            TelemetryEvent::BusDiag | TelemetryEvent::Timing => {}
+            TelemetryEvent::Interlock { idx, state } => {
+                let Some(rule) = INTERLOCKS.lock(|t| t.borrow().get(idx).copied()) else { continue };
+                match (state.active, state.tripped) {
+                    (true, _)      => warn!("🔒 interlock #{} AKTIF ({})", idx + 1, rule),
+                    (false, true)  => info!("🔒 interlock #{} TRIP, tidak memaksa ({})", idx + 1, rule),
+                    (false, false) => info!("🔒 interlock #{} normal ({})", idx + 1, rule),
+                }
+            }
+            TelemetryEvent::Discovery(e) => print_discovery(&e),
+            TelemetryEvent::Phase(t) => print_transition(&t),
+            TelemetryEvent::Replay => send_records(),
+            TelemetryEvent::Input { idx, on } => {
+                debug!("🔌 input {} → {}", DI_CHANNELS[idx].name, if on { "ON" } else { "OFF" });
+            }
+            TelemetryEvent::SelfTest => {
+                if let Some(report) = POST_REPORT.lock(|r| r.get()) { log_post(&report); }
+                post_pending = true;
+            }
+            TelemetryEvent::Feedback { idx, event } => {
+                let cfg = DI_CHANNELS[idx];
+                let out = cfg.feedback.map_or("-", |f| f.output);
+                match event {
+                    FeedbackEvent::Raised(d) => warn!("🚨 input {} vs output {}: {}", cfg.name, out, d),
+                    FeedbackEvent::Cleared   => info!("✅ input {} vs output {}: sesuai lagi", cfg.name, out),
+                }
+            }
+            TelemetryEvent::ServoStall(event) => match event {
+                StallEvent::Stalled { cmd_deg, actual_deg } => warn!("🚨 servo macet: perintah {}° | aktual {:.1}°", cmd_deg, actual_deg),
+                StallEvent::Cleared => info!("✅ servo mengikuti perintah lagi"),
+            },
+        }
+    }
+}
+
+/// Kirim record yang belum terkirim (maks. `REPLAY_BURST`) kalau host sedang `listen`.
+fn send_records() {
+    let now = Instant::now().as_millis();
+    for _ in 0..REPLAY_BURST {
+        let Some(rec) = STORE.lock(|s| s.borrow_mut().next_to_send(now)) else { break };
+        println!("[rec] {}", rec);
+    }
+}
+
+/// Hapus sektor spill berikutnya di luar lock `STORE` (erase ±50 ms). Aman karena
+/// hanya task ini dan console (executor yang sama) yang memakai `STORE`.
+fn erase_spill_ahead() {
+    let mut spill = STORE.lock(|s| s.borrow_mut().take_spill());
+    spill.erase_ahead();
+    STORE.lock(|s| s.borrow_mut().restore_spill(spill));
+}
+
+fn print_transition(t: &Transition) {
+    match LIFECYCLE.lock(|lc| lc.borrow().fault()) {
+        Some(cause) if t.to == Phase::Fault => error!("🧭 fase {} → {} ({})", t.from, t.to, cause),
+        _ => info!("🧭 fase {} → {}", t.from, t.to),
+    }
+}
+
+/// Hasil POST per check (konsol `post`).
+pub fn print_post(report: &Report) {
+    for (check, outcome) in report.iter() {
+        let mark = match outcome {
+            Outcome::Pass => "✅",
+            Outcome::Fail(_) => "❌",
+            _ => "➖",
+        };
+        println!("{} [post] {:<7} {}", mark, check.name(), outcome);
+    }
+}
+
+/// Hasil POST saat selesai: check gagal sebagai error.
+fn log_post(report: &Report) {
+    for (check, outcome) in report.iter() {
+        match outcome {
+            Outcome::Fail(_) => error!("❌ POST {:<7} {}", check.name(), outcome),
+            _ => info!("POST {:<7} {}", check.name(), outcome),
+        }
+    }
+}
+
+/// Posisi servo aktual vs perintah.
+pub fn print_servo_pos(pos: &PositionMonitor) {
+    let raw = pos.raw().unwrap_or(f32::NAN);
+    match (pos.actual_deg(), pos.cmd_deg()) {
+        (Some(act), Some(cmd)) => println!("🎚️  Servo         → perintah {}° | aktual {:.1}° (dev {:+.1}°){}", cmd, act,
+            act - cmd as f32, if pos.stalled() { " ⚠️ MACET" } else { "" }),
+        (Some(act), None) => println!("🎚️  Servo         → pulsa mentah | aktual {:.1}° (ADC {:.0})", act, raw),
+        (None, _) => println!("🎚️  Servo         → ADC {:.0} (feedback belum dikalibrasi)", raw),
+    }
+}
+
+/// Satu baris untuk frame telemetry.
+fn print_post_summary(report: &Report) {
+    match report.failures() {
+        0 => println!("🧪 POST          → OK"),
+        n => {
+            let first = report.iter().find(|(_, o)| matches!(o, Outcome::Fail(_))).map_or(Check::Config, |(c, _)| c);
+            println!("🧪 POST          → {} GAGAL (pertama: {})", n, first.name());
+        }
+    }
+}
+
+/// PV & SP satu loop seperti yang dipakai controller (`STATUS.loops`).
+fn print_loop(name: &str, l: &LoopConfig, now: LoopStatus) {
+    match now.pv {
+        Some(pv) => println!("🎛️  {} {} {:.2}{} → SP {:.2} band {:.2}", name, l.var, pv, l.var.unit(), now.sp, l.band),
+        None     => println!("🎛️  {} {} - (ditahan) → SP {:.2}", name, l.var, now.sp),
+    }
+}
+
+/// Tabel counter per slave.
+pub fn print_bus_diag(diag: &BusDiagnostics) {
+    println!("[diag] slave   req     ok   tout    crc    exc  badid  short  other | latency min/avg/max ms");
+    for (sid, c) in diag.iter() {
+        let ms = |us: Option<u32>| us.map_or(-1.0, |v| v as f32 / 1000.0);
+        println!("[diag] {:>5} {:>6} {:>6} {:>6} {:>6} {:>6} {:>6} {:>6} {:>6} | {:.1}/{:.1}/{:.1}",
+            sid, c.requests, c.ok, c.timeouts, c.crc_errors, c.exceptions, c.unexpected_id,
+            c.short_frames, c.other,
+            ms(c.latency_min_us()), ms(c.latency_avg_us()), ms(c.latency_max_us()));
+    }
+    if diag.untracked > 0 {
+        println!("[diag] request ke slave lain (tidak dilacak): {}", diag.untracked);
+    }
+}
+
+/// Baris tabel `discover`.
+fn print_discovery(e: &ScanEvent) {
+    match *e {
+        ScanEvent::Baud(baud) => println!("[disc] coba {} bps ...", baud),
+        ScanEvent::Found { baud, sid } => {
+            println!("[disc] ✅ slave {} menjawab di {} bps", sid, baud);
+            println!("[disc]    id  fc    addr    hex    dec");
+        }
+        ScanEvent::Register { sid, fc, addr, result } => match result {
+            Ok(v) => println!("[disc] {:>5}  {:02X}  0x{:04X}  {:04X} {:>6}", sid, fc, addr, v, v),
+            Err(ModbusError::Exception(code)) => println!("[disc] {:>5}  {:02X}  0x{:04X}  exception {}", sid, fc, addr, code),
+            Err(err) => println!("[disc] {:>5}  {:02X}  0x{:04X}  {:?}", sid, fc, addr, err),
+        },
+        ScanEvent::Done { found } => println!("[disc] selesai: {} slave ditemukan", found),
+    }
+}
+
+/// Statistik scan + jitter servo, satuan ms.
+pub fn print_timing() {
+    let scan = SCAN_TIMING.lock(|s| *s.borrow());
+    let servo = SERVO_TIMING.lock(|s| *s.borrow());
+    let ms = |us: Option<u32>| us.map_or(-1.0, |v| v as f32 / 1000.0);
+    println!("[scan] n {} | min/avg/max {:.1}/{:.1}/{:.1} ms | overrun {} (> {:.0} ms)",
+        scan.count(), ms(scan.min_us()), ms(scan.avg_us()), ms(scan.max_us()),
+        scan.overruns(), scan.budget_us() as f32 / 1000.0);
+    print_histogram("scan", &scan);
+    println!("[servo] periode min/avg/max {:.2}/{:.2}/{:.2} ms | jitter pk-pk {:.2} ms | telat {} (> {:.1} ms)",
+        ms(servo.min_us()), ms(servo.avg_us()), ms(servo.max_us()), ms(servo.spread_us()),
+        servo.overruns(), servo.budget_us() as f32 / 1000.0);
+    print_histogram("servo", &servo);
+    SENSOR_SCHED.lock(|s| {
+        for (_, name, period_ms, offset_ms, st) in s.borrow().iter() {
+            println!("[sched] {:<6} {} ms (+{}) | jalan {} | overrun {} lompat {} | telat maks {} ms",
+                name, period_ms, offset_ms, st.runs, st.overruns, st.skipped, st.max_late_ms);
+        }
+    });
+}
+
+fn print_histogram(tag: &str, stats: &TimingStats) {
+    print!("[{}] hist ms", tag);
+    for (edge, n) in stats.histogram() {
+        match edge {
+            Some(e) => print!(" <{:.1}:{}", e as f32 / 1000.0, n),
+            None    => print!(" lebih:{}", n),
+        }
+    }
+    println!();
+}
@@ -0,0 +1,202 @@
+//! Kalibrasi per kanal sensor: `kalibrasi = raw * gain + offset`.
+//! Diterapkan tepat setelah pembacaan, sebelum filter/kontrol/interlock.
+//!
+//! Satu titik : geser offset supaya raw sekarang = nilai referensi (gain tetap).
+//! Dua titik  : ambil dua pasang (raw, referensi) berjauhan → gain + offset.
+
+use core::fmt;
+
+use crate::sht20::Measurement;
+
+/// Gain di luar rentang ini hampir pasti salah input / titik tertukar.
+pub const GAIN_MIN: f32 = 0.5;
+pub const GAIN_MAX: f32 = 2.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Channel {
+    Rh,
+    Temp,
+}
+
+impl Channel {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s { "rh" => Some(Channel::Rh), "t" | "temp" => Some(Channel::Temp), _ => None }
+    }
+
+    pub const fn name(self) -> &'static str {
+        match self { Channel::Rh => "rh", Channel::Temp => "t" }
+    }
+
+    pub const fn unit(self) -> &'static str {
+        match self { Channel::Rh => "%", Channel::Temp => "°C" }
+    }
+
+    /// Jarak minimal dua titik referensi supaya gain bermakna.
+    pub const fn min_span(self) -> f32 {
+        match self { Channel::Rh => 10.0, Channel::Temp => 5.0 }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CalError {
+    /// Dua titik terlalu dekat (lihat `Channel::min_span`).
+    SpanTooSmall,
+    /// Gain hasil di luar `GAIN_MIN..=GAIN_MAX`.
+    GainOutOfRange,
+    /// Gain, offset, atau nilai referensi NaN/inf.
+    NotFinite,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Calibration {
+    pub gain:    f32,
+    pub offset:  f32,
+    /// Tanggal kalibrasi (epoch ms dari sync host); 0 = tidak diketahui.
+    pub date_ms: u64,
+}
+
+impl Default for Calibration {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Calibration {
+    pub const IDENTITY: Self = Self { gain: 1.0, offset: 0.0, date_ms: 0 };
+
+    pub fn apply(&self, raw: f32) -> f32 {
+        raw * self.gain + self.offset
+    }
+
+    pub fn is_identity(&self) -> bool {
+        self.gain == 1.0 && self.offset == 0.0
+    }
+
+    /// Koefisien manual; gain tetap dicek rentangnya.
+    pub fn new(gain: f32, offset: f32, date_ms: u64) -> Result<Self, CalError> {
+        if !gain.is_finite() || !offset.is_finite() { return Err(CalError::NotFinite); }
+        if !(GAIN_MIN..=GAIN_MAX).contains(&gain) { return Err(CalError::GainOutOfRange); }
+        Ok(Self { gain, offset, date_ms })
+    }
+
+    /// Kalibrasi satu titik: gain dipertahankan, offset digeser.
+    pub fn one_point(&self, raw: f32, reference: f32, date_ms: u64) -> Result<Self, CalError> {
+        if !raw.is_finite() || !reference.is_finite() { return Err(CalError::NotFinite); }
+        Self::new(self.gain, reference - raw * self.gain, date_ms)
+    }
+
+    /// Kalibrasi dua titik dari pasangan (raw, referensi).
+    pub fn two_point(ch: Channel, p1: (f32, f32), p2: (f32, f32), date_ms: u64) -> Result<Self, CalError> {
+        if ![p1.0, p1.1, p2.0, p2.1].iter().all(|v| v.is_finite()) { return Err(CalError::NotFinite); }
+        let (raw_span, ref_span) = (p2.0 - p1.0, p2.1 - p1.1);
+        if raw_span.abs() < ch.min_span() || ref_span.abs() < ch.min_span() {
+            return Err(CalError::SpanTooSmall);
+        }
+        let gain = ref_span / raw_span;
+        Self::new(gain, p1.1 - gain * p1.0, date_ms)
+    }
+}
+
+/// Koefisien semua kanal SHT20.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CalibrationSet {
+    pub rh:   Calibration,
+    pub temp: Calibration,
+}
+
+impl CalibrationSet {
+    pub const IDENTITY: Self = Self { rh: Calibration::IDENTITY, temp: Calibration::IDENTITY };
+
+    pub fn is_identity(&self) -> bool {
+        self.rh.is_identity() && self.temp.is_identity()
+    }
+
+    pub fn get(&self, ch: Channel) -> &Calibration {
+        match ch { Channel::Rh => &self.rh, Channel::Temp => &self.temp }
+    }
+
+    pub fn get_mut(&mut self, ch: Channel) -> &mut Calibration {
+        match ch { Channel::Rh => &mut self.rh, Channel::Temp => &mut self.temp }
+    }
+
+    /// Nilai mentah → terkalibrasi. RH dibatasi 0..100 %.
+    pub fn apply(&self, raw: &Measurement) -> Measurement {
+        Measurement {
+            rh:   raw.rh.map(|v| self.rh.apply(v).clamp(0.0, 100.0)),
+            temp: raw.temp.map(|v| self.temp.apply(v)),
+        }
+    }
+}
+
+impl fmt::Display for CalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            CalError::SpanTooSmall   => "dua titik terlalu dekat",
+            CalError::GainOutOfRange => "gain di luar 0.5..2.0",
+            CalError::NotFinite      => "nilai bukan angka berhingga",
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-3
+    }
+
+    #[test]
+    fn identity_passes_values_through() {
+        let m = Measurement { rh: Some(55.5), temp: Some(27.1) };
+        assert_eq!(CalibrationSet::IDENTITY.apply(&m), m);
+        assert_eq!(CalibrationSet::IDENTITY.apply(&Measurement::default()), Measurement::default());
+    }
+
+    #[test]
+    fn one_point_shifts_offset_and_keeps_gain() {
+        let cal = Calibration { gain: 1.1, offset: 0.0, date_ms: 0 }.one_point(50.0, 52.0, 123).unwrap();
+        assert!(close(cal.apply(50.0), 52.0));
+        assert_eq!(cal.gain, 1.1);
+        assert_eq!(cal.date_ms, 123);
+    }
+
+    #[test]
+    fn two_point_fits_gain_and_offset() {
+        // Sensor membaca 3 % terlalu rendah di 33 % dan 5 % terlalu rendah di 75 %
+        let cal = Calibration::two_point(Channel::Rh, (30.0, 33.0), (70.0, 75.0), 0).unwrap();
+        assert!(close(cal.apply(30.0), 33.0));
+        assert!(close(cal.apply(70.0), 75.0));
+        assert!(close(cal.gain, 1.05));
+
+        // Urutan titik tidak berpengaruh
+        let rev = Calibration::two_point(Channel::Rh, (70.0, 75.0), (30.0, 33.0), 0).unwrap();
+        assert!(close(rev.gain, cal.gain) && close(rev.offset, cal.offset));
+    }
+
+    #[test]
+    fn two_point_rejects_close_or_absurd_points() {
+        assert_eq!(Calibration::two_point(Channel::Rh, (50.0, 50.0), (55.0, 56.0), 0), Err(CalError::SpanTooSmall));
+        assert_eq!(Calibration::two_point(Channel::Temp, (20.0, 20.0), (30.0, 60.0), 0), Err(CalError::GainOutOfRange));
+        assert_eq!(Calibration::new(0.1, 0.0, 0), Err(CalError::GainOutOfRange));
+    }
+
+    #[test]
+    fn non_finite_values_are_rejected() {
+        for bad in [f32::NAN, f32::INFINITY, f32::NEG_INFINITY] {
+            assert_eq!(Calibration::new(bad, 0.0, 0), Err(CalError::NotFinite));
+            assert_eq!(Calibration::new(1.0, bad, 0), Err(CalError::NotFinite));
+            assert_eq!(Calibration::IDENTITY.one_point(50.0, bad, 0), Err(CalError::NotFinite));
+            assert_eq!(Calibration::IDENTITY.one_point(bad, 50.0, 0), Err(CalError::NotFinite));
+            assert_eq!(Calibration::two_point(Channel::Rh, (30.0, bad), (70.0, 75.0), 0), Err(CalError::NotFinite));
+        }
+        // Raw ekstrem tapi berhingga bisa menghasilkan offset inf
+        assert_eq!(Calibration::IDENTITY.one_point(f32::MAX, -f32::MAX, 0), Err(CalError::NotFinite));
+    }
+
+    #[test]
+    fn calibrated_rh_is_clamped() {
+        let set = CalibrationSet { rh: Calibration { gain: 1.0, offset: 5.0, date_ms: 0 }, ..CalibrationSet::IDENTITY };
+        assert_eq!(set.apply(&Measurement { rh: Some(98.0), temp: None }).rh, Some(100.0));
+    }
+}
@@ -0,0 +1,51 @@
+//! Penulis/pembaca payload biner sederhana (little-endian, tanpa alokasi),
+//! dipakai bersama `config` dan `retain`. Encoding field tetap milik modul
+//! masing-masing.
+
+pub struct Writer<'a> {
+    buf: &'a mut [u8],
+    pos: usize,
+}
+
+impl<'a> Writer<'a> {
+    pub fn new(buf: &'a mut [u8]) -> Self {
+        Self { buf, pos: 0 }
+    }
+
+    /// Jumlah byte yang sudah ditulis.
+    pub fn pos(&self) -> usize {
+        self.pos
+    }
+
+    /// Panic kalau buffer tidak cukup: ukuran blob tetap, dicek di test.
+    pub fn put(&mut self, bytes: &[u8]) {
+        self.buf[self.pos..self.pos + bytes.len()].copy_from_slice(bytes);
+        self.pos += bytes.len();
+    }
+
+    pub fn f32(&mut self, v: f32) {
+        self.put(&v.to_le_bytes());
+    }
+}
+
+pub struct Reader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(buf: &'a [u8]) -> Self {
+        Self { buf, pos: 0 }
+    }
+
+    /// None kalau payload habis.
+    pub fn take<const N: usize>(&mut self) -> Option<[u8; N]> {
+        let b = self.buf.get(self.pos..self.pos + N)?.try_into().ok()?;
+        self.pos += N;
+        Some(b)
+    }
+
+    pub fn f32(&mut self) -> Option<f32> {
+        Some(f32::from_le_bytes(self.take()?))
+    }
+}
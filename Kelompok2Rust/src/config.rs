@@ -0,0 +1,336 @@
+//! Konfigurasi persisten (kalibrasi sensor, servo & feedback servo, strategi
+//! kontrol) di partisi flash kecil.
+//!
+//! Dua slot A/B, masing-masing satu sektor erase. `save` selalu menulis ke slot
+//! yang tidak aktif dengan nomor generasi +1, jadi listrik mati saat menulis
+//! paling buruk hanya menghilangkan perubahan terakhir.
+//!
+//! Format slot (little-endian):
+//!   `"KCFG" | versi u8 | len u16 | gen u32 | payload[len] | crc16`
+//! Field baru selalu ditambah di akhir payload; blob lama yang lebih pendek
+//! tetap terbaca dan field yang belum ada memakai default.
+
+use embedded_storage::nor_flash::NorFlash;
+
+use crate::calib::{Calibration, CalibrationSet};
+use crate::codec::{Reader, Writer};
+use crate::control::{LoopConfig, Strategy, Variable};
+use crate::modbus::crc16;
+use crate::servo::ServoCal;
+use crate::servo_fb::FbCal;
+
+const MAGIC: [u8; 4] = *b"KCFG";
+const VERSION: u8 = 1;
+const HEADER_LEN: usize = 11;
+/// Batas ukuran blob (header + payload + crc).
+pub const MAX_BLOB: usize = 256;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Config {
+    pub calib: CalibrationSet,
+    pub servo: ServoCal,
+    pub strategy: Strategy,
+    /// Kalibrasi potensiometer servo (None = belum / feedback tidak dipakai).
+    pub servo_fb: Option<FbCal>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigError {
+    /// Belum pernah disimpan (kedua slot kosong).
+    Empty,
+    /// Ada isi tapi tidak ada slot dengan CRC valid.
+    Corrupt,
+    /// Flash gagal dibaca/ditulis.
+    Io,
+}
+
+fn put_cal(w: &mut Writer, c: &Calibration) {
+    w.put(&c.gain.to_le_bytes());
+    w.put(&c.offset.to_le_bytes());
+    w.put(&c.date_ms.to_le_bytes());
+}
+
+fn put_servo(w: &mut Writer, c: &ServoCal) {
+    w.put(&(c.min_us as u16).to_le_bytes());
+    w.put(&(c.max_us as u16).to_le_bytes());
+    for v in [c.range_deg, c.limit_lo, c.limit_hi, c.park_deg] { w.put(&(v as i16).to_le_bytes()); }
+    w.put(&[c.inverted as u8]);
+}
+
+fn put_control_loop(w: &mut Writer, l: &LoopConfig) {
+    let (tag, w_rh, w_t) = match l.var {
+        Variable::Rh       => (0, 0.0, 0.0),
+        Variable::Temp     => (1, 0.0, 0.0),
+        Variable::DewPoint => (2, 0.0, 0.0),
+        Variable::Weighted { w_rh, w_t } => (3, w_rh, w_t),
+    };
+    w.put(&[tag, l.setpoint.is_some() as u8]);
+    for v in [w_rh, w_t, l.setpoint.unwrap_or(0.0), l.band] { w.f32(v); }
+}
+
+fn put_servo_fb(w: &mut Writer, c: &Option<FbCal>) {
+    w.put(&[c.is_some() as u8]);
+    let c = c.unwrap_or(FbCal { deg_a: 0, raw_a: 0, deg_b: 0, raw_b: 0 });
+    for (deg, raw) in [(c.deg_a, c.raw_a), (c.deg_b, c.raw_b)] {
+        w.put(&(deg as i16).to_le_bytes());
+        w.put(&raw.to_le_bytes());
+    }
+}
+
+fn take_cal(r: &mut Reader) -> Option<Calibration> {
+    Some(Calibration {
+        gain: f32::from_le_bytes(r.take()?),
+        offset: f32::from_le_bytes(r.take()?),
+        date_ms: u64::from_le_bytes(r.take()?),
+    })
+}
+
+fn take_servo(r: &mut Reader) -> Option<ServoCal> {
+    let us = |b: [u8; 2]| u16::from_le_bytes(b) as u32;
+    let deg = |b: [u8; 2]| i16::from_le_bytes(b) as i32;
+    Some(ServoCal {
+        min_us: us(r.take()?),
+        max_us: us(r.take()?),
+        range_deg: deg(r.take()?),
+        limit_lo: deg(r.take()?),
+        limit_hi: deg(r.take()?),
+        park_deg: deg(r.take()?),
+        inverted: r.take::<1>()?[0] != 0,
+    })
+}
+
+fn take_control_loop(r: &mut Reader) -> Option<LoopConfig> {
+    let [tag, has_sp] = r.take()?;
+    let (w_rh, w_t, sp, band) = (r.f32()?, r.f32()?, r.f32()?, r.f32()?);
+    let var = match tag {
+        0 => Variable::Rh,
+        1 => Variable::Temp,
+        2 => Variable::DewPoint,
+        3 => Variable::Weighted { w_rh, w_t },
+        _ => return None,
+    };
+    Some(LoopConfig { var, setpoint: (has_sp != 0).then_some(sp), band })
+}
+
+/// None juga kalau tersimpan sebagai "belum dikalibrasi".
+fn take_servo_fb(r: &mut Reader) -> Option<FbCal> {
+    let [set] = r.take()?;
+    let mut point = || Some((i16::from_le_bytes(r.take()?) as i32, u16::from_le_bytes(r.take()?)));
+    let (a, b) = (point()?, point()?);
+    (set != 0).then_some(FbCal { deg_a: a.0, raw_a: a.1, deg_b: b.0, raw_b: b.1 })
+}
+
+fn take_strategy(r: &mut Reader) -> Option<Strategy> {
+    Some(Strategy { servo: take_control_loop(r)?, fan: take_control_loop(r)?, ff_gain: r.f32()?, ff_ref_c: r.f32()? })
+}
+
+impl Config {
+    fn encode_payload(&self, buf: &mut [u8]) -> usize {
+        let mut w = Writer::new(buf);
+        put_cal(&mut w, &self.calib.rh);
+        put_cal(&mut w, &self.calib.temp);
+        put_servo(&mut w, &self.servo);
+        put_control_loop(&mut w, &self.strategy.servo);
+        put_control_loop(&mut w, &self.strategy.fan);
+        w.f32(self.strategy.ff_gain);
+        w.f32(self.strategy.ff_ref_c);
+        put_servo_fb(&mut w, &self.servo_fb);
+        w.pos()
+    }
+
+    fn decode_payload(buf: &[u8]) -> Self {
+        let mut r = Reader::new(buf);
+        let mut cfg = Self::default();
+        if let Some(c) = take_cal(&mut r) { cfg.calib.rh = c; }
+        if let Some(c) = take_cal(&mut r) { cfg.calib.temp = c; }
+        // Kalibrasi servo yang tidak valid (mis. batas keras berubah) → bawaan
+        if let Some(c) = take_servo(&mut r).filter(|c| c.validate().is_ok()) { cfg.servo = c; }
+        if let Some(s) = take_strategy(&mut r).filter(|s| s.validate().is_ok()) { cfg.strategy = s; }
+        cfg.servo_fb = take_servo_fb(&mut r).filter(|c| c.validate().is_ok());
+        cfg
+    }
+
+    /// Blob lengkap satu slot; mengembalikan panjang terpakai.
+    pub fn encode(&self, gen: u32, out: &mut [u8; MAX_BLOB]) -> usize {
+        let len = self.encode_payload(&mut out[HEADER_LEN..MAX_BLOB - 2]);
+        out[0..4].copy_from_slice(&MAGIC);
+        out[4] = VERSION;
+        out[5..7].copy_from_slice(&(len as u16).to_le_bytes());
+        out[7..11].copy_from_slice(&gen.to_le_bytes());
+        let end = HEADER_LEN + len;
+        let crc = crc16(&out[..end]);
+        out[end..end + 2].copy_from_slice(&crc.to_le_bytes());
+        end + 2
+    }
+
+    /// `(gen, config)` atau None kalau magic/CRC tidak cocok.
+    pub fn decode(b: &[u8]) -> Option<(u32, Self)> {
+        let h = b.get(..HEADER_LEN)?;
+        if h[0..4] != MAGIC { return None; }
+        let len = u16::from_le_bytes([h[5], h[6]]) as usize;
+        let end = HEADER_LEN + len;
+        let crc = u16::from_le_bytes(b.get(end..end + 2)?.try_into().ok()?);
+        if crc16(&b[..end]) != crc { return None; }
+        let gen = u32::from_le_bytes(h[7..11].try_into().ok()?);
+        Some((gen, Self::decode_payload(&b[HEADER_LEN..end])))
+    }
+}
+
+/// Penyimpanan A/B di atas `NorFlash`.
+pub struct ConfigStore<F> {
+    flash: F,
+    base:  u32,
+    /// Slot aktif + generasinya (None = belum ada yang valid).
+    current: Option<(u32, u32)>,
+}
+
+impl<F: NorFlash> ConfigStore<F> {
+    const SLOT: u32 = MAX_BLOB.next_multiple_of(F::ERASE_SIZE) as u32;
+
+    /// `base`/`size` harus sejajar sektor dan muat dua slot.
+    pub fn new(flash: F, base: u32, size: u32) -> Option<Self> {
+        let erase = F::ERASE_SIZE as u32;
+        if !base.is_multiple_of(erase) || size < 2 * Self::SLOT { return None; }
+        Some(Self { flash, base, current: None })
+    }
+
+    /// Muat slot valid dengan generasi tertinggi.
+    pub fn load(&mut self) -> Result<Config, ConfigError> {
+        let mut best: Option<(u32, u32, Config)> = None;
+        let mut written = false;
+        for slot in 0..2 {
+            let mut b = [0u8; MAX_BLOB];
+            self.flash.read(self.base + slot * Self::SLOT, &mut b).map_err(|_| ConfigError::Io)?;
+            if b.iter().all(|&x| x == 0xFF) { continue; }
+            written = true;
+            if let Some((gen, cfg)) = Config::decode(&b) {
+                if best.is_none_or(|(g, _, _)| gen.wrapping_sub(g) as i32 > 0) { best = Some((gen, slot, cfg)); }
+            }
+        }
+        match best {
+            Some((gen, slot, cfg)) => {
+                self.current = Some((slot, gen));
+                Ok(cfg)
+            }
+            None if written => Err(ConfigError::Corrupt),
+            None => Err(ConfigError::Empty),
+        }
+    }
+
+    /// Tulis ke slot tidak aktif; slot lama baru "kalah" setelah tulisan baru valid.
+    pub fn save(&mut self, cfg: &Config) -> Result<(), ConfigError> {
+        let (slot, gen) = match self.current {
+            Some((slot, gen)) => (1 - slot, gen.wrapping_add(1)),
+            None => (0, 1),
+        };
+        let mut b = [0xFFu8; MAX_BLOB];
+        let len = cfg.encode(gen, &mut b).next_multiple_of(F::WRITE_SIZE);
+        let addr = self.base + slot * Self::SLOT;
+        self.flash.erase(addr, addr + Self::SLOT).map_err(|_| ConfigError::Io)?;
+        self.flash.write(addr, &b[..len]).map_err(|_| ConfigError::Io)?;
+        self.current = Some((slot, gen));
+        Ok(())
+    }
+
+    /// Generasi slot aktif (jumlah save sejak flash dihapus).
+    pub fn generation(&self) -> Option<u32> {
+        self.current.map(|(_, gen)| gen)
+    }
+}
+
+impl core::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            ConfigError::Empty   => "kosong",
+            ConfigError::Corrupt => "CRC rusak",
+            ConfigError::Io      => "flash error",
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::RamFlash;
+
+    fn cfg(offset: f32) -> Config {
+        let mut c = Config::default();
+        c.calib.rh = Calibration { gain: 1.05, offset, date_ms: 1_700_000_000_000 };
+        c.servo = ServoCal { min_us: 700, max_us: 2200, inverted: true, park_deg: 10, ..ServoCal::DEFAULT };
+        c.strategy.fan = LoopConfig { var: Variable::Weighted { w_rh: 1.0, w_t: -0.5 }, setpoint: Some(50.0), band: 1.5 };
+        c.strategy.ff_gain = 0.4;
+        c.servo_fb = Some(FbCal { deg_a: 0, raw_a: 3400, deg_b: 120, raw_b: 700 });
+        c
+    }
+
+    #[test]
+    fn blob_roundtrip_and_short_payload_defaults() {
+        let mut b = [0u8; MAX_BLOB];
+        let n = cfg(-1.5).encode(7, &mut b);
+        assert_eq!(Config::decode(&b[..n]), Some((7, cfg(-1.5))));
+
+        b[HEADER_LEN] ^= 1;
+        assert_eq!(Config::decode(&b[..n]), None);
+
+        // Payload versi lama tanpa field apa pun → default
+        let mut short = [0u8; HEADER_LEN + 2];
+        short[..4].copy_from_slice(&MAGIC);
+        short[4] = VERSION;
+        let crc = crc16(&short[..HEADER_LEN]);
+        short[HEADER_LEN..].copy_from_slice(&crc.to_le_bytes());
+        assert_eq!(Config::decode(&short), Some((0, Config::default())));
+
+        // Blob sebelum ada kalibrasi servo (hanya 2 kalibrasi sensor) → servo bawaan
+        let mut old = [0u8; MAX_BLOB];
+        cfg(-1.5).encode(3, &mut old);
+        let len = 32;
+        old[5..7].copy_from_slice(&(len as u16).to_le_bytes());
+        let crc = crc16(&old[..HEADER_LEN + len]);
+        old[HEADER_LEN + len..HEADER_LEN + len + 2].copy_from_slice(&crc.to_le_bytes());
+        let (_, dec) = Config::decode(&old).unwrap();
+        assert_eq!(dec.calib, cfg(-1.5).calib);
+        assert_eq!(dec.servo, ServoCal::DEFAULT);
+        assert_eq!(dec.strategy, Strategy::DEFAULT);
+        assert_eq!(dec.servo_fb, None);
+    }
+
+    #[test]
+    fn empty_flash_then_save_and_reload() {
+        let mut store = ConfigStore::new(RamFlash::new(), 0, 768).unwrap();
+        // RamFlash awalnya 0xAA (bukan kosong) → rusak; setelah erase → kosong
+        assert_eq!(store.load(), Err(ConfigError::Corrupt));
+
+        store.save(&cfg(1.0)).unwrap();
+        store.save(&cfg(2.0)).unwrap();
+        store.save(&cfg(3.0)).unwrap();
+        assert_eq!(store.generation(), Some(3));
+
+        let mut reboot = ConfigStore::new(store.flash, 0, 768).unwrap();
+        assert_eq!(reboot.load(), Ok(cfg(3.0)));
+        assert_eq!(reboot.generation(), Some(3));
+    }
+
+    #[test]
+    fn torn_write_falls_back_to_previous_slot() {
+        let mut store = ConfigStore::new(RamFlash::new(), 0, 768).unwrap();
+        store.save(&cfg(1.0)).unwrap(); // slot 0
+        store.save(&cfg(2.0)).unwrap(); // slot 1
+        store.flash.mem[256 + HEADER_LEN] ^= 0xFF;
+
+        let mut reboot = ConfigStore::new(store.flash, 0, 768).unwrap();
+        assert_eq!(reboot.load(), Ok(cfg(1.0)));
+        // Save berikutnya menimpa slot yang rusak, bukan slot yang valid
+        reboot.save(&cfg(4.0)).unwrap();
+        assert_eq!(reboot.load(), Ok(cfg(4.0)));
+        assert_eq!(reboot.generation(), Some(2));
+    }
+
+    #[test]
+    fn erased_flash_is_empty_and_bad_geometry_rejected() {
+        let mut flash = RamFlash::new();
+        flash.mem.fill(0xFF);
+        assert_eq!(ConfigStore::new(flash, 0, 768).unwrap().load(), Err(ConfigError::Empty));
+        assert!(ConfigStore::new(RamFlash::new(), 4, 768).is_none());
+        assert!(ConfigStore::new(RamFlash::new(), 0, 256).is_none());
+    }
+}
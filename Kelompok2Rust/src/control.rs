@@ -0,0 +1,293 @@
+//! Logika kontrol: variabel proses → sudut servo (0/60/120) dan kipas, relatif
+//! terhadap setpoint. Variabel tiap loop (servo, kipas) dipilih lewat `Strategy`:
+//! RH, T, titik embun, atau kombinasi berbobot, plus feedforward T ke loop RH.
+
+use core::fmt;
+
+use crate::hal::{DigitalOutput, Servo};
+use crate::servo::ServoCal;
+use crate::sht20::Measurement;
+
+/// Perintah ke aktuator.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ActuatorCmd {
+    pub servo_deg: i32,
+    pub fan_on:    bool,
+}
+
+/// Setpoint bawaan: ambang lama 60/70/80 %RH.
+pub const DEFAULT_SETPOINT: f32 = 65.0;
+/// Servo 0° di bawah SP − band, 120° di atas SP + band, 60° di antaranya.
+pub const SERVO_BAND: f32 = 5.0;
+/// Kipas ON di atas SP + offset ini.
+pub const FAN_ABOVE_SP: f32 = 15.0;
+
+/// Variabel proses yang dikontrol satu loop.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Variable {
+    /// %RH (bawaan).
+    Rh,
+    /// °C.
+    Temp,
+    /// Titik embun °C (Magnus), butuh RH dan T.
+    DewPoint,
+    /// `w_rh * RH + w_t * T` (indeks tanpa satuan).
+    Weighted { w_rh: f32, w_t: f32 },
+}
+
+impl Variable {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Variable::Rh       => "rh",
+            Variable::Temp     => "t",
+            Variable::DewPoint => "dp",
+            Variable::Weighted { .. } => "mix",
+        }
+    }
+
+    pub fn unit(&self) -> &'static str {
+        match self {
+            Variable::Rh => "%",
+            Variable::Temp | Variable::DewPoint => "°C",
+            Variable::Weighted { .. } => "",
+        }
+    }
+
+    /// Nilai dari pengukuran; None kalau komponen yang dibutuhkan tidak valid.
+    pub fn value(&self, m: &Measurement) -> Option<f32> {
+        match *self {
+            Variable::Rh       => m.rh,
+            Variable::Temp     => m.temp,
+            Variable::DewPoint => Some(dew_point(m.temp?, m.rh?)),
+            Variable::Weighted { w_rh, w_t } => Some(w_rh * m.rh? + w_t * m.temp?),
+        }
+    }
+}
+
+/// Titik embun (°C) dari T (°C) dan RH (%), rumus Magnus (Sonntag 1990).
+pub fn dew_point(t: f32, rh: f32) -> f32 {
+    const B: f32 = 17.62;
+    const C: f32 = 243.12;
+    let g = libm::logf(rh.clamp(0.1, 100.0) / 100.0) + B * t / (C + t);
+    C * g / (B - g)
+}
+
+/// Konfigurasi satu loop.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LoopConfig {
+    pub var:      Variable,
+    /// Setpoint tetap dalam satuan `var`; None = ikut setpoint generator (%RH, hanya untuk `Rh`).
+    pub setpoint: Option<f32>,
+    /// Servo: pita ± di sekitar SP. Kipas: ON di atas SP + band.
+    pub band:     f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Strategy {
+    pub servo:    LoopConfig,
+    pub fan:      LoopConfig,
+    /// Feedforward T → loop `Rh`: PV digeser `ff_gain` %RH per °C di atas `ff_ref_c`
+    /// (gain positif = udara lebih hangat, damper/kipas bereaksi lebih awal). 0 = mati.
+    pub ff_gain:  f32,
+    pub ff_ref_c: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StrategyError {
+    /// Variabel selain RH butuh setpoint tetap (generator bekerja dalam %RH).
+    NeedSetpoint,
+    /// Band ≤ 0 / bukan angka, atau bobot mix dua-duanya 0.
+    Invalid,
+}
+
+impl Strategy {
+    /// Perilaku lama: RH untuk kedua loop, tanpa feedforward.
+    pub const DEFAULT: Self = Self {
+        servo:    LoopConfig { var: Variable::Rh, setpoint: None, band: SERVO_BAND },
+        fan:      LoopConfig { var: Variable::Rh, setpoint: None, band: FAN_ABOVE_SP },
+        ff_gain:  0.0,
+        ff_ref_c: 25.0,
+    };
+
+    pub fn validate(&self) -> Result<(), StrategyError> {
+        for l in [&self.servo, &self.fan] {
+            if l.var != Variable::Rh && l.setpoint.is_none() { return Err(StrategyError::NeedSetpoint); }
+            if l.band <= 0.0 || !l.band.is_finite() || l.setpoint.is_some_and(|sp| !sp.is_finite()) { return Err(StrategyError::Invalid); }
+            if let Variable::Weighted { w_rh, w_t } = l.var {
+                if !w_rh.is_finite() || !w_t.is_finite() || (w_rh == 0.0 && w_t == 0.0) { return Err(StrategyError::Invalid); }
+            }
+        }
+        if !self.ff_gain.is_finite() || !self.ff_ref_c.is_finite() { return Err(StrategyError::Invalid); }
+        Ok(())
+    }
+
+    /// PV satu loop (dengan feedforward untuk loop RH).
+    pub fn pv(&self, l: &LoopConfig, m: &Measurement) -> Option<f32> {
+        let v = l.var.value(m)?;
+        match (l.var, m.temp) {
+            (Variable::Rh, Some(t)) if self.ff_gain != 0.0 => Some(v + self.ff_gain * (t - self.ff_ref_c)),
+            _ => Some(v),
+        }
+    }
+}
+
+impl Default for Strategy {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// PV & SP terakhir per loop (untuk telemetry).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct LoopStatus {
+    pub pv: Option<f32>,
+    pub sp: f32,
+}
+
+pub struct Controller {
+    cmd:      ActuatorCmd,
+    setpoint: f32,
+    strategy: Strategy,
+    /// [servo, kipas]
+    status:   [LoopStatus; 2],
+}
+
+impl Default for Controller {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Controller {
+    pub const fn new() -> Self {
+        Self {
+            cmd:      ActuatorCmd { servo_deg: 0, fan_on: false },
+            setpoint: DEFAULT_SETPOINT,
+            strategy: Strategy::DEFAULT,
+            status:   [LoopStatus { pv: None, sp: DEFAULT_SETPOINT }; 2],
+        }
+    }
+
+    /// Strategi baru (sudah lolos `validate`); berlaku mulai `update` berikutnya.
+    pub fn set_strategy(&mut self, strategy: Strategy) {
+        self.strategy = strategy;
+    }
+
+    pub fn strategy(&self) -> &Strategy {
+        &self.strategy
+    }
+
+    /// [servo, kipas] dari `update` terakhir.
+    pub fn status(&self) -> [LoopStatus; 2] {
+        self.status
+    }
+
+    /// Setpoint RH (%) untuk `update` berikutnya (dari generator ramp/profil).
+    pub fn set_setpoint(&mut self, sp: f32) {
+        self.setpoint = sp;
+    }
+
+    pub fn setpoint(&self) -> f32 {
+        self.setpoint
+    }
+
+    /// Hitung perintah baru. Loop yang PV-nya tidak valid menahan output
+    /// terakhirnya; None kalau tidak ada loop yang bisa dihitung.
+    pub fn update(&mut self, m: &Measurement) -> Option<ActuatorCmd> {
+        let st = self.strategy;
+        let [servo, fan] = [st.servo, st.fan].map(|l| LoopStatus { pv: st.pv(&l, m), sp: l.setpoint.unwrap_or(self.setpoint) });
+        self.status = [servo, fan];
+
+        // --------- Update target_deg berdasar PV loop servo ---------
+        if let Some(pv) = servo.pv {
+            let (sp, band) = (servo.sp, st.servo.band);
+            self.cmd.servo_deg = if pv > sp + band { 120 } else if pv < sp - band { 0 } else { 60 };
+        }
+
+        // Logika kipas
+        if let Some(pv) = fan.pv {
+            let on_above = fan.sp + st.fan.band;
+            if pv > on_above { self.cmd.fan_on = true; }
+            if pv < on_above { self.cmd.fan_on = false; }
+        }
+
+        (servo.pv.is_some() || fan.pv.is_some()).then_some(self.cmd)
+    }
+
+    pub fn cmd(&self) -> ActuatorCmd {
+        self.cmd
+    }
+
+    /// State internal (output terakhir, termasuk histeresis kipas) dari warm restart.
+    pub fn restore(&mut self, cmd: ActuatorCmd) {
+        self.cmd = cmd;
+    }
+}
+
+impl fmt::Display for Variable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Variable::Weighted { w_rh, w_t } => write!(f, "mix {:.2}*RH{:+.2}*T", w_rh, w_t),
+            v => f.write_str(v.name()),
+        }
+    }
+}
+
+impl fmt::Display for StrategyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            StrategyError::NeedSetpoint => "variabel selain rh butuh setpoint tetap",
+            StrategyError::Invalid      => "band harus > 0 dan bobot/angka valid",
+        })
+    }
+}
+
+/// Tulis perintah ke relay kipas dan servo (sudut → pulsa lewat kalibrasi `cal`).
+pub fn apply(cmd: &ActuatorCmd, cal: &ServoCal, fan: &mut impl DigitalOutput, servo: &mut impl Servo) {
+    fan.set(cmd.fan_on);
+    servo.set_pulse_us(cal.pulse_us(cmd.servo_deg));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn m(rh: f32, t: f32) -> Measurement {
+        Measurement { rh: Some(rh), temp: Some(t) }
+    }
+
+    #[test]
+    fn loops_follow_selected_variable_and_feedforward() {
+        // Titik embun 20 °C / 50 %RH ≈ 9.3 °C
+        assert!((dew_point(20.0, 50.0) - 9.3).abs() < 0.1);
+
+        let mut c = Controller::new();
+        // Bawaan (RH, SP 65): 72 %RH → servo 120°, kipas OFF
+        assert_eq!(c.update(&m(72.0, 30.0)), Some(ActuatorCmd { servo_deg: 120, fan_on: false }));
+
+        // Servo dari T (SP 28 ± 1 °C), kipas dari titik embun (ON di atas 18 + 2 °C)
+        let s = Strategy {
+            servo: LoopConfig { var: Variable::Temp, setpoint: Some(28.0), band: 1.0 },
+            fan:   LoopConfig { var: Variable::DewPoint, setpoint: Some(18.0), band: 2.0 },
+            ..Strategy::DEFAULT
+        };
+        assert_eq!(s.validate(), Ok(()));
+        c.set_strategy(s);
+        assert_eq!(c.update(&m(60.0, 25.0)), Some(ActuatorCmd { servo_deg: 0, fan_on: false }));
+        // 30 °C / 72 %RH → titik embun ≈ 24.4 °C
+        assert_eq!(c.update(&m(72.0, 30.0)), Some(ActuatorCmd { servo_deg: 120, fan_on: true }));
+        // T hilang: kedua loop menahan output
+        assert_eq!(c.update(&Measurement { rh: Some(50.0), temp: None }), None);
+        assert_eq!(c.cmd(), ActuatorCmd { servo_deg: 120, fan_on: true });
+
+        // Feedforward: 66 %RH di 35 °C dengan 0.5 %RH/°C di atas 25 °C → PV 71 → servo 120°
+        c.set_strategy(Strategy { ff_gain: 0.5, ..Strategy::DEFAULT });
+        assert_eq!(c.update(&m(66.0, 35.0)).map(|c| c.servo_deg), Some(120));
+        assert_eq!(c.status()[0].pv, Some(71.0));
+        c.set_strategy(Strategy::DEFAULT);
+        assert_eq!(c.update(&m(66.0, 35.0)).map(|c| c.servo_deg), Some(60));
+
+        let bad = Strategy { fan: LoopConfig { var: Variable::Weighted { w_rh: 1.0, w_t: 0.5 }, setpoint: None, band: 1.0 }, ..Strategy::DEFAULT };
+        assert_eq!(bad.validate(), Err(StrategyError::NeedSetpoint));
+    }
+}
@@ -0,0 +1,241 @@
+//! Input digital: debounce per kanal dan pemantau feedback "diperintah ON
+//! tapi tidak jalan" (mis. saklar arus kipas, limit switch damper).
+//!
+//! Kanal yang di-link ke output membandingkan state input (sudah di-debounce)
+//! dengan state yang diperintah ke output itu. Kalau berbeda lebih lama dari
+//! `timeout_ms` → alarm diskrepansi; alarm hilang sendiri begitu cocok lagi.
+
+use core::fmt;
+
+use crate::hal::RawInput;
+use crate::remote::IoPoint;
+
+/// Konfigurasi satu kanal (tabel di firmware).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiConfig {
+    pub name:        &'static str,
+    /// true = kontak menarik pin ke GND saat aktif (pull-up).
+    pub active_low:  bool,
+    /// Level harus stabil selama ini sebelum dianggap berubah.
+    pub debounce_ms: u64,
+    /// Output (nama kanal DO) yang seharusnya diikuti input ini.
+    pub feedback:    Option<Feedback>,
+    /// GPIO di board atau discrete input modul Modbus.
+    pub point:       IoPoint,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Feedback {
+    pub output:     &'static str,
+    /// Toleransi beda perintah ↔ feedback (start-up kipas, waktu tempuh damper).
+    pub timeout_ms: u64,
+}
+
+/// Debounce waktu: perubahan baru diterima setelah stabil `debounce_ms`.
+#[derive(Debug, Clone, Copy)]
+pub struct Debouncer {
+    debounce_ms: u64,
+    stable:      bool,
+    candidate:   bool,
+    since_ms:    u64,
+}
+
+impl Debouncer {
+    pub const fn new(debounce_ms: u64, initial: bool) -> Self {
+        Self { debounce_ms, stable: initial, candidate: initial, since_ms: 0 }
+    }
+
+    /// Sample mentah; return state baru kalau berubah.
+    pub fn update(&mut self, raw: bool, now_ms: u64) -> Option<bool> {
+        if raw != self.candidate {
+            self.candidate = raw;
+            self.since_ms = now_ms;
+        }
+        if self.candidate != self.stable && now_ms.saturating_sub(self.since_ms) >= self.debounce_ms {
+            self.stable = self.candidate;
+            return Some(self.stable);
+        }
+        None
+    }
+
+    pub fn state(&self) -> bool {
+        self.stable
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Discrepancy {
+    /// Output diperintah ON, feedback tidak datang.
+    NoFeedback,
+    /// Output OFF tapi feedback tetap aktif (mis. kontak relay lengket).
+    Uncommanded,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeedbackEvent {
+    Raised(Discrepancy),
+    Cleared,
+}
+
+/// Pemantau perintah ↔ feedback satu link.
+#[derive(Debug, Clone, Copy)]
+pub struct FeedbackMonitor {
+    timeout_ms:     u64,
+    mismatch_since: Option<u64>,
+    alarm:          Option<Discrepancy>,
+}
+
+impl FeedbackMonitor {
+    pub const fn new(timeout_ms: u64) -> Self {
+        Self { timeout_ms, mismatch_since: None, alarm: None }
+    }
+
+    pub fn update(&mut self, commanded: bool, feedback: bool, now_ms: u64) -> Option<FeedbackEvent> {
+        if commanded == feedback {
+            self.mismatch_since = None;
+            return self.alarm.take().map(|_| FeedbackEvent::Cleared);
+        }
+        // Perintah berubah arah saat alarm aktif: jenis diskrepansi dihitung ulang dari awal
+        let kind = if commanded { Discrepancy::NoFeedback } else { Discrepancy::Uncommanded };
+        if self.alarm.is_some_and(|a| a != kind) {
+            self.alarm = None;
+            self.mismatch_since = None;
+        }
+        let since = *self.mismatch_since.get_or_insert(now_ms);
+        if self.alarm.is_none() && now_ms.saturating_sub(since) >= self.timeout_ms {
+            self.alarm = Some(kind);
+            return Some(FeedbackEvent::Raised(kind));
+        }
+        None
+    }
+
+    pub fn alarm(&self) -> Option<Discrepancy> {
+        self.alarm
+    }
+}
+
+/// Readback satu kanal input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiState {
+    /// State logis setelah debounce.
+    pub on:    bool,
+    pub alarm: Option<Discrepancy>,
+}
+
+impl DiState {
+    pub const OFF: Self = Self { on: false, alarm: None };
+}
+
+pub struct DiChannel<P> {
+    cfg:     DiConfig,
+    pin:     P,
+    deb:     Debouncer,
+    monitor: Option<FeedbackMonitor>,
+}
+
+impl<P: RawInput> DiChannel<P> {
+    /// State awal = level pin saat ini (tanpa menunggu debounce).
+    pub fn new(cfg: DiConfig, pin: P) -> Self {
+        let on = pin.is_high() != cfg.active_low;
+        Self { cfg, pin, deb: Debouncer::new(cfg.debounce_ms, on), monitor: cfg.feedback.map(|f| FeedbackMonitor::new(f.timeout_ms)) }
+    }
+
+    /// Baca pin; return state baru kalau berubah setelah debounce.
+    pub fn poll(&mut self, now_ms: u64) -> Option<bool> {
+        let raw = self.pin.is_high() != self.cfg.active_low;
+        self.deb.update(raw, now_ms)
+    }
+
+    /// Bandingkan dengan perintah output yang di-link (abaikan kalau tidak di-link).
+    pub fn check(&mut self, commanded: bool, now_ms: u64) -> Option<FeedbackEvent> {
+        let on = self.deb.state();
+        self.monitor.as_mut()?.update(commanded, on, now_ms)
+    }
+
+    pub fn config(&self) -> &DiConfig {
+        &self.cfg
+    }
+
+    pub fn state(&self) -> DiState {
+        DiState { on: self.deb.state(), alarm: self.monitor.and_then(|m| m.alarm()) }
+    }
+}
+
+impl fmt::Display for Discrepancy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Discrepancy::NoFeedback  => "diperintah ON, feedback tidak ada",
+            Discrepancy::Uncommanded => "diperintah OFF, feedback tetap aktif",
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::cell::Cell;
+
+    struct Pin<'a>(&'a Cell<bool>);
+
+    impl RawInput for Pin<'_> {
+        fn is_high(&self) -> bool { self.0.get() }
+    }
+
+    #[test]
+    fn debounce_ignores_short_glitches() {
+        let mut d = Debouncer::new(50, false);
+        assert_eq!(d.update(true, 0), None);
+        assert_eq!(d.update(false, 20), None); // glitch 20 ms
+        assert_eq!(d.update(true, 30), None);
+        assert_eq!(d.update(true, 79), None);
+        assert_eq!(d.update(true, 80), Some(true));
+        assert_eq!(d.update(true, 200), None);
+        assert!(d.state());
+    }
+
+    #[test]
+    fn no_feedback_after_timeout_raises_and_clears() {
+        let mut m = FeedbackMonitor::new(5_000);
+        assert_eq!(m.update(true, false, 0), None);
+        assert_eq!(m.update(true, false, 4_999), None);
+        assert_eq!(m.update(true, false, 5_000), Some(FeedbackEvent::Raised(Discrepancy::NoFeedback)));
+        assert_eq!(m.update(true, false, 9_000), None);
+        assert_eq!(m.alarm(), Some(Discrepancy::NoFeedback));
+        assert_eq!(m.update(true, true, 10_000), Some(FeedbackEvent::Cleared));
+        assert_eq!(m.alarm(), None);
+    }
+
+    #[test]
+    fn feedback_within_timeout_is_fine_and_stuck_contact_is_reported() {
+        let mut m = FeedbackMonitor::new(5_000);
+        m.update(true, false, 0);
+        assert_eq!(m.update(true, true, 3_000), None);
+
+        // Perintah OFF tapi feedback tetap ada
+        m.update(false, true, 10_000);
+        assert_eq!(m.update(false, true, 15_000), Some(FeedbackEvent::Raised(Discrepancy::Uncommanded)));
+    }
+
+    #[test]
+    fn channel_polarity_debounce_and_link() {
+        let level = Cell::new(true); // pull-up, kontak terbuka
+        let cfg = DiConfig {
+            name: "fan_run", active_low: true, debounce_ms: 30,
+            feedback: Some(Feedback { output: "fan", timeout_ms: 1_000 }), point: IoPoint::Gpio,
+        };
+        let mut ch = DiChannel::new(cfg, Pin(&level));
+        assert_eq!(ch.state(), DiState::OFF);
+
+        level.set(false);
+        assert_eq!(ch.poll(0), None);
+        assert_eq!(ch.poll(30), Some(true));
+        assert_eq!(ch.check(true, 30), None);
+
+        level.set(true);
+        ch.poll(100);
+        ch.poll(130);
+        assert_eq!(ch.check(true, 130), None);
+        assert_eq!(ch.check(true, 1_130), Some(FeedbackEvent::Raised(Discrepancy::NoFeedback)));
+        assert_eq!(ch.state().alarm, Some(Discrepancy::NoFeedback));
+    }
+}
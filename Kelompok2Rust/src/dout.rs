@@ -0,0 +1,241 @@
+//! Output digital generik: kanal bernama dengan polaritas sendiri, state
+//! power-on yang jelas, mode pulsa/momentary, dan readback state yang diperintah.
+//!
+//! Polaritas hanya diurus di sini; di luar modul ini semua kanal bicara
+//! "ON/OFF" logis (lewat `DoChannel::set` atau trait `hal::DigitalOutput`).
+
+use core::fmt;
+
+use crate::hal::{DigitalOutput, RawOutput};
+use crate::remote::IoPoint;
+
+/// Mode momentary: ON hanya selama diperbarui tiap `MOMENTARY_MS` (dead-man).
+pub const MOMENTARY_MS: u64 = 500;
+
+/// Konfigurasi satu kanal (tabel di firmware).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DoConfig {
+    pub name:       &'static str,
+    /// true = modul relay aktif-LOW.
+    pub active_low: bool,
+    /// State logis saat boot (sebelum task mana pun memberi perintah).
+    pub power_on:   bool,
+    /// GPIO di board atau coil modul relay Modbus.
+    pub point:      IoPoint,
+}
+
+impl DoConfig {
+    /// Level pin (true = HIGH) untuk state logis `on`.
+    pub const fn level(&self, on: bool) -> bool {
+        on != self.active_low
+    }
+
+    /// Level awal pin, dipakai saat membuat GPIO supaya tidak ada glitch saat boot.
+    pub const fn power_on_level(&self) -> bool {
+        self.level(self.power_on)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DoMode {
+    /// Tetap di state terakhir.
+    Latched,
+    /// ON sampai `until_ms`, lalu OFF.
+    Pulse { until_ms: u64 },
+    /// ON selama diperbarui; OFF otomatis setelah `until_ms`.
+    Momentary { until_ms: u64 },
+}
+
+/// Readback satu kanal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DoState {
+    /// State logis yang diperintah.
+    pub on:         bool,
+    /// Level yang benar-benar di-drive di pin (register output).
+    pub level_high: bool,
+    pub mode:       DoMode,
+}
+
+impl DoState {
+    pub const OFF: Self = Self { on: false, level_high: false, mode: DoMode::Latched };
+
+    /// Level pin cocok dengan perintah + polaritas.
+    pub fn consistent(&self, cfg: &DoConfig) -> bool {
+        self.level_high == cfg.level(self.on)
+    }
+}
+
+pub struct DoChannel<P> {
+    cfg:  DoConfig,
+    pin:  P,
+    on:   bool,
+    mode: DoMode,
+}
+
+impl<P: RawOutput> DoChannel<P> {
+    /// Pin langsung di-drive ke state power-on.
+    pub fn new(cfg: DoConfig, mut pin: P) -> Self {
+        pin.set_high(cfg.power_on_level());
+        Self { cfg, pin, on: cfg.power_on, mode: DoMode::Latched }
+    }
+
+    fn drive(&mut self, on: bool, mode: DoMode) {
+        self.on = on;
+        self.mode = mode;
+        self.pin.set_high(self.cfg.level(on));
+    }
+
+    /// State tetap (membatalkan pulsa/momentary yang berjalan).
+    pub fn set(&mut self, on: bool) {
+        self.drive(on, DoMode::Latched);
+    }
+
+    /// ON selama `ms`, lalu OFF lewat `tick`.
+    pub fn pulse(&mut self, ms: u64, now_ms: u64) {
+        self.drive(true, DoMode::Pulse { until_ms: now_ms.saturating_add(ms) });
+    }
+
+    /// ON (atau perpanjang) selama `MOMENTARY_MS` dari sekarang.
+    pub fn momentary(&mut self, now_ms: u64) {
+        self.drive(true, DoMode::Momentary { until_ms: now_ms.saturating_add(MOMENTARY_MS) });
+    }
+
+    /// Akhiri pulsa/momentary yang sudah lewat waktunya. true kalau output berubah.
+    pub fn tick(&mut self, now_ms: u64) -> bool {
+        match self.mode {
+            DoMode::Pulse { until_ms } | DoMode::Momentary { until_ms } if now_ms >= until_ms => {
+                self.set(false);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn config(&self) -> &DoConfig {
+        &self.cfg
+    }
+
+    pub fn is_on(&self) -> bool {
+        self.on
+    }
+
+    pub fn state(&self) -> DoState {
+        DoState { on: self.on, level_high: self.pin.is_set_high(), mode: self.mode }
+    }
+}
+
+impl<P: RawOutput> DigitalOutput for DoChannel<P> {
+    fn set(&mut self, on: bool) {
+        DoChannel::set(self, on);
+    }
+}
+
+/// Sekumpulan kanal dengan lookup nama.
+pub struct DoBank<P, const N: usize> {
+    channels: [DoChannel<P>; N],
+}
+
+impl<P: RawOutput, const N: usize> DoBank<P, N> {
+    pub fn new(channels: [DoChannel<P>; N]) -> Self {
+        Self { channels }
+    }
+
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.channels.iter().position(|c| c.cfg.name == name)
+    }
+
+    pub fn get(&self, idx: usize) -> Option<&DoChannel<P>> {
+        self.channels.get(idx)
+    }
+
+    pub fn get_mut(&mut self, idx: usize) -> Option<&mut DoChannel<P>> {
+        self.channels.get_mut(idx)
+    }
+
+    /// `tick` semua kanal; true kalau ada yang berubah.
+    pub fn tick(&mut self, now_ms: u64) -> bool {
+        self.channels.iter_mut().fold(false, |changed, c| c.tick(now_ms) | changed)
+    }
+
+    /// Readback semua kanal, urut sesuai tabel konfigurasi.
+    pub fn states(&self) -> [DoState; N] {
+        core::array::from_fn(|i| self.channels[i].state())
+    }
+}
+
+impl fmt::Display for DoMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DoMode::Latched => f.write_str("tetap"),
+            DoMode::Pulse { until_ms } => write!(f, "pulsa s/d {} ms", until_ms),
+            DoMode::Momentary { until_ms } => write!(f, "momentary s/d {} ms", until_ms),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct Pin(bool);
+
+    impl RawOutput for Pin {
+        fn set_high(&mut self, high: bool) { self.0 = high; }
+        fn is_set_high(&self) -> bool { self.0 }
+    }
+
+    const FAN: DoConfig = DoConfig { name: "fan", active_low: true, power_on: false, point: IoPoint::Gpio };
+    const HUM: DoConfig = DoConfig { name: "hum", active_low: false, power_on: true, point: IoPoint::Gpio };
+
+    #[test]
+    fn polarity_and_power_on_state() {
+        let fan = DoChannel::new(FAN, Pin(false));
+        assert_eq!(fan.state(), DoState { on: false, level_high: true, mode: DoMode::Latched });
+        let hum = DoChannel::new(HUM, Pin(false));
+        assert_eq!(hum.state(), DoState { on: true, level_high: true, mode: DoMode::Latched });
+
+        let mut fan = fan;
+        fan.set(true);
+        assert!(!fan.state().level_high);
+        assert!(fan.state().consistent(&FAN));
+    }
+
+    #[test]
+    fn pulse_turns_off_after_duration() {
+        let mut bank = DoBank::new([DoChannel::new(FAN, Pin::default()), DoChannel::new(HUM, Pin::default())]);
+        let hum = bank.find("hum").unwrap();
+        bank.get_mut(hum).unwrap().set(false);
+        bank.get_mut(hum).unwrap().pulse(2_000, 1_000);
+        assert!(!bank.tick(2_999));
+        assert!(bank.get(hum).unwrap().is_on());
+        assert!(bank.tick(3_000));
+        assert_eq!(bank.states()[hum], DoState { on: false, level_high: false, mode: DoMode::Latched });
+        assert_eq!(bank.find("pompa"), None);
+    }
+
+    #[test]
+    fn huge_pulse_saturates_instead_of_overflowing() {
+        let mut ch = DoChannel::new(HUM, Pin::default());
+        ch.pulse(u64::MAX, 1_000);
+        assert_eq!(ch.state().mode, DoMode::Pulse { until_ms: u64::MAX });
+        assert!(!ch.tick(u64::MAX - 1));
+        ch.momentary(u64::MAX - 10);
+        assert_eq!(ch.state().mode, DoMode::Momentary { until_ms: u64::MAX });
+    }
+
+    #[test]
+    fn momentary_needs_refresh_and_set_cancels_timers() {
+        let mut ch = DoChannel::new(FAN, Pin::default());
+        ch.momentary(0);
+        ch.momentary(400);
+        assert!(!ch.tick(800));
+        assert!(ch.tick(900));
+        assert!(!ch.is_on());
+
+        ch.pulse(100, 1_000);
+        ch.set(true);
+        assert!(!ch.tick(5_000));
+        assert!(ch.is_on());
+    }
+}
@@ -0,0 +1,54 @@
+//! Abstraksi hardware. Aplikasi kontrol hanya bicara lewat trait ini, jadi
+//! logika yang sama bisa jalan di ESP32-S3 maupun di host (lihat `sim`).
+
+/// Error di level port (UART/RS485), sebelum frame Modbus divalidasi.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PortError {
+    /// Tidak ada byte reply sama sekali dalam batas waktu.
+    Timeout,
+    /// Error UART (overrun, framing, dsb).
+    Io,
+}
+
+/// Port Modbus RTU: kirim satu request lalu kumpulkan satu frame reply.
+#[allow(async_fn_in_trait)]
+pub trait ModbusPort {
+    /// Return jumlah byte reply yang ditulis ke `response`.
+    async fn transact(&mut self, request: &[u8], response: &mut [u8]) -> Result<usize, PortError>;
+}
+
+/// Port yang baudrate-nya bisa diganti saat jalan (scan discovery).
+pub trait SetBaud {
+    fn set_baud(&mut self, baud: u32) -> Result<(), PortError>;
+}
+
+/// Output digital logis (true = ON); polaritas pin urusan implementasi.
+pub trait DigitalOutput {
+    fn set(&mut self, on: bool);
+}
+
+/// Pin output mentah (level listrik, true = HIGH); polaritas diurus `dout`.
+pub trait RawOutput {
+    fn set_high(&mut self, high: bool);
+    /// Level yang sedang di-drive (register output), untuk readback.
+    fn is_set_high(&self) -> bool;
+}
+
+/// Pin input mentah (level listrik); polaritas & debounce diurus `din`.
+pub trait RawInput {
+    fn is_high(&self) -> bool;
+}
+
+/// Servo hobby 50 Hz; cukup terima lebar pulsa.
+pub trait Servo {
+    fn set_pulse_us(&mut self, us: u32);
+}
+
+/// Jam monoton (sejak boot / awal simulasi).
+pub trait Clock {
+    fn now_us(&self) -> u64;
+
+    fn now_ms(&self) -> u64 {
+        self.now_us() / 1000
+    }
+}
@@ -0,0 +1,414 @@
+//! Interlock & permissive: aturan boolean atas nilai proses, state aktuator,
+//! dan fault yang memaksa (force) output kipas/servo setelah controller.
+//!
+//! Satu aturan ditulis seperti di konsol, mis.
+//!   `fan off if t > 45`          → paksa kipas OFF di atas 45 °C
+//!   `servo 0 if fan off`         → servo tertutup kecuali kipas jalan (permissive)
+//!   `fan on if rh > 90 & t < 40`
+//!
+//! Aturan dievaluasi berurutan (nomor kecil = prioritas lebih tinggi). Kondisi
+//! aturan berikutnya melihat output yang sudah dipaksa aturan sebelumnya, dan
+//! output yang sudah dipaksa tidak bisa diubah lagi oleh aturan di bawahnya.
+
+use core::fmt;
+
+use crate::control::ActuatorCmd;
+use crate::sht20::Measurement;
+
+pub const MAX_INTERLOCKS: usize = 8;
+pub const MAX_TERMS:      usize = 3;
+
+/// Variabel proses yang bisa dibandingkan.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Var {
+    Rh,
+    Temp,
+    /// Sudut servo setelah controller (dan aturan sebelumnya).
+    Servo,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cmp {
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Term {
+    /// Nilai yang tidak terbaca (sensor error) selalu false; pakai
+    /// `SensorFault` kalau butuh aksi fail-safe.
+    Compare(Var, Cmp, f32),
+    /// State kipas (true = ON).
+    Fan(bool),
+    /// Pembacaan RH atau T terakhir gagal.
+    SensorFault,
+}
+
+/// Gabungan maksimal `MAX_TERMS` term, semuanya AND atau semuanya OR.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Condition {
+    terms: [Term; MAX_TERMS],
+    len:   usize,
+    any:   bool,
+}
+
+/// Output yang dipaksa saat kondisi terpenuhi.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Force {
+    Fan(bool),
+    Servo(i32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Interlock {
+    pub force:    Force,
+    pub when:     Condition,
+    pub enabled:  bool,
+    /// Di-bypass manual dari konsol: tetap dievaluasi & dilaporkan, tapi tidak memaksa output.
+    pub bypassed: bool,
+}
+
+/// Input evaluasi selain output controller.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ProcessImage {
+    pub rh:           Option<f32>,
+    pub temp:         Option<f32>,
+    pub sensor_fault: bool,
+}
+
+impl From<&Measurement> for ProcessImage {
+    fn from(m: &Measurement) -> Self {
+        Self { rh: m.rh, temp: m.temp, sensor_fault: m.rh.is_none() || m.temp.is_none() }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct InterlockState {
+    /// Kondisi terpenuhi.
+    pub tripped: bool,
+    /// Tripped, enabled, tidak di-bypass, dan output benar-benar dipaksa
+    /// (bukan sudah dipegang aturan dengan prioritas lebih tinggi).
+    pub active:  bool,
+}
+
+impl Term {
+    fn eval(&self, image: &ProcessImage, out: &ActuatorCmd) -> bool {
+        match *self {
+            Term::Compare(var, cmp, limit) => {
+                let value = match var {
+                    Var::Rh    => image.rh,
+                    Var::Temp  => image.temp,
+                    Var::Servo => Some(out.servo_deg as f32),
+                };
+                value.is_some_and(|v| match cmp {
+                    Cmp::Gt => v > limit,
+                    Cmp::Ge => v >= limit,
+                    Cmp::Lt => v < limit,
+                    Cmp::Le => v <= limit,
+                })
+            }
+            Term::Fan(on) => out.fan_on == on,
+            Term::SensorFault => image.sensor_fault,
+        }
+    }
+}
+
+impl Condition {
+    /// `terms` tidak boleh kosong dan maksimal `MAX_TERMS`.
+    pub fn new(terms: &[Term], any: bool) -> Option<Self> {
+        if terms.is_empty() || terms.len() > MAX_TERMS { return None; }
+        let mut buf = [Term::SensorFault; MAX_TERMS];
+        buf[..terms.len()].copy_from_slice(terms);
+        Some(Self { terms: buf, len: terms.len(), any })
+    }
+
+    pub fn terms(&self) -> &[Term] {
+        &self.terms[..self.len]
+    }
+
+    pub fn eval(&self, image: &ProcessImage, out: &ActuatorCmd) -> bool {
+        let mut terms = self.terms().iter();
+        if self.any { terms.any(|t| t.eval(image, out)) } else { terms.all(|t| t.eval(image, out)) }
+    }
+}
+
+impl Interlock {
+    pub const fn new(force: Force, when: Condition) -> Self {
+        Self { force, when, enabled: true, bypassed: false }
+    }
+
+    /// Parse aturan `<force> if <kondisi>`; None kalau format salah.
+    ///
+    /// force   : `fan on|off` | `servo <deg>`
+    /// kondisi : term (`&`|`and` term)* atau term (`|`|`or` term)*
+    /// term    : `rh|t|servo <op> <angka>` | `fan on|off` | `sensor fault`
+    /// op      : `>` `>=` `<` `<=`
+    pub fn parse(rule: &str) -> Option<Self> {
+        let mut tok = rule.split_whitespace();
+
+        let force = match (tok.next()?, tok.next()?) {
+            ("fan", s) => Force::Fan(parse_on_off(s)?),
+            ("servo", deg) => Force::Servo(deg.parse().ok()?),
+            _ => return None,
+        };
+        if tok.next()? != "if" { return None; }
+
+        let mut terms = [Term::SensorFault; MAX_TERMS];
+        let mut len = 0usize;
+        let mut any: Option<bool> = None;
+        loop {
+            if len == MAX_TERMS { return None; }
+            terms[len] = match tok.next()? {
+                "fan" => Term::Fan(parse_on_off(tok.next()?)?),
+                "sensor" => { if tok.next()? != "fault" { return None; } Term::SensorFault }
+                name => {
+                    let var = match name { "rh" => Var::Rh, "t" => Var::Temp, "servo" => Var::Servo, _ => return None };
+                    let cmp = match tok.next()? { ">" => Cmp::Gt, ">=" => Cmp::Ge, "<" => Cmp::Lt, "<=" => Cmp::Le, _ => return None };
+                    Term::Compare(var, cmp, tok.next()?.parse().ok()?)
+                }
+            };
+            len += 1;
+
+            let is_any = match tok.next() {
+                None => break,
+                Some("&" | "and") => false,
+                Some("|" | "or") => true,
+                Some(_) => return None,
+            };
+            // AND dan OR tidak boleh dicampur
+            if any.is_some_and(|a| a != is_any) { return None; }
+            any = Some(is_any);
+        }
+
+        Some(Self::new(force, Condition::new(&terms[..len], any.unwrap_or(false))?))
+    }
+}
+
+fn parse_on_off(s: &str) -> Option<bool> {
+    match s { "on" => Some(true), "off" => Some(false), _ => None }
+}
+
+fn on_off(on: bool) -> &'static str {
+    if on { "on" } else { "off" }
+}
+
+impl fmt::Display for Term {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Term::Compare(var, cmp, v) => {
+                let var = match var { Var::Rh => "rh", Var::Temp => "t", Var::Servo => "servo" };
+                let cmp = match cmp { Cmp::Gt => ">", Cmp::Ge => ">=", Cmp::Lt => "<", Cmp::Le => "<=" };
+                write!(f, "{} {} {}", var, cmp, v)
+            }
+            Term::Fan(on) => write!(f, "fan {}", on_off(on)),
+            Term::SensorFault => f.write_str("sensor fault"),
+        }
+    }
+}
+
+/// Format sama dengan input `Interlock::parse`.
+impl fmt::Display for Interlock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.force {
+            Force::Fan(on) => write!(f, "fan {} if ", on_off(on))?,
+            Force::Servo(deg) => write!(f, "servo {} if ", deg)?,
+        }
+        for (i, t) in self.when.terms().iter().enumerate() {
+            if i > 0 { f.write_str(if self.when.any { " | " } else { " & " })?; }
+            write!(f, "{}", t)?;
+        }
+        Ok(())
+    }
+}
+
+/// Tabel interlock + state hasil evaluasi terakhir.
+#[derive(Debug, Clone, Copy)]
+pub struct InterlockTable {
+    slots: [Option<Interlock>; MAX_INTERLOCKS],
+    state: [InterlockState; MAX_INTERLOCKS],
+}
+
+impl Default for InterlockTable {
+    fn default() -> Self {
+        Self::defaults()
+    }
+}
+
+impl InterlockTable {
+    pub const fn empty() -> Self {
+        Self {
+            slots: [None; MAX_INTERLOCKS],
+            state: [InterlockState { tripped: false, active: false }; MAX_INTERLOCKS],
+        }
+    }
+
+    /// Tabel bawaan: proteksi suhu aktif, permissive servo↔kipas tersedia tapi nonaktif.
+    pub fn defaults() -> Self {
+        let mut table = Self::empty();
+        table.slots[0] = Interlock::parse("fan off if t > 45");
+        table.slots[1] = Interlock::parse("servo 0 if fan off").map(|mut il| { il.enabled = false; il });
+        table
+    }
+
+    pub fn get(&self, idx: usize) -> Option<&Interlock> {
+        self.slots.get(idx)?.as_ref()
+    }
+
+    pub fn get_mut(&mut self, idx: usize) -> Option<&mut Interlock> {
+        self.slots.get_mut(idx)?.as_mut()
+    }
+
+    /// Isi/hapus slot `idx` (0-based). false kalau idx di luar tabel.
+    pub fn set(&mut self, idx: usize, interlock: Option<Interlock>) -> bool {
+        let Some(slot) = self.slots.get_mut(idx) else { return false };
+        *slot = interlock;
+        self.state[idx] = InterlockState::default();
+        true
+    }
+
+    pub fn state(&self, idx: usize) -> InterlockState {
+        self.state.get(idx).copied().unwrap_or_default()
+    }
+
+    /// Slot terisi: (idx, aturan, state).
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &Interlock, InterlockState)> {
+        self.slots.iter().enumerate()
+            .filter_map(|(i, s)| s.as_ref().map(|il| (i, il, self.state[i])))
+    }
+
+    /// Terapkan tabel ke output controller; state tiap aturan ikut diperbarui.
+    pub fn evaluate(&mut self, image: &ProcessImage, cmd: ActuatorCmd) -> ActuatorCmd {
+        let mut out = cmd;
+        let (mut fan_forced, mut servo_forced) = (false, false);
+
+        for (slot, state) in self.slots.iter().zip(self.state.iter_mut()) {
+            let Some(il) = slot else { *state = InterlockState::default(); continue };
+
+            let tripped = il.when.eval(image, &out);
+            let mut active = false;
+            if tripped && il.enabled && !il.bypassed {
+                match il.force {
+                    Force::Fan(on) if !fan_forced => { out.fan_on = on; fan_forced = true; active = true; }
+                    Force::Servo(deg) if !servo_forced => { out.servo_deg = deg; servo_forced = true; active = true; }
+                    _ => {} // output sudah dipegang aturan di atasnya
+                }
+            }
+            *state = InterlockState { tripped, active };
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cmd(servo_deg: i32, fan_on: bool) -> ActuatorCmd {
+        ActuatorCmd { servo_deg, fan_on }
+    }
+
+    fn image(rh: f32, temp: f32) -> ProcessImage {
+        ProcessImage { rh: Some(rh), temp: Some(temp), sensor_fault: false }
+    }
+
+    fn table(rules: &[&str]) -> InterlockTable {
+        let mut t = InterlockTable::empty();
+        for (i, r) in rules.iter().enumerate() {
+            assert!(t.set(i, Some(Interlock::parse(r).unwrap())), "{}", r);
+        }
+        t
+    }
+
+    #[test]
+    fn parse_display_roundtrip() {
+        for rule in ["fan off if t > 45", "servo 0 if fan off", "fan on if rh >= 90 & t < 40",
+                     "servo 120 if sensor fault | servo <= 10", "fan off if rh > 85.5"] {
+            assert_eq!(Interlock::parse(rule).unwrap().to_string(), rule);
+        }
+        assert_eq!(Interlock::parse("fan on if rh > 90 and t < 40").unwrap().to_string(),
+                   "fan on if rh > 90 & t < 40");
+    }
+
+    #[test]
+    fn parse_rejects_bad_rules() {
+        for rule in ["", "fan", "fan maybe if t > 1", "fan on t > 1", "fan on if", "fan on if x > 1",
+                     "fan on if t = 1", "fan on if t > abc", "fan on if t > 1 & rh > 1 | fan on",
+                     "fan on if t > 1 & t > 2 & t > 3 & t > 4", "servo x if fan on", "fan on if sensor ok"] {
+            assert!(Interlock::parse(rule).is_none(), "{}", rule);
+        }
+    }
+
+    #[test]
+    fn force_fan_off_above_temperature_limit() {
+        let mut t = table(&["fan off if t > 45"]);
+        assert_eq!(t.evaluate(&image(85.0, 40.0), cmd(120, true)), cmd(120, true));
+        assert_eq!(t.state(0), InterlockState { tripped: false, active: false });
+
+        assert_eq!(t.evaluate(&image(85.0, 46.0), cmd(120, true)), cmd(120, false));
+        assert_eq!(t.state(0), InterlockState { tripped: true, active: true });
+    }
+
+    #[test]
+    fn permissive_sees_outputs_forced_by_earlier_rules() {
+        // Kipas dipaksa OFF oleh aturan 1 → permissive aturan 2 menutup servo
+        let mut t = table(&["fan off if t > 45", "servo 0 if fan off"]);
+        assert_eq!(t.evaluate(&image(85.0, 46.0), cmd(120, true)), cmd(0, false));
+        assert_eq!(t.evaluate(&image(85.0, 30.0), cmd(120, true)), cmd(120, true));
+        assert_eq!(t.evaluate(&image(65.0, 30.0), cmd(60, false)), cmd(0, false));
+    }
+
+    #[test]
+    fn earlier_rule_wins_on_conflict() {
+        let mut t = table(&["fan off if t > 45", "fan on if rh > 90"]);
+        assert_eq!(t.evaluate(&image(95.0, 50.0), cmd(0, false)), cmd(0, false));
+        assert_eq!(t.state(1), InterlockState { tripped: true, active: false });
+    }
+
+    #[test]
+    fn bypassed_and_disabled_rules_report_but_do_not_force() {
+        let mut t = table(&["fan off if t > 45", "servo 0 if fan on"]);
+        t.get_mut(0).unwrap().bypassed = true;
+        t.get_mut(1).unwrap().enabled = false;
+
+        assert_eq!(t.evaluate(&image(85.0, 50.0), cmd(120, true)), cmd(120, true));
+        assert_eq!(t.state(0), InterlockState { tripped: true, active: false });
+        assert_eq!(t.state(1), InterlockState { tripped: true, active: false });
+    }
+
+    #[test]
+    fn missing_values_do_not_trip_compare_but_trip_sensor_fault() {
+        let mut t = table(&["fan off if t > 45", "servo 120 if sensor fault"]);
+        let img = ProcessImage { rh: None, temp: None, sensor_fault: true };
+        assert_eq!(t.evaluate(&img, cmd(0, true)), cmd(120, true));
+        assert!(!t.state(0).tripped);
+        assert!(t.state(1).active);
+    }
+
+    #[test]
+    fn or_condition() {
+        let mut t = table(&["fan on if rh > 90 | t > 40"]);
+        assert!(t.evaluate(&image(50.0, 41.0), cmd(0, false)).fan_on);
+        assert!(t.evaluate(&image(91.0, 20.0), cmd(0, false)).fan_on);
+        assert!(!t.evaluate(&image(50.0, 20.0), cmd(0, false)).fan_on);
+    }
+
+    #[test]
+    fn cleared_slot_resets_state() {
+        let mut t = table(&["fan off if t > 45"]);
+        t.evaluate(&image(50.0, 50.0), cmd(0, true));
+        assert!(t.set(0, None));
+        assert_eq!(t.state(0), InterlockState::default());
+        assert_eq!(t.iter().count(), 0);
+        assert!(!t.set(MAX_INTERLOCKS, None));
+    }
+
+    #[test]
+    fn default_table() {
+        let t = InterlockTable::defaults();
+        let rules: Vec<_> = t.iter().map(|(i, il, _)| (i, il.to_string(), il.enabled)).collect();
+        assert_eq!(rules, [(0, "fan off if t > 45".to_string(), true),
+                           (1, "servo 0 if fan off".to_string(), false)]);
+    }
+}
@@ -1 +1,32 @@
-#![no_std]
\ No newline at end of file
+#![cfg_attr(not(test), no_std)]
+
+pub mod app;
+pub mod calib;
+mod codec;
+pub mod config;
+pub mod control;
+pub mod din;
+pub mod dout;
+pub mod hal;
+pub mod interlock;
+pub mod lifecycle;
+pub mod log;
+pub mod modbus;
+pub mod post;
+pub mod remote;
+pub mod retain;
+pub mod rs485;
+pub mod sched;
+pub mod servo;
+pub mod servo_fb;
+pub mod setpoint;
+pub mod sht20;
+pub mod sht2x;
+#[cfg(feature = "sim")]
+pub mod sim;
+pub mod store;
+pub mod timesync;
+pub mod timing;
+
+#[cfg(test)]
+mod testutil;
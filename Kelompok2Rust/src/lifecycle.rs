@@ -0,0 +1,403 @@
+//! State machine unit: INIT → SELF-TEST → STANDBY → RUN → SHUTDOWN, plus FAULT.
+//!
+//!   INIT      → SELF-TEST  inisialisasi hardware selesai
+//!   INIT      → STANDBY/RUN warm restart (fase sebelum reset, lihat `retain`)
+//!   SELF-TEST → STANDBY    sensor terbaca valid (dan POST selesai, lihat `post`)
+//!   SELF-TEST → FAULT      timeout `SELF_TEST_TIMEOUT_MS`
+//!   STANDBY   → RUN        perintah `start` (atau otomatis kecuali setelah `stop`), sensor harus valid,
+//!                          tidak selama `hold` (discovery RS485)
+//!   RUN       → SHUTDOWN   perintah `stop`
+//!   RUN       → FAULT      sensor hilang ≥ `SENSOR_LOSS_MS`
+//!   (fase aktif) → FAULT   fault eksternal, mis. feedback DI tidak sesuai atau check POST kritis gagal (`raise`)
+//!   SHUTDOWN  → STANDBY    purge selesai
+//!   FAULT     → SELF-TEST  perintah `reset`
+//!
+//! Controller hanya menentukan output di RUN; fase lain punya output tetap
+//! (lihat `Phase::outputs`). Tabel interlock dievaluasi terakhir, di semua fase.
+
+use core::fmt;
+
+use crate::control::ActuatorCmd;
+use crate::post::Check;
+use crate::sht20::Measurement;
+
+/// Batas waktu self-test menunggu pembacaan sensor valid pertama.
+pub const SELF_TEST_TIMEOUT_MS: u64 = 10_000;
+/// Lama purge (damper buka + kipas ON) saat shutdown sebelum kembali ke STANDBY.
+pub const SHUTDOWN_PURGE_MS:    u64 = 10_000;
+/// Sensor tidak terbaca selama ini di RUN → FAULT. Gangguan lebih singkat: output ditahan.
+pub const SENSOR_LOSS_MS:       u64 = 30_000;
+
+/// Output aman: damper tertutup, kipas OFF (servo ke posisi park, lihat `Lifecycle::set_park`).
+pub const SAFE_OUTPUTS:  ActuatorCmd = ActuatorCmd { servo_deg: 0, fan_on: false };
+/// Output purge saat shutdown: damper buka penuh + kipas ON.
+pub const PURGE_OUTPUTS: ActuatorCmd = ActuatorCmd { servo_deg: 120, fan_on: true };
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    Init,
+    SelfTest,
+    Standby,
+    Run,
+    Shutdown,
+    Fault,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FaultCause {
+    /// Self-test tidak mendapat pembacaan sensor valid.
+    SelfTestTimeout,
+    /// Sensor tidak terbaca lebih dari `SENSOR_LOSS_MS` saat RUN.
+    SensorLost,
+    /// Input feedback (nama kanal DI) tidak sesuai output yang diperintah.
+    Feedback(&'static str),
+    /// Check POST kritis gagal.
+    SelfTest(Check),
+    /// Posisi servo (feedback potensiometer) tidak mengikuti perintah.
+    ServoStall,
+}
+
+/// Perintah operator (konsol).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    /// STANDBY → RUN
+    Start,
+    /// RUN → SHUTDOWN
+    Stop,
+    /// FAULT → SELF-TEST
+    Reset,
+}
+
+/// Alasan perintah ditolak.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rejected {
+    /// Perintah tidak berlaku di fase ini.
+    WrongPhase(Phase),
+    /// Start butuh pembacaan sensor terakhir valid.
+    SensorNotReady,
+    /// Fase ditahan di luar RUN (discovery RS485 berjalan).
+    Held,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Transition {
+    pub from: Phase,
+    pub to:   Phase,
+}
+
+impl Phase {
+    /// Output tetap fase ini; None = RUN (output dari controller + interlock).
+    pub const fn outputs(self) -> Option<ActuatorCmd> {
+        match self {
+            Phase::Run      => None,
+            Phase::Shutdown => Some(PURGE_OUTPUTS),
+            _               => Some(SAFE_OUTPUTS),
+        }
+    }
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Phase::Init     => "INIT",
+            Phase::SelfTest => "SELF-TEST",
+            Phase::Standby  => "STANDBY",
+            Phase::Run      => "RUN",
+            Phase::Shutdown => "SHUTDOWN",
+            Phase::Fault    => "FAULT",
+        }
+    }
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl fmt::Display for FaultCause {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            FaultCause::SelfTestTimeout => "self-test timeout (sensor tidak terbaca)",
+            FaultCause::SensorLost      => "sensor hilang saat RUN",
+            FaultCause::Feedback(name)  => return write!(f, "feedback '{}' tidak sesuai perintah output", name),
+            FaultCause::SelfTest(check) => return write!(f, "self-test {} gagal", check.name()),
+            FaultCause::ServoStall      => "servo macet / damper terhalang",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Lifecycle {
+    phase:      Phase,
+    since_ms:   u64,
+    /// STANDBY → RUN otomatis tanpa perintah `start` (perilaku firmware lama).
+    auto_start: bool,
+    /// Operator `stop`: auto-start ditahan sampai `start` (atau `reset` dari FAULT).
+    stopped:    bool,
+    sensor_ok:  bool,
+    /// Waktu pembacaan sensor valid terakhir.
+    last_ok_ms: u64,
+    fault:      Option<FaultCause>,
+    /// Sudut servo di fase aman (kalibrasi servo `park_deg`).
+    park_deg:   i32,
+    /// POST sedang berjalan: SELF-TEST ditahan (output dari POST).
+    post:       bool,
+    /// Discovery RS485 berjalan (polling sensor berhenti): tidak boleh ke RUN.
+    held:       bool,
+}
+
+impl Lifecycle {
+    pub const fn new(auto_start: bool) -> Self {
+        Self { phase: Phase::Init, since_ms: 0, auto_start, stopped: false, sensor_ok: false, last_ok_ms: 0, fault: None, park_deg: 0, post: false, held: false }
+    }
+
+    /// Posisi park servo untuk fase dengan `SAFE_OUTPUTS`.
+    pub fn set_park(&mut self, deg: i32) {
+        self.park_deg = deg;
+    }
+
+    /// Tandai POST berjalan/selesai. Selama berjalan SELF-TEST tidak keluar
+    /// sendiri (termasuk timeout); hasilnya diputuskan pemanggil lewat `raise`.
+    pub fn set_post(&mut self, running: bool) {
+        self.post = running;
+    }
+
+    pub fn post_running(&self) -> bool {
+        self.post
+    }
+
+    /// Tahan fase di luar RUN selama polling sensor berhenti (discovery):
+    /// auto-start dan `start` ditolak. Ditolak kalau sudah RUN.
+    pub fn hold(&mut self) -> Result<(), Rejected> {
+        if self.phase == Phase::Run { return Err(Rejected::WrongPhase(Phase::Run)); }
+        self.held = true;
+        Ok(())
+    }
+
+    /// Lepas `hold`. Timeout SELF-TEST dihitung ulang dari sekarang karena
+    /// selama ditahan tidak ada pembacaan sensor.
+    pub fn release(&mut self, now_ms: u64) {
+        if core::mem::take(&mut self.held) && self.phase == Phase::SelfTest { self.since_ms = now_ms; }
+    }
+
+    pub fn held(&self) -> bool {
+        self.held
+    }
+
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    /// Penyebab FAULT terakhir (None di luar FAULT).
+    pub fn fault(&self) -> Option<FaultCause> {
+        self.fault
+    }
+
+    /// Lama berada di fase sekarang.
+    pub fn elapsed_ms(&self, now_ms: u64) -> u64 {
+        now_ms.saturating_sub(self.since_ms)
+    }
+
+    /// Inisialisasi hardware selesai: INIT → SELF-TEST.
+    pub fn boot(&mut self, now_ms: u64) -> Option<Transition> {
+        (self.phase == Phase::Init).then(|| self.go(Phase::SelfTest, now_ms))
+    }
+
+    /// Warm restart: langsung kembali ke fase sebelum reset (hanya STANDBY/RUN,
+    /// tanpa SELF-TEST). Fase lain → None, pakai `boot` biasa. STANDBY dilanjutkan
+    /// sebagai STANDBY (tanpa auto-start), karena bisa jadi hasil `stop`.
+    pub fn warm_start(&mut self, phase: Phase, now_ms: u64) -> Option<Transition> {
+        if self.phase != Phase::Init || !matches!(phase, Phase::Standby | Phase::Run) { return None; }
+        self.stopped = phase == Phase::Standby;
+        Some(self.go(phase, now_ms))
+    }
+
+    pub fn command(&mut self, cmd: Command, now_ms: u64) -> Result<Transition, Rejected> {
+        match (cmd, self.phase) {
+            (Command::Start, Phase::Standby) if self.held => Err(Rejected::Held),
+            (Command::Start, Phase::Standby) if !self.sensor_ok => Err(Rejected::SensorNotReady),
+            (Command::Start, Phase::Standby) => { self.stopped = false; Ok(self.go(Phase::Run, now_ms)) }
+            (Command::Stop, Phase::Run)      => { self.stopped = true; Ok(self.go(Phase::Shutdown, now_ms)) }
+            (Command::Reset, Phase::Fault)   => { self.stopped = false; Ok(self.go(Phase::SelfTest, now_ms)) }
+            (_, phase) => Err(Rejected::WrongPhase(phase)),
+        }
+    }
+
+    /// Transisi berdasarkan kondisi; panggil tiap hasil polling sensor.
+    /// Maksimal satu transisi per panggilan.
+    pub fn update(&mut self, m: &Measurement, now_ms: u64) -> Option<Transition> {
+        self.sensor_ok = m.rh.is_some() && m.temp.is_some();
+        if self.sensor_ok { self.last_ok_ms = now_ms; }
+
+        match self.phase {
+            Phase::SelfTest if self.post => None,
+            Phase::SelfTest if self.sensor_ok => Some(self.go(Phase::Standby, now_ms)),
+            Phase::SelfTest if self.elapsed_ms(now_ms) >= SELF_TEST_TIMEOUT_MS => {
+                Some(self.trip(FaultCause::SelfTestTimeout, now_ms))
+            }
+            Phase::Standby if self.auto_start && !self.stopped && !self.held && self.sensor_ok => Some(self.go(Phase::Run, now_ms)),
+            Phase::Run if now_ms.saturating_sub(self.last_ok_ms.max(self.since_ms)) >= SENSOR_LOSS_MS => {
+                Some(self.trip(FaultCause::SensorLost, now_ms))
+            }
+            Phase::Shutdown if self.elapsed_ms(now_ms) >= SHUTDOWN_PURGE_MS => Some(self.go(Phase::Standby, now_ms)),
+            _ => None,
+        }
+    }
+
+    /// Fault dari luar state machine (mis. diskrepansi feedback). Diabaikan di
+    /// INIT dan kalau sudah FAULT (penyebab pertama yang dipertahankan).
+    pub fn raise(&mut self, cause: FaultCause, now_ms: u64) -> Option<Transition> {
+        (!matches!(self.phase, Phase::Init | Phase::Fault)).then(|| self.trip(cause, now_ms))
+    }
+
+    /// Output fase sekarang (sebelum interlock); `run` = hasil controller.
+    pub fn outputs(&self, run: ActuatorCmd) -> ActuatorCmd {
+        match self.phase.outputs() {
+            Some(SAFE_OUTPUTS) => ActuatorCmd { servo_deg: self.park_deg, ..SAFE_OUTPUTS },
+            Some(out) => out,
+            None => run,
+        }
+    }
+
+    fn trip(&mut self, cause: FaultCause, now_ms: u64) -> Transition {
+        let t = self.go(Phase::Fault, now_ms);
+        self.fault = Some(cause);
+        t
+    }
+
+    fn go(&mut self, to: Phase, now_ms: u64) -> Transition {
+        let from = self.phase;
+        self.phase = to;
+        self.since_ms = now_ms;
+        self.fault = None;
+        if to != Phase::SelfTest { self.post = false; }
+        Transition { from, to }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OK:  Measurement = Measurement { rh: Some(60.0), temp: Some(27.0) };
+    const BAD: Measurement = Measurement { rh: None, temp: None };
+
+    fn running() -> Lifecycle {
+        let mut lc = Lifecycle::new(false);
+        lc.boot(0);
+        lc.update(&OK, 1000);
+        lc.command(Command::Start, 2000).unwrap();
+        lc
+    }
+
+    #[test]
+    fn startup_sequence_waits_for_start_command() {
+        let mut lc = Lifecycle::new(false);
+        assert_eq!(lc.phase(), Phase::Init);
+        assert_eq!(lc.boot(0), Some(Transition { from: Phase::Init, to: Phase::SelfTest }));
+        assert_eq!(lc.boot(0), None);
+
+        assert_eq!(lc.update(&OK, 1000).map(|t| t.to), Some(Phase::Standby));
+        assert_eq!(lc.update(&OK, 2000), None);
+        assert_eq!(lc.command(Command::Start, 3000).map(|t| t.to), Ok(Phase::Run));
+    }
+
+    #[test]
+    fn auto_start_enters_run_on_next_good_sample() {
+        let mut lc = Lifecycle::new(true);
+        lc.boot(0);
+        lc.update(&OK, 1000);
+        assert_eq!(lc.phase(), Phase::Standby);
+        assert_eq!(lc.update(&BAD, 2000), None);
+        assert_eq!(lc.update(&OK, 3000).map(|t| t.to), Some(Phase::Run));
+    }
+
+    #[test]
+    fn self_test_times_out_into_fault_and_reset_retries() {
+        let mut lc = Lifecycle::new(false);
+        lc.boot(0);
+        assert_eq!(lc.update(&BAD, SELF_TEST_TIMEOUT_MS - 1), None);
+        assert_eq!(lc.update(&BAD, SELF_TEST_TIMEOUT_MS).map(|t| t.to), Some(Phase::Fault));
+        assert_eq!(lc.fault(), Some(FaultCause::SelfTestTimeout));
+
+        assert_eq!(lc.command(Command::Start, 20_000), Err(Rejected::WrongPhase(Phase::Fault)));
+        assert_eq!(lc.command(Command::Reset, 20_000).map(|t| t.to), Ok(Phase::SelfTest));
+        assert_eq!(lc.fault(), None);
+        assert_eq!(lc.update(&OK, 21_000).map(|t| t.to), Some(Phase::Standby));
+    }
+
+    #[test]
+    fn start_is_guarded_by_sensor() {
+        let mut lc = Lifecycle::new(false);
+        lc.boot(0);
+        lc.update(&OK, 1000);
+        lc.update(&BAD, 2000);
+        assert_eq!(lc.command(Command::Start, 2000), Err(Rejected::SensorNotReady));
+        lc.update(&OK, 3000);
+        assert!(lc.command(Command::Start, 3000).is_ok());
+    }
+
+    #[test]
+    fn stop_purges_then_returns_to_standby() {
+        let mut lc = running();
+        assert_eq!(lc.command(Command::Stop, 5000).map(|t| t.to), Ok(Phase::Shutdown));
+        assert_eq!(lc.outputs(ActuatorCmd { servo_deg: 60, fan_on: false }), PURGE_OUTPUTS);
+        assert_eq!(lc.update(&OK, 5000 + SHUTDOWN_PURGE_MS - 1), None);
+        assert_eq!(lc.update(&OK, 5000 + SHUTDOWN_PURGE_MS).map(|t| t.to), Some(Phase::Standby));
+        assert_eq!(lc.outputs(PURGE_OUTPUTS), SAFE_OUTPUTS);
+        lc.set_park(30);
+        assert_eq!(lc.outputs(PURGE_OUTPUTS), ActuatorCmd { servo_deg: 30, fan_on: false });
+    }
+
+    #[test]
+    fn short_sensor_dropout_holds_run_long_one_faults() {
+        let mut lc = running();
+        let run = ActuatorCmd { servo_deg: 120, fan_on: true };
+        lc.update(&OK, 10_000);
+        assert_eq!(lc.update(&BAD, 10_000 + SENSOR_LOSS_MS - 1), None);
+        assert_eq!(lc.outputs(run), run);
+
+        assert_eq!(lc.update(&BAD, 10_000 + SENSOR_LOSS_MS).map(|t| t.to), Some(Phase::Fault));
+        assert_eq!(lc.fault(), Some(FaultCause::SensorLost));
+        assert_eq!(lc.outputs(run), SAFE_OUTPUTS);
+    }
+
+    #[test]
+    fn external_fault_keeps_first_cause() {
+        let mut lc = running();
+        assert_eq!(lc.raise(FaultCause::Feedback("fan_run"), 5000).map(|t| t.from), Some(Phase::Run));
+        assert_eq!(lc.raise(FaultCause::SensorLost, 6000), None);
+        assert_eq!(lc.fault(), Some(FaultCause::Feedback("fan_run")));
+        assert_eq!(Lifecycle::new(false).raise(FaultCause::SensorLost, 0), None);
+    }
+
+    #[test]
+    fn hold_keeps_unit_out_of_run_until_released() {
+        let mut lc = Lifecycle::new(true);
+        lc.boot(0);
+        assert_eq!(lc.hold(), Ok(()));
+        // SELF-TEST → STANDBY tetap jalan, tapi tidak auto-start
+        assert_eq!(lc.update(&OK, 1000).map(|t| t.to), Some(Phase::Standby));
+        assert_eq!(lc.update(&OK, 2000), None);
+        assert_eq!(lc.command(Command::Start, 2000), Err(Rejected::Held));
+        lc.release(60_000);
+        assert_eq!(lc.update(&OK, 61_000).map(|t| t.to), Some(Phase::Run));
+        assert_eq!(lc.hold(), Err(Rejected::WrongPhase(Phase::Run)));
+        assert!(!lc.held());
+
+        // Scan panjang saat SELF-TEST: timeout dihitung dari release
+        let mut lc = Lifecycle::new(false);
+        lc.boot(0);
+        lc.hold().unwrap();
+        lc.release(SELF_TEST_TIMEOUT_MS * 5);
+        assert_eq!(lc.update(&BAD, SELF_TEST_TIMEOUT_MS * 5 + 1), None);
+    }
+
+    #[test]
+    fn commands_outside_their_phase_are_rejected() {
+        let mut lc = Lifecycle::new(false);
+        assert_eq!(lc.command(Command::Start, 0), Err(Rejected::WrongPhase(Phase::Init)));
+        lc.boot(0);
+        assert_eq!(lc.command(Command::Stop, 0), Err(Rejected::WrongPhase(Phase::SelfTest)));
+        assert_eq!(lc.command(Command::Reset, 0), Err(Rejected::WrongPhase(Phase::SelfTest)));
+    }
+}
@@ -0,0 +1,136 @@
+//! Level log dan filter per modul (tanpa backend). Firmware memakai ini lewat
+//! makro `error!`..`trace!` di `src/bin/log/mod.rs`; backend-nya `esp-println`
+//! atau defmt (feature `defmt`).
+//!
+//! Modul = segmen terakhir `module_path!()` (mis. `sensor`, `control`).
+
+use core::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl Level {
+    pub const ALL: [Level; 5] = [Level::Error, Level::Warn, Level::Info, Level::Debug, Level::Trace];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Level::Error => "error",
+            Level::Warn  => "warn",
+            Level::Info  => "info",
+            Level::Debug => "debug",
+            Level::Trace => "trace",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|l| l.name().eq_ignore_ascii_case(s))
+    }
+}
+
+/// Maks. override per modul.
+pub const MAX_OVERRIDES: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Filter {
+    level:     Level,
+    overrides: [Option<(&'static str, Level)>; MAX_OVERRIDES],
+}
+
+impl Default for Filter {
+    fn default() -> Self {
+        Self::new(Level::Info)
+    }
+}
+
+/// `a::b::sensor` → `sensor`.
+pub fn module_name(path: &str) -> &str {
+    path.rsplit("::").next().unwrap_or(path)
+}
+
+impl Filter {
+    pub const fn new(level: Level) -> Self {
+        Self { level, overrides: [None; MAX_OVERRIDES] }
+    }
+
+    /// Level global (modul tanpa override).
+    pub fn level(&self) -> Level {
+        self.level
+    }
+
+    pub fn set_level(&mut self, level: Level) {
+        self.level = level;
+    }
+
+    /// Override level satu modul; None = ikut level global lagi.
+    /// false kalau tabel override penuh.
+    pub fn set_module(&mut self, module: &'static str, level: Option<Level>) -> bool {
+        let slot = self.overrides.iter().position(|o| o.is_some_and(|(m, _)| m == module));
+        match (slot, level) {
+            (Some(i), None)    => self.overrides[i] = None,
+            (Some(i), Some(l)) => self.overrides[i] = Some((module, l)),
+            (None, None)       => {}
+            (None, Some(l))    => match self.overrides.iter().position(|o| o.is_none()) {
+                Some(i) => self.overrides[i] = Some((module, l)),
+                None    => return false,
+            },
+        }
+        true
+    }
+
+    /// Level efektif untuk `module_path!()` (atau nama modul).
+    pub fn level_for(&self, path: &str) -> Level {
+        let m = module_name(path);
+        self.overrides.iter().flatten().find(|(o, _)| *o == m).map_or(self.level, |(_, l)| *l)
+    }
+
+    pub fn enabled(&self, path: &str, level: Level) -> bool {
+        level <= self.level_for(path)
+    }
+
+    pub fn overrides(&self) -> impl Iterator<Item = (&'static str, Level)> + '_ {
+        self.overrides.iter().flatten().copied()
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn global_level_and_module_overrides() {
+        let mut f = Filter::default();
+        assert!(f.enabled("hello_rust::tasks::sensor", Level::Warn));
+        assert!(!f.enabled("hello_rust::tasks::sensor", Level::Debug));
+
+        assert!(f.set_module("sensor", Some(Level::Trace)));
+        assert!(f.set_module("telemetry", Some(Level::Error)));
+        assert!(f.enabled("hello_rust::tasks::sensor", Level::Trace));
+        assert!(!f.enabled("hello_rust::tasks::telemetry", Level::Warn));
+        assert!(!f.enabled("hello_rust::tasks::control", Level::Debug));
+
+        f.set_level(Level::Debug);
+        assert!(f.enabled("control", Level::Debug));
+        assert!(f.set_module("sensor", None));
+        assert_eq!(f.level_for("x::sensor"), Level::Debug);
+        assert_eq!(f.overrides().count(), 1);
+
+        for i in 0..MAX_OVERRIDES - 1 { assert!(f.set_module(["a", "b", "c", "d", "e", "f", "g"][i], Some(Level::Warn))); }
+        assert!(!f.set_module("h", Some(Level::Warn)));
+        assert!(f.set_module("a", Some(Level::Info)));
+
+        assert_eq!(Level::parse("WARN"), Some(Level::Warn));
+        assert_eq!(Level::parse("verbose"), None);
+    }
+}
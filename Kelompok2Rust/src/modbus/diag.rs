@@ -0,0 +1,118 @@
+//! Counter kesehatan bus RS485 per slave: request, reply sukses, jenis error,
+//! dan latency (min/avg/max) dari request dikirim sampai reply lengkap.
+
+use super::ModbusError;
+
+/// Jumlah slave yang dilacak; slave ke-9 dst hanya dihitung di `untracked`.
+pub const MAX_SLAVES: usize = 8;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SlaveCounters {
+    pub requests:      u32,
+    pub ok:            u32,
+    pub timeouts:      u32,
+    pub crc_errors:    u32,
+    pub exceptions:    u32,
+    pub unexpected_id: u32,
+    pub short_frames:  u32,
+    /// Io + Malformed
+    pub other:         u32,
+    latency_min_us:    u32,
+    latency_max_us:    u32,
+    latency_sum_us:    u64,
+    latency_n:         u32,
+}
+
+impl SlaveCounters {
+    fn record(&mut self, result: &Result<(), ModbusError>, latency_us: u32) {
+        self.requests = self.requests.wrapping_add(1);
+        let counter = match result {
+            Ok(())                             => &mut self.ok,
+            Err(ModbusError::Timeout)          => &mut self.timeouts,
+            Err(ModbusError::Crc)              => &mut self.crc_errors,
+            Err(ModbusError::Exception(_))     => &mut self.exceptions,
+            Err(ModbusError::UnexpectedSlave(_)) => &mut self.unexpected_id,
+            Err(ModbusError::ShortFrame)       => &mut self.short_frames,
+            Err(ModbusError::Io | ModbusError::Malformed | ModbusError::InvalidRequest) => &mut self.other,
+        };
+        *counter = counter.wrapping_add(1);
+
+        // Latency hanya untuk reply yang benar-benar datang
+        if result.is_ok() || matches!(result, Err(ModbusError::Exception(_))) {
+            if self.latency_n == 0 || latency_us < self.latency_min_us { self.latency_min_us = latency_us; }
+            if latency_us > self.latency_max_us { self.latency_max_us = latency_us; }
+            self.latency_sum_us += latency_us as u64;
+            self.latency_n += 1;
+        }
+    }
+
+    pub fn latency_min_us(&self) -> Option<u32> {
+        (self.latency_n > 0).then_some(self.latency_min_us)
+    }
+
+    pub fn latency_max_us(&self) -> Option<u32> {
+        (self.latency_n > 0).then_some(self.latency_max_us)
+    }
+
+    pub fn latency_avg_us(&self) -> Option<u32> {
+        (self.latency_n > 0).then(|| (self.latency_sum_us / self.latency_n as u64) as u32)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BusDiagnostics {
+    slaves:        [(u8, SlaveCounters); MAX_SLAVES],
+    used:          usize,
+    /// Request ke slave yang tidak kebagian slot.
+    pub untracked: u32,
+}
+
+impl Default for BusDiagnostics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BusDiagnostics {
+    pub const fn new() -> Self {
+        Self {
+            slaves: [(0, SlaveCounters {
+                requests: 0, ok: 0, timeouts: 0, crc_errors: 0, exceptions: 0,
+                unexpected_id: 0, short_frames: 0, other: 0,
+                latency_min_us: 0, latency_max_us: 0, latency_sum_us: 0, latency_n: 0,
+            }); MAX_SLAVES],
+            used: 0,
+            untracked: 0,
+        }
+    }
+
+    /// Catat hasil satu transaksi ke slave `sid`.
+    pub fn record(&mut self, sid: u8, result: &Result<(), ModbusError>, latency_us: u32) {
+        let idx = match self.slaves[..self.used].iter().position(|(id, _)| *id == sid) {
+            Some(i) => i,
+            None if self.used < MAX_SLAVES => {
+                self.slaves[self.used] = (sid, SlaveCounters::default());
+                self.used += 1;
+                self.used - 1
+            }
+            None => {
+                self.untracked = self.untracked.wrapping_add(1);
+                return;
+            }
+        };
+        self.slaves[idx].1.record(result, latency_us);
+    }
+
+    pub fn slave(&self, sid: u8) -> Option<&SlaveCounters> {
+        self.iter().find(|(id, _)| *id == sid).map(|(_, c)| c)
+    }
+
+    /// Slave yang pernah di-request, urut sesuai kemunculan pertama.
+    pub fn iter(&self) -> impl Iterator<Item = (u8, &SlaveCounters)> {
+        self.slaves[..self.used].iter().map(|(id, c)| (*id, c))
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
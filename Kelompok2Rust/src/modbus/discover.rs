@@ -0,0 +1,98 @@
+//! Scan penemuan modul RS485 baru: cari slave ID yang menjawab di tiap
+//! baudrate, lalu baca satu per satu register di rentang tertentu dengan FC03
+//! dan FC04. Hasil dikirim lewat callback async sebagai baris tabel.
+
+use core::future::Future;
+
+use crate::hal::{Clock, ModbusPort, SetBaud};
+
+use super::{Master, ModbusError, FC_READ_HOLDING, FC_READ_INPUT};
+
+/// Baudrate umum modul RS485 murah.
+pub const COMMON_BAUDS: [u32; 6] = [2_400, 4_800, 9_600, 19_200, 38_400, 115_200];
+pub const MAX_BAUDS: usize = COMMON_BAUDS.len();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScanConfig {
+    /// Rentang slave ID (inklusif).
+    pub first_id:   u8,
+    pub last_id:    u8,
+    bauds:          [u32; MAX_BAUDS],
+    n_bauds:        usize,
+    /// Rentang register yang dibaca di tiap ID yang menjawab.
+    pub reg_start:  u16,
+    pub reg_count:  u16,
+}
+
+impl ScanConfig {
+    pub fn new(first_id: u8, last_id: u8, reg_start: u16, reg_count: u16) -> Self {
+        Self { first_id, last_id, bauds: [0; MAX_BAUDS], n_bauds: 0, reg_start, reg_count }
+    }
+
+    /// Tambah baudrate yang dicoba (urut); diabaikan kalau sudah `MAX_BAUDS`.
+    pub fn with_baud(mut self, baud: u32) -> Self {
+        if self.n_bauds < MAX_BAUDS {
+            self.bauds[self.n_bauds] = baud;
+            self.n_bauds += 1;
+        }
+        self
+    }
+
+    pub fn with_common_bauds(self) -> Self {
+        COMMON_BAUDS.into_iter().fold(self, |c, b| c.with_baud(b))
+    }
+
+    pub fn bauds(&self) -> &[u32] {
+        &self.bauds[..self.n_bauds]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScanEvent {
+    /// Mulai scan di baudrate ini.
+    Baud(u32),
+    /// Slave menjawab (normal atau exception) di baudrate ini.
+    Found { baud: u32, sid: u8 },
+    /// Satu register: nilai mentah atau error (exception / timeout).
+    Register { sid: u8, fc: u8, addr: u16, result: Result<u16, ModbusError> },
+    Done { found: u32 },
+}
+
+/// Error yang berarti "ada slave di ID ini" (jawaban dengan CRC benar).
+fn answered(r: &Result<u16, ModbusError>) -> bool {
+    matches!(r, Ok(_) | Err(ModbusError::Exception(_)))
+}
+
+/// Jalankan scan. Baudrate port dibiarkan di baudrate terakhir yang dicoba;
+/// pemanggil mengembalikannya sendiri. `emit` di-await tiap baris, jadi
+/// penerima yang lambat menahan scan alih-alih kehilangan baris.
+pub async fn scan<P, C, F>(master: &mut Master<P, C>, cfg: &ScanConfig, mut emit: impl FnMut(ScanEvent) -> F)
+where
+    P: ModbusPort + SetBaud,
+    C: Clock,
+    F: Future<Output = ()>,
+{
+    let mut found = 0u32;
+    for &baud in cfg.bauds() {
+        if master.port_mut().set_baud(baud).is_err() { continue; }
+        emit(ScanEvent::Baud(baud)).await;
+
+        for sid in cfg.first_id..=cfg.last_id {
+            // Alamat awal rentang dengan FC03 lalu FC04; exception pun berarti ada slave
+            let probe = master.read_u16(sid, FC_READ_HOLDING, cfg.reg_start).await;
+            let alive = answered(&probe) || answered(&master.read_u16(sid, FC_READ_INPUT, cfg.reg_start).await);
+            if !alive { continue; }
+            found += 1;
+            emit(ScanEvent::Found { baud, sid }).await;
+
+            for fc in [FC_READ_HOLDING, FC_READ_INPUT] {
+                for i in 0..cfg.reg_count {
+                    let addr = cfg.reg_start.wrapping_add(i);
+                    let result = master.read_u16(sid, fc, addr).await;
+                    emit(ScanEvent::Register { sid, fc, addr, result }).await;
+                }
+            }
+        }
+    }
+    emit(ScanEvent::Done { found }).await;
+}
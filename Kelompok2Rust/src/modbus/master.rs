@@ -0,0 +1,105 @@
+//! Master Modbus RTU di atas `ModbusPort`, sekaligus mencatat diagnostik bus.
+
+use crate::hal::{Clock, ModbusPort, PortError};
+
+use super::diag::BusDiagnostics;
+use super::{parse_bits_response, parse_read_response, parse_write_response, read_request, read_response_len};
+use super::{write_coil_request, write_coils_request, ModbusError, COIL_OFF, COIL_ON, FC_WRITE_COIL, FC_WRITE_COILS};
+
+/// Maksimal register per request (buffer reply 64 byte).
+pub const MAX_READ_REGS: usize = 16;
+/// Maksimal coil/discrete input per request baca atau tulis.
+pub const MAX_BITS: usize = 64;
+
+// Buffer reply terbesar (FC03/04 `MAX_READ_REGS`)
+const RX_LEN: usize = read_response_len(MAX_READ_REGS as u16);
+
+/// Jumlah item dari pemanggil; di luar batas → tidak dikirim dan tidak dicatat di diagnostik.
+fn check_count(n: usize, max: usize) -> Result<(), ModbusError> {
+    if n == 0 || n > max { return Err(ModbusError::InvalidRequest); }
+    Ok(())
+}
+
+pub struct Master<P, C> {
+    port:  P,
+    clock: C,
+    diag:  BusDiagnostics,
+}
+
+impl<P: ModbusPort, C: Clock> Master<P, C> {
+    pub fn new(port: P, clock: C) -> Self {
+        Self { port, clock, diag: BusDiagnostics::new() }
+    }
+
+    /// Satu transaksi: kirim `req`, validasi reply dengan `parse`, catat ke diagnostik.
+    async fn transaction(&mut self, sid: u8, req: &[u8], parse: impl FnOnce(&[u8]) -> Result<(), ModbusError>) -> Result<(), ModbusError> {
+        let mut rx = [0u8; RX_LEN];
+
+        let t0 = self.clock.now_us();
+        let reply = self.port.transact(req, &mut rx).await;
+        let latency_us = self.clock.now_us().saturating_sub(t0).min(u32::MAX as u64) as u32;
+
+        let result = match reply {
+            Ok(n) => parse(&rx[..n]),
+            Err(PortError::Timeout) => Err(ModbusError::Timeout),
+            Err(PortError::Io) => Err(ModbusError::Io),
+        };
+        self.diag.record(sid, &result, latency_us);
+        result
+    }
+
+    /// FC03/FC04: baca `out.len()` register (1..=`MAX_READ_REGS`) mulai `addr`.
+    pub async fn read_registers(&mut self, sid: u8, fc: u8, addr: u16, out: &mut [u16]) -> Result<(), ModbusError> {
+        check_count(out.len(), MAX_READ_REGS)?;
+        let req = read_request(sid, fc, addr, out.len() as u16);
+        self.transaction(sid, &req, |rx| parse_read_response(rx, sid, fc, out)).await
+    }
+
+    /// FC01/FC02: baca `out.len()` coil / discrete input (1..=`MAX_BITS`) mulai `addr`.
+    pub async fn read_bits(&mut self, sid: u8, fc: u8, addr: u16, out: &mut [bool]) -> Result<(), ModbusError> {
+        check_count(out.len(), MAX_BITS)?;
+        let req = read_request(sid, fc, addr, out.len() as u16);
+        self.transaction(sid, &req, |rx| parse_bits_response(rx, sid, fc, out)).await
+    }
+
+    pub async fn read_bit(&mut self, sid: u8, fc: u8, addr: u16) -> Result<bool, ModbusError> {
+        let mut bit = [false; 1];
+        self.read_bits(sid, fc, addr, &mut bit).await?;
+        Ok(bit[0])
+    }
+
+    /// FC05: satu coil ON/OFF.
+    pub async fn write_coil(&mut self, sid: u8, addr: u16, on: bool) -> Result<(), ModbusError> {
+        let req = write_coil_request(sid, addr, on);
+        let value = if on { COIL_ON } else { COIL_OFF };
+        self.transaction(sid, &req, |rx| parse_write_response(rx, sid, FC_WRITE_COIL, addr, value)).await
+    }
+
+    /// FC0F: `bits.len()` coil (1..=`MAX_BITS`) berurutan mulai `addr`.
+    pub async fn write_coils(&mut self, sid: u8, addr: u16, bits: &[bool]) -> Result<(), ModbusError> {
+        check_count(bits.len(), MAX_BITS)?;
+        let mut req = [0u8; 9 + MAX_BITS / 8];
+        let n = write_coils_request(sid, addr, bits, &mut req);
+        let count = bits.len() as u16;
+        self.transaction(sid, &req[..n], |rx| parse_write_response(rx, sid, FC_WRITE_COILS, addr, count)).await
+    }
+
+    pub async fn read_u16(&mut self, sid: u8, fc: u8, addr: u16) -> Result<u16, ModbusError> {
+        let mut reg = [0u16; 1];
+        self.read_registers(sid, fc, addr, &mut reg).await?;
+        Ok(reg[0])
+    }
+
+    pub fn diag(&self) -> &BusDiagnostics {
+        &self.diag
+    }
+
+    pub fn diag_mut(&mut self) -> &mut BusDiagnostics {
+        &mut self.diag
+    }
+
+    /// Port di bawahnya (mis. ganti baudrate).
+    pub fn port_mut(&mut self) -> &mut P {
+        &mut self.port
+    }
+}
@@ -0,0 +1,213 @@
+//! Modbus RTU mentah (tanpa library): CRC16, susun request, validasi response.
+
+pub mod diag;
+pub mod discover;
+pub mod master;
+pub mod sniff;
+
+pub use master::Master;
+
+// Function Codes: 0x01=Coil, 0x02=Discrete Input, 0x03=Holding, 0x04=Input.
+pub const FC_READ_COILS: u8 = 0x01;
+pub const FC_READ_DISCRETE: u8 = 0x02;
+pub const FC_READ_HOLDING: u8 = 0x03;
+pub const FC_READ_INPUT: u8 = 0x04;
+// Tulis coil: 0x05=satu, 0x0F=banyak.
+pub const FC_WRITE_COIL: u8 = 0x05;
+pub const FC_WRITE_COILS: u8 = 0x0F;
+
+/// Nilai FC05 untuk coil ON / OFF.
+pub const COIL_ON: u16 = 0xFF00;
+pub const COIL_OFF: u16 = 0x0000;
+
+/// CRC16 Modbus (poly 0xA001, init 0xFFFF).
+pub fn crc16(data: &[u8]) -> u16 {
+    let mut crc = 0xFFFFu16;
+    for &b in data {
+        crc ^= b as u16;
+        for _ in 0..8 {
+            crc = if (crc & 1) != 0 { (crc >> 1) ^ 0xA001 } else { crc >> 1 };
+        }
+    }
+    crc
+}
+
+/// Cek 2 byte terakhir frame (CRC Lo, CRC Hi).
+pub fn check_crc(frame: &[u8]) -> bool {
+    if frame.len() < 3 { return false; }
+    let calc = crc16(&frame[..frame.len() - 2]);
+    frame[frame.len() - 2] == (calc & 0xFF) as u8 && frame[frame.len() - 1] == (calc >> 8) as u8
+}
+
+/// [ID][FC][ADDR_H][ADDR_L][CNT_H][CNT_L][CRC_L][CRC_H]
+pub fn read_request(sid: u8, fc: u8, addr: u16, count: u16) -> [u8; 8] {
+    let mut req = [0u8; 8];
+    req[0] = sid;
+    req[1] = fc;
+    req[2..4].copy_from_slice(&addr.to_be_bytes());
+    req[4..6].copy_from_slice(&count.to_be_bytes());
+    let crc = crc16(&req[..6]);
+    req[6] = (crc & 0xFF) as u8;      // CRC Lo
+    req[7] = (crc >> 8) as u8;        // CRC Hi
+    req
+}
+
+/// Panjang response normal: ID+FC+BC+DATA(2*count)+CRC2.
+pub const fn read_response_len(count: u16) -> usize {
+    5 + 2 * count as usize
+}
+
+/// Panjang response FC01/FC02: ID+FC+BC+DATA(ceil(count/8))+CRC2.
+pub const fn bits_response_len(count: u16) -> usize {
+    5 + (count as usize).div_ceil(8)
+}
+
+/// FC05: [ID][05][ADDR][FF00|0000][CRC]. Response = echo request.
+pub fn write_coil_request(sid: u8, addr: u16, on: bool) -> [u8; 8] {
+    read_request(sid, FC_WRITE_COIL, addr, if on { COIL_ON } else { COIL_OFF })
+}
+
+/// FC0F: [ID][0F][ADDR][COUNT][BC][bit LSB-first...][CRC] ke `buf`; return panjang frame.
+pub fn write_coils_request(sid: u8, addr: u16, bits: &[bool], buf: &mut [u8]) -> usize {
+    let bc = bits.len().div_ceil(8);
+    let n = 7 + bc;
+    assert!(!bits.is_empty() && buf.len() >= n + 2);
+    buf[..6].copy_from_slice(&read_request(sid, FC_WRITE_COILS, addr, bits.len() as u16)[..6]);
+    buf[6] = bc as u8;
+    buf[7..n].fill(0);
+    for (i, _) in bits.iter().enumerate().filter(|(_, b)| **b) { buf[7 + i / 8] |= 1 << (i % 8); }
+    let crc = crc16(&buf[..n]);
+    buf[n] = (crc & 0xFF) as u8;
+    buf[n + 1] = (crc >> 8) as u8;
+    n + 2
+}
+
+/// Panjang frame exception: ID+FC|0x80+CODE+CRC2.
+pub const EXCEPTION_LEN: usize = 5;
+
+/// Kenapa satu transaksi Modbus gagal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModbusError {
+    /// Tidak ada byte reply sama sekali.
+    Timeout,
+    /// Error UART (overrun, framing).
+    Io,
+    /// Reply terpotong (lebih pendek dari yang diharapkan).
+    ShortFrame,
+    /// CRC tidak cocok.
+    Crc,
+    /// Slave menjawab dengan exception code.
+    Exception(u8),
+    /// CRC valid, tapi dari slave ID lain.
+    UnexpectedSlave(u8),
+    /// CRC valid, tapi function code / byte count tidak sesuai request.
+    Malformed,
+    /// Request ditolak sebelum dikirim (jumlah register/bit 0 atau melebihi batas `Master`).
+    InvalidRequest,
+}
+
+/// Frame exception yang valid untuk `sid`/`fc` → Err(Exception); None kalau bukan exception.
+fn exception(frame: &[u8], sid: u8, fc: u8) -> Option<ModbusError> {
+    if frame[1] & 0x80 == 0 { return None; }
+    if !check_crc(&frame[..EXCEPTION_LEN]) { return Some(ModbusError::Crc); }
+    if frame[0] != sid { return Some(ModbusError::UnexpectedSlave(frame[0])); }
+    if frame[1] != fc | 0x80 { return Some(ModbusError::Malformed); }
+    Some(ModbusError::Exception(frame[2]))
+}
+
+/// Validasi response FC01/FC02 lalu isi `out` (jumlah bit = `out.len()`, LSB dulu).
+pub fn parse_bits_response(frame: &[u8], sid: u8, fc: u8, out: &mut [bool]) -> Result<(), ModbusError> {
+    if frame.len() < EXCEPTION_LEN { return Err(ModbusError::ShortFrame); }
+    if let Some(e) = exception(frame, sid, fc) { return Err(e); }
+
+    let len = bits_response_len(out.len() as u16);
+    if frame.len() < len { return Err(ModbusError::ShortFrame); }
+    if !check_crc(&frame[..len]) { return Err(ModbusError::Crc); }
+    if frame[0] != sid { return Err(ModbusError::UnexpectedSlave(frame[0])); }
+    if frame[1] != fc || frame[2] as usize != len - 5 { return Err(ModbusError::Malformed); }
+
+    for (i, bit) in out.iter_mut().enumerate() {
+        *bit = frame[3 + i / 8] & (1 << (i % 8)) != 0;
+    }
+    Ok(())
+}
+
+/// Validasi response FC05/FC06/FC0F/FC10: echo alamat + nilai (FC05/06) atau jumlah (FC0F/10).
+pub fn parse_write_response(frame: &[u8], sid: u8, fc: u8, addr: u16, value: u16) -> Result<(), ModbusError> {
+    if frame.len() < EXCEPTION_LEN { return Err(ModbusError::ShortFrame); }
+    if let Some(e) = exception(frame, sid, fc) { return Err(e); }
+
+    if frame.len() < 8 { return Err(ModbusError::ShortFrame); }
+    if !check_crc(&frame[..8]) { return Err(ModbusError::Crc); }
+    if frame[0] != sid { return Err(ModbusError::UnexpectedSlave(frame[0])); }
+    if frame[1] != fc || frame[2..4] != addr.to_be_bytes() || frame[4..6] != value.to_be_bytes() {
+        return Err(ModbusError::Malformed);
+    }
+    Ok(())
+}
+
+/// Validasi response FC03/FC04 lalu isi `out` (jumlah register = `out.len()`).
+pub fn parse_read_response(frame: &[u8], sid: u8, fc: u8, out: &mut [u16]) -> Result<(), ModbusError> {
+    if frame.len() < EXCEPTION_LEN { return Err(ModbusError::ShortFrame); }
+
+    if let Some(e) = exception(frame, sid, fc) { return Err(e); }
+
+    let len = read_response_len(out.len() as u16);
+    if frame.len() < len { return Err(ModbusError::ShortFrame); }
+    if !check_crc(&frame[..len]) { return Err(ModbusError::Crc); }
+    if frame[0] != sid { return Err(ModbusError::UnexpectedSlave(frame[0])); }
+    if frame[1] != fc || frame[2] as usize != 2 * out.len() { return Err(ModbusError::Malformed); }
+
+    for (i, reg) in out.iter_mut().enumerate() {
+        *reg = u16::from_be_bytes([frame[3 + 2 * i], frame[4 + 2 * i]]);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with_crc(pdu: &[u8]) -> Vec<u8> {
+        let mut v = pdu.to_vec();
+        v.extend_from_slice(&crc16(pdu).to_le_bytes());
+        v
+    }
+
+    #[test]
+    fn write_coils_request_packs_bits_lsb_first() {
+        // Contoh spesifikasi Modbus: 10 coil mulai 0x0013 → data CD 01
+        let bits = [true, false, true, true, false, false, true, true, true, false];
+        let mut buf = [0u8; 16];
+        let n = write_coils_request(0x11, 0x0013, &bits, &mut buf);
+        assert_eq!(&buf[..n], &with_crc(&[0x11, 0x0F, 0x00, 0x13, 0x00, 0x0A, 0x02, 0xCD, 0x01])[..]);
+
+        // Sisa buffer lama tidak bocor ke byte data
+        let mut buf = [0xFFu8; 16];
+        let n = write_coils_request(1, 0, &[false, true], &mut buf);
+        assert_eq!(&buf[..n], &with_crc(&[0x01, 0x0F, 0x00, 0x00, 0x00, 0x02, 0x01, 0x02])[..]);
+    }
+
+    #[test]
+    fn parse_bits_response_checks_frame_and_unpacks() {
+        let frame = with_crc(&[0x11, 0x01, 0x02, 0xCD, 0x01]);
+        let mut out = [false; 10];
+        assert_eq!(parse_bits_response(&frame, 0x11, FC_READ_COILS, &mut out), Ok(()));
+        assert_eq!(out, [true, false, true, true, false, false, true, true, true, false]);
+
+        let mut out = [false; 10];
+        assert_eq!(parse_bits_response(&frame[..6], 0x11, FC_READ_COILS, &mut out), Err(ModbusError::ShortFrame));
+        assert_eq!(parse_bits_response(&frame, 0x12, FC_READ_COILS, &mut out), Err(ModbusError::UnexpectedSlave(0x11)));
+        assert_eq!(parse_bits_response(&frame, 0x11, FC_READ_DISCRETE, &mut out), Err(ModbusError::Malformed));
+        let mut bad = frame.clone();
+        bad[3] ^= 0x01;
+        assert_eq!(parse_bits_response(&bad, 0x11, FC_READ_COILS, &mut out), Err(ModbusError::Crc));
+        // Panjang frame pas, tapi byte count bukan ceil(4/8)
+        let mut out = [false; 4];
+        assert_eq!(parse_bits_response(&with_crc(&[0x11, 0x01, 0x02, 0x0F]), 0x11, FC_READ_COILS, &mut out), Err(ModbusError::Malformed));
+        assert_eq!(out, [false; 4]);
+
+        let exc = with_crc(&[0x11, 0x82, 0x02]);
+        assert_eq!(parse_bits_response(&exc, 0x11, FC_READ_DISCRETE, &mut out), Err(ModbusError::Exception(2)));
+    }
+}
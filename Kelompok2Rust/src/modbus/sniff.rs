@@ -0,0 +1,355 @@
+//! Penganalisa jalur Modbus RTU pasif: potong aliran byte jadi frame berdasar
+//! jeda antar frame (t3.5), lalu pasangkan request ↔ response dengan vonis CRC.
+//!
+//! Tanpa I/O: firmware (mode `sniffer`) memberi byte + waktu terimanya.
+
+use core::fmt;
+
+use crate::rs485::t3_5_us;
+
+use super::check_crc;
+
+/// Frame RTU maksimal (ADU 256 byte).
+pub const MAX_FRAME: usize = 256;
+/// Jawaban harus mulai dalam jendela ini setelah request. Lebih pendek dari
+/// timeout master (300 ms di firmware ini) supaya retry tidak dikira jawaban.
+pub const RESPONSE_WINDOW_US: u64 = 250_000;
+
+#[derive(Clone, Copy)]
+pub struct Frame {
+    buf:          [u8; MAX_FRAME],
+    len:          usize,
+    /// Waktu byte pertama / terakhir (µs).
+    pub start_us: u64,
+    pub end_us:   u64,
+    /// Byte di atas `MAX_FRAME` dibuang.
+    pub overflow: bool,
+}
+
+impl Frame {
+    const EMPTY: Self = Self { buf: [0; MAX_FRAME], len: 0, start_us: 0, end_us: 0, overflow: false };
+
+    pub fn bytes(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+
+    pub fn crc_ok(&self) -> bool {
+        !self.overflow && check_crc(self.bytes())
+    }
+}
+
+/// Pemotong frame: byte yang datang setelah bus diam ≥ `gap_us` memulai frame baru.
+pub struct FrameSplitter {
+    gap_us: u64,
+    cur:    Frame,
+}
+
+impl FrameSplitter {
+    /// Jeda = t3.5 untuk baudrate ini.
+    pub fn new(baud: u32) -> Self {
+        Self::with_gap(t3_5_us(baud))
+    }
+
+    pub fn with_gap(gap_us: u32) -> Self {
+        Self { gap_us: gap_us as u64, cur: Frame::EMPTY }
+    }
+
+    /// Satu byte + waktu terimanya. Return frame sebelumnya kalau byte ini
+    /// datang setelah jeda antar frame.
+    pub fn push(&mut self, byte: u8, t_us: u64) -> Option<Frame> {
+        let done = self.flush(t_us);
+        let f = &mut self.cur;
+        if f.len == 0 { f.start_us = t_us; }
+        if f.len < MAX_FRAME {
+            f.buf[f.len] = byte;
+            f.len += 1;
+        } else {
+            f.overflow = true;
+        }
+        f.end_us = t_us;
+        done
+    }
+
+    /// Tutup frame yang sedang dikumpulkan kalau bus sudah diam ≥ jeda.
+    pub fn flush(&mut self, now_us: u64) -> Option<Frame> {
+        if self.cur.len == 0 || now_us.saturating_sub(self.cur.end_us) < self.gap_us { return None; }
+        let f = self.cur;
+        self.cur = Frame::EMPTY;
+        Some(f)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    Request,
+    /// Jawaban request sebelumnya; `latency_us` = akhir request → awal jawaban.
+    Response { latency_us: u32 },
+    /// CRC salah / terlalu pendek: tidak bisa dipasangkan.
+    Unknown,
+}
+
+/// Isi PDU yang dikenali (alamat & jumlah dari request, data dari response).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pdu {
+    /// FC01..04 request: alamat awal + jumlah.
+    Read { addr: u16, count: u16 },
+    /// FC01/02 response: byte status bit.
+    Bits { bytes: u8 },
+    /// FC03/04 response: jumlah register + (maks. 4) nilai pertama.
+    Registers { count: u8, first: [u16; 4] },
+    /// FC05/06 request/echo.
+    WriteSingle { addr: u16, value: u16 },
+    /// FC0F/10 request/response.
+    WriteMultiple { addr: u16, count: u16 },
+    Exception(u8),
+    /// FC lain atau panjang tidak cocok.
+    Other,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Decoded {
+    pub role:   Role,
+    pub sid:    u8,
+    pub fc:     u8,
+    pub crc_ok: bool,
+    pub pdu:    Pdu,
+}
+
+/// Request terakhir yang belum dijawab.
+#[derive(Debug, Clone, Copy)]
+struct Pending {
+    sid:    u8,
+    fc:     u8,
+    /// PDU request (untuk mencocokkan bentuk jawaban).
+    pdu:    Pdu,
+    end_us: u64,
+}
+
+impl Pending {
+    /// Bentuk `response` cocok dengan request ini: byte count FC01..04,
+    /// echo FC05/06, alamat + jumlah FC0F/10, atau exception.
+    fn answered_by(&self, sid: u8, fc: u8, response: Pdu) -> bool {
+        if sid != self.sid { return false; }
+        if fc == self.fc | 0x80 { return matches!(response, Pdu::Exception(_)); }
+        if fc != self.fc { return false; }
+        match (self.pdu, response) {
+            (Pdu::Read { count, .. }, Pdu::Bits { bytes }) => matches!(fc, 0x01 | 0x02) && bytes as u16 == count.div_ceil(8),
+            (Pdu::Read { count, .. }, Pdu::Registers { count: n, .. }) => matches!(fc, 0x03 | 0x04) && n as u16 == count,
+            (Pdu::WriteSingle { .. }, echo @ Pdu::WriteSingle { .. }) => echo == self.pdu,
+            (Pdu::WriteMultiple { addr, count }, Pdu::WriteMultiple { addr: a, count: n }) => (addr, count) == (a, n),
+            // FC lain: tidak bisa dicek bentuknya
+            (Pdu::Other, _) => true,
+            _ => false,
+        }
+    }
+}
+
+/// Pemasang request ↔ response. Master RTU hanya satu transaksi di bus pada
+/// satu waktu, jadi frame valid dari slave & FC yang sama, dengan bentuk yang
+/// cocok, dalam `RESPONSE_WINDOW_US` setelah request = jawaban. Selain itu
+/// (mis. retry setelah timeout) dianggap request baru.
+pub struct Sniffer {
+    pending:   Option<Pending>,
+    window_us: u64,
+}
+
+impl Default for Sniffer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Sniffer {
+    pub const fn new() -> Self {
+        Self::with_window(RESPONSE_WINDOW_US)
+    }
+
+    pub const fn with_window(window_us: u64) -> Self {
+        Self { pending: None, window_us }
+    }
+
+    pub fn decode(&mut self, frame: &Frame) -> Decoded {
+        let b = frame.bytes();
+        let (sid, fc) = (b.first().copied().unwrap_or(0), b.get(1).copied().unwrap_or(0));
+        if b.len() < 4 || !frame.crc_ok() {
+            // Jawaban rusak tetap "memakai" slot jawaban request sebelumnya
+            self.pending = None;
+            return Decoded { role: Role::Unknown, sid, fc, crc_ok: false, pdu: Pdu::Other };
+        }
+        // Tidak dijawab dalam jendela: lupakan
+        if self.pending.is_some_and(|p| frame.start_us.saturating_sub(p.end_us) > self.window_us) { self.pending = None; }
+
+        let pdu = &b[2..b.len() - 2];
+        let response = response_pdu(fc, pdu);
+        match self.pending {
+            Some(p) if p.answered_by(sid, fc, response) => {
+                self.pending = None;
+                let latency_us = frame.start_us.saturating_sub(p.end_us).min(u32::MAX as u64) as u32;
+                Decoded { role: Role::Response { latency_us }, sid, fc, crc_ok: true, pdu: response }
+            }
+            _ => {
+                let pdu = request_pdu(fc, pdu);
+                self.pending = Some(Pending { sid, fc, pdu, end_us: frame.end_us });
+                Decoded { role: Role::Request, sid, fc, crc_ok: true, pdu }
+            }
+        }
+    }
+}
+
+fn be(p: &[u8], i: usize) -> u16 {
+    u16::from_be_bytes([p[i], p[i + 1]])
+}
+
+fn request_pdu(fc: u8, p: &[u8]) -> Pdu {
+    match (fc, p.len()) {
+        (0x01..=0x04, 4)           => Pdu::Read { addr: be(p, 0), count: be(p, 2) },
+        (0x05 | 0x06, 4)           => Pdu::WriteSingle { addr: be(p, 0), value: be(p, 2) },
+        (0x0F | 0x10, n) if n >= 5 => Pdu::WriteMultiple { addr: be(p, 0), count: be(p, 2) },
+        _ => Pdu::Other,
+    }
+}
+
+fn response_pdu(fc: u8, p: &[u8]) -> Pdu {
+    if fc & 0x80 != 0 { return p.first().map_or(Pdu::Other, |&c| Pdu::Exception(c)); }
+    match fc {
+        0x01 | 0x02 if !p.is_empty() && p.len() == 1 + p[0] as usize => Pdu::Bits { bytes: p[0] },
+        0x03 | 0x04 if !p.is_empty() && p.len() == 1 + p[0] as usize && p[0].is_multiple_of(2) => {
+            let count = p[0] / 2;
+            let mut first = [0u16; 4];
+            for (i, v) in first.iter_mut().enumerate().take(count as usize) { *v = be(p, 1 + 2 * i); }
+            Pdu::Registers { count, first }
+        }
+        0x05 | 0x06 if p.len() == 4 => Pdu::WriteSingle { addr: be(p, 0), value: be(p, 2) },
+        0x0F | 0x10 if p.len() == 4 => Pdu::WriteMultiple { addr: be(p, 0), count: be(p, 2) },
+        _ => Pdu::Other,
+    }
+}
+
+impl fmt::Display for Decoded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.role {
+            Role::Request       => write!(f, "REQ  ")?,
+            Role::Response { .. } if matches!(self.pdu, Pdu::Exception(_)) => write!(f, "EXC  ")?,
+            Role::Response { .. } => write!(f, "RESP ")?,
+            Role::Unknown       => return write!(f, "???  sid {} fc 0x{:02X} | CRC salah / frame rusak", self.sid, self.fc),
+        }
+        write!(f, "sid {} fc 0x{:02X}", self.sid, self.fc)?;
+        match self.pdu {
+            Pdu::Read { addr, count }          => write!(f, " baca 0x{:04X} x{}", addr, count)?,
+            Pdu::Bits { bytes }                => write!(f, " {} byte status", bytes)?,
+            Pdu::Registers { count, first }    => {
+                write!(f, " {} reg:", count)?;
+                for v in &first[..(count as usize).min(4)] { write!(f, " 0x{:04X}", v)?; }
+                if count > 4 { write!(f, " ...")?; }
+            }
+            Pdu::WriteSingle { addr, value }   => write!(f, " tulis 0x{:04X} ← 0x{:04X}", addr, value)?,
+            Pdu::WriteMultiple { addr, count } => write!(f, " tulis 0x{:04X} x{}", addr, count)?,
+            Pdu::Exception(code)               => write!(f, " exception {}", code)?,
+            Pdu::Other                         => write!(f, " (PDU tidak dikenal)")?,
+        }
+        write!(f, " | CRC ok")?;
+        if let Role::Response { latency_us } = self.role { write!(f, " | {:.1} ms", latency_us as f32 / 1000.0)?; }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modbus::{crc16, read_request};
+
+    fn with_crc(pdu: &[u8]) -> Vec<u8> {
+        let mut v = pdu.to_vec();
+        v.extend_from_slice(&crc16(pdu).to_le_bytes());
+        v
+    }
+
+    /// Kirim byte dengan jarak 1 ms (9600 bps), frame dipisah `gap_us`.
+    fn feed(s: &mut FrameSplitter, frames: &[&[u8]], gap_us: u64) -> Vec<Frame> {
+        let mut out = Vec::new();
+        let mut t = 0;
+        for f in frames {
+            for &b in *f {
+                out.extend(s.push(b, t));
+                t += 1_146;
+            }
+            t += gap_us;
+        }
+        out.extend(s.flush(t + 10_000));
+        out
+    }
+
+    #[test]
+    fn silence_splits_frames_and_pairs_request_with_response() {
+        let req = read_request(1, 0x04, 0x0002, 1);
+        let resp = with_crc(&[1, 0x04, 2, 0x02, 0x53]);
+        let exc = with_crc(&[1, 0x84, 2]);
+
+        let mut s = FrameSplitter::new(9_600);
+        // Jeda 3 ms + 1 karakter dari byte terakhir > t3.5 (≈ 4 ms)
+        let frames = feed(&mut s, &[&req, &resp, &req, &exc], 3_000);
+        assert_eq!(frames.len(), 4);
+        assert_eq!(frames[0].bytes(), &req);
+        assert_eq!(frames[1].bytes(), resp.as_slice());
+
+        let mut sn = Sniffer::new();
+        let d = sn.decode(&frames[0]);
+        assert_eq!((d.role, d.pdu), (Role::Request, Pdu::Read { addr: 2, count: 1 }));
+        let d = sn.decode(&frames[1]);
+        assert!(matches!(d.role, Role::Response { latency_us } if latency_us > 3_000 && latency_us < 5_000));
+        assert_eq!(d.pdu, Pdu::Registers { count: 1, first: [0x0253, 0, 0, 0] });
+        sn.decode(&frames[2]);
+        assert_eq!(sn.decode(&frames[3]).pdu, Pdu::Exception(2));
+    }
+
+    #[test]
+    fn retry_after_timeout_is_a_new_request_not_a_response() {
+        // Master: request, timeout, retry identik, lalu slave menjawab
+        let req = with_crc(&[1, 0x06, 0x00, 0x10, 0x00, 0x01]);
+        let frame = |bytes: &[u8], start_us: u64| {
+            let mut s = FrameSplitter::with_gap(4_000);
+            for (i, &b) in bytes.iter().enumerate() { s.push(b, start_us + i as u64); }
+            s.flush(u64::MAX).unwrap()
+        };
+
+        let mut sn = Sniffer::new();
+        assert_eq!(sn.decode(&frame(&req, 0)).role, Role::Request);
+        // Retry 300 ms kemudian: sama persis dengan echo FC06, tapi di luar jendela
+        let d = sn.decode(&frame(&req, 300_000));
+        assert_eq!((d.role, d.pdu), (Role::Request, Pdu::WriteSingle { addr: 0x10, value: 1 }));
+        let d = sn.decode(&frame(&req, 320_000));
+        assert!(matches!(d.role, Role::Response { latency_us } if latency_us < 20_000));
+
+        // FC03 x2: jawaban dengan byte count salah bukan jawaban
+        let read = read_request(1, 0x03, 0x0000, 2);
+        sn.decode(&frame(&read, 1_000_000));
+        let d = sn.decode(&frame(&with_crc(&[1, 0x03, 2, 0x00, 0x01]), 1_010_000));
+        assert_eq!(d.role, Role::Request);
+        // FC01 x10 → 2 byte status
+        let coils = read_request(1, 0x01, 0x0000, 10);
+        sn.decode(&frame(&coils, 2_000_000));
+        assert_eq!(sn.decode(&frame(&with_crc(&[1, 0x01, 1, 0xFF]), 2_010_000)).role, Role::Request);
+        sn.decode(&frame(&coils, 3_000_000));
+        let d = sn.decode(&frame(&with_crc(&[1, 0x01, 2, 0xFF, 0x03]), 3_010_000));
+        assert_eq!((matches!(d.role, Role::Response { .. }), d.pdu), (true, Pdu::Bits { bytes: 2 }));
+    }
+
+    #[test]
+    fn short_gap_merges_and_bad_crc_is_unknown() {
+        let req = read_request(1, 0x03, 0x0000, 2);
+        let mut s = FrameSplitter::new(9_600);
+        // Jeda 1 ms < t3.5: dua request menyatu jadi satu frame rusak
+        let frames = feed(&mut s, &[&req, &req], 1_000);
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0].bytes().len(), 16);
+
+        let mut sn = Sniffer::new();
+        let d = sn.decode(&frames[0]);
+        assert_eq!((d.role, d.crc_ok), (Role::Unknown, false));
+
+        let mut bad = req;
+        bad[3] ^= 1;
+        let frames = feed(&mut s, &[&bad], 10_000);
+        assert!(!frames[0].crc_ok());
+    }
+}
@@ -0,0 +1,320 @@
+//! Self-test saat boot (POST): cek config, sensor, stroke servo, dan pulsa
+//! relay kipas (dengan feedback kalau ada input yang di-link).
+//!
+//! Berjalan di fase SELF-TEST, dimajukan tiap hasil polling sensor (`step`).
+//! Selama berjalan, POST yang menentukan output aktuator; lifecycle menahan
+//! SELF-TEST (`Lifecycle::set_post`). Kegagalan check yang ditandai kritis
+//! memblokir boot (FAULT sampai `reset`, yang menjalankan POST lagi).
+
+use core::fmt;
+
+use crate::control::ActuatorCmd;
+use crate::servo::ServoCal;
+use crate::sht20::Measurement;
+
+pub const CHECKS: usize = 4;
+/// Batas tunggu pembacaan sensor valid + masuk akal.
+pub const SENSOR_TIMEOUT_MS: u64 = 10_000;
+/// Lama tiap posisi stroke servo (batas bawah, batas atas, park).
+pub const STROKE_DWELL_MS: u64 = 1_500;
+/// Lama pulsa kipas ON, lalu OFF lagi (feedback harus mengikuti dalam waktu ini).
+/// Di bawah toleransi feedback input supaya pemantau DI tidak ikut alarm.
+pub const FAN_PULSE_MS: u64 = 3_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Check {
+    /// Blob config di flash valid (CRC).
+    Config,
+    /// SHT20 menjawab dengan nilai masuk akal.
+    Sensor,
+    /// Servo digerakkan batas bawah → batas atas → park.
+    Servo,
+    /// Relay kipas dipulsa ON → OFF, feedback dicek kalau ada.
+    Fan,
+}
+
+impl Check {
+    pub const ALL: [Check; CHECKS] = [Check::Config, Check::Sensor, Check::Servo, Check::Fan];
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Check::Config => "config",
+            Check::Sensor => "sensor",
+            Check::Servo  => "servo",
+            Check::Fan    => "kipas",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// Belum dijalankan.
+    Pending,
+    Pass,
+    /// Dijalankan tapi tidak bisa diverifikasi (tanpa feedback).
+    Unverified,
+    Skipped,
+    Fail(&'static str),
+}
+
+/// Apa saja yang dijalankan dan mana yang kritis (tabel di firmware).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PostPlan {
+    pub servo_stroke: bool,
+    pub fan_pulse:    bool,
+    /// Ada input feedback untuk kipas (`step` menerima state-nya).
+    pub fan_feedback: bool,
+    /// Indeks `Check as usize`; gagal di check kritis → boot diblokir.
+    pub critical:     [bool; CHECKS],
+}
+
+impl PostPlan {
+    pub const fn is_critical(&self, check: Check) -> bool {
+        self.critical[check as usize]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Report {
+    outcomes: [Outcome; CHECKS],
+}
+
+impl Report {
+    pub const fn new() -> Self {
+        Self { outcomes: [Outcome::Pending; CHECKS] }
+    }
+
+    pub fn get(&self, check: Check) -> Outcome {
+        self.outcomes[check as usize]
+    }
+
+    fn set(&mut self, check: Check, outcome: Outcome) {
+        self.outcomes[check as usize] = outcome;
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Check, Outcome)> + '_ {
+        Check::ALL.iter().map(|&c| (c, self.get(c)))
+    }
+
+    /// Check kritis pertama yang gagal.
+    pub fn blocking(&self, plan: &PostPlan) -> Option<Check> {
+        self.iter().find(|&(c, o)| plan.is_critical(c) && matches!(o, Outcome::Fail(_))).map(|(c, _)| c)
+    }
+
+    pub fn failures(&self) -> usize {
+        self.outcomes.iter().filter(|o| matches!(o, Outcome::Fail(_))).count()
+    }
+}
+
+impl Default for Report {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Stage {
+    Sensor,
+    /// Posisi stroke ke-`idx` (lihat `Post::stroke`).
+    Stroke { idx: usize },
+    FanOn { seen_on: bool },
+    FanOff { seen_on: bool, seen_off: bool },
+    Done,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Post {
+    plan:     PostPlan,
+    /// Batas bawah, batas atas, park.
+    stroke:   [i32; 3],
+    stage:    Stage,
+    since_ms: u64,
+    report:   Report,
+}
+
+impl Post {
+    /// `config` = hasil validasi blob config saat boot.
+    pub fn new(plan: PostPlan, config: Outcome, servo: &ServoCal, now_ms: u64) -> Self {
+        let mut report = Report::new();
+        report.set(Check::Config, config);
+        Self {
+            plan,
+            stroke: [servo.limit_lo, servo.limit_hi, servo.park_deg],
+            stage: Stage::Sensor,
+            since_ms: now_ms,
+            report,
+        }
+    }
+
+    pub fn report(&self) -> &Report {
+        &self.report
+    }
+
+    pub fn plan(&self) -> &PostPlan {
+        &self.plan
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.stage == Stage::Done
+    }
+
+    /// Majukan dengan hasil polling terbaru dan state feedback kipas (None =
+    /// tidak ada input). Return output yang harus di-drive; None = selesai.
+    pub fn step(&mut self, m: &Measurement, feedback: Option<bool>, now_ms: u64) -> Option<ActuatorCmd> {
+        let park = ActuatorCmd { servo_deg: self.stroke[2], fan_on: false };
+        let elapsed = now_ms.saturating_sub(self.since_ms);
+
+        match self.stage {
+            Stage::Sensor => match (m.rh, m.temp) {
+                (Some(rh), Some(t)) if !(0.0..=100.0).contains(&rh) || !(-40.0..=125.0).contains(&t) => {
+                    self.next(Check::Sensor, Outcome::Fail("nilai di luar rentang SHT20"), now_ms);
+                }
+                (Some(_), Some(_)) => self.next(Check::Sensor, Outcome::Pass, now_ms),
+                _ if elapsed >= SENSOR_TIMEOUT_MS => self.next(Check::Sensor, Outcome::Fail("tidak menjawab"), now_ms),
+                _ => {}
+            },
+            Stage::Stroke { idx } if elapsed >= STROKE_DWELL_MS => {
+                if idx + 1 < self.stroke.len() {
+                    self.stage = Stage::Stroke { idx: idx + 1 };
+                    self.since_ms = now_ms;
+                } else {
+                    // Tanpa sensor posisi, gerakan hanya bisa diamati operator
+                    self.next(Check::Servo, Outcome::Unverified, now_ms);
+                }
+            }
+            Stage::Stroke { .. } => {}
+            Stage::FanOn { seen_on } => {
+                let seen_on = seen_on || feedback == Some(true);
+                self.stage = Stage::FanOn { seen_on };
+                if elapsed >= FAN_PULSE_MS {
+                    self.stage = Stage::FanOff { seen_on, seen_off: false };
+                    self.since_ms = now_ms;
+                }
+            }
+            Stage::FanOff { seen_on, seen_off } => {
+                let seen_off = seen_off || feedback == Some(false);
+                self.stage = Stage::FanOff { seen_on, seen_off };
+                if elapsed >= FAN_PULSE_MS {
+                    let outcome = match (self.plan.fan_feedback, seen_on, seen_off) {
+                        (false, ..)         => Outcome::Unverified,
+                        (true, false, _)    => Outcome::Fail("feedback tidak datang saat ON"),
+                        (true, true, false) => Outcome::Fail("feedback tetap aktif saat OFF"),
+                        (true, true, true)  => Outcome::Pass,
+                    };
+                    self.next(Check::Fan, outcome, now_ms);
+                }
+            }
+            Stage::Done => return None,
+        }
+
+        match self.stage {
+            Stage::Sensor | Stage::FanOff { .. } => Some(park),
+            Stage::Stroke { idx } => Some(ActuatorCmd { servo_deg: self.stroke[idx], fan_on: false }),
+            Stage::FanOn { .. } => Some(ActuatorCmd { fan_on: true, ..park }),
+            Stage::Done => None,
+        }
+    }
+
+    /// Catat hasil `check`, lalu lanjut ke tahap berikutnya sesuai plan.
+    fn next(&mut self, check: Check, outcome: Outcome, now_ms: u64) {
+        self.report.set(check, outcome);
+        self.since_ms = now_ms;
+        self.stage = match check {
+            Check::Config | Check::Sensor if self.plan.servo_stroke => Stage::Stroke { idx: 0 },
+            Check::Config | Check::Sensor => {
+                self.report.set(Check::Servo, Outcome::Skipped);
+                self.fan_stage()
+            }
+            Check::Servo => self.fan_stage(),
+            Check::Fan => Stage::Done,
+        };
+    }
+
+    fn fan_stage(&mut self) -> Stage {
+        if self.plan.fan_pulse { return Stage::FanOn { seen_on: false }; }
+        self.report.set(Check::Fan, Outcome::Skipped);
+        Stage::Done
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Outcome::Pending    => f.write_str("belum"),
+            Outcome::Pass       => f.write_str("OK"),
+            Outcome::Unverified => f.write_str("jalan (tanpa feedback)"),
+            Outcome::Skipped    => f.write_str("dilewati"),
+            Outcome::Fail(why)  => write!(f, "GAGAL: {}", why),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OK:  Measurement = Measurement { rh: Some(60.0), temp: Some(27.0) };
+    const BAD: Measurement = Measurement { rh: None, temp: None };
+    const PLAN: PostPlan = PostPlan { servo_stroke: true, fan_pulse: true, fan_feedback: true, critical: [false, true, false, true] };
+
+    /// Jalankan sampai selesai dengan polling 1 s; feedback = `fb(fan_on)`.
+    fn run(post: &mut Post, m: Measurement, fb: impl Fn(bool) -> Option<bool>) -> u64 {
+        let mut fan = false;
+        let mut t = 0;
+        while let Some(cmd) = post.step(&m, fb(fan), t) {
+            fan = cmd.fan_on;
+            t += 1000;
+            assert!(t < 60_000);
+        }
+        t
+    }
+
+    #[test]
+    fn full_sequence_passes_with_following_feedback() {
+        let servo = ServoCal { limit_lo: 10, limit_hi: 100, park_deg: 20, ..ServoCal::DEFAULT };
+        let mut post = Post::new(PLAN, Outcome::Pass, &servo, 0);
+        assert_eq!(post.step(&BAD, Some(false), 0), Some(ActuatorCmd { servo_deg: 20, fan_on: false }));
+        assert_eq!(post.step(&OK, Some(false), 1000), Some(ActuatorCmd { servo_deg: 10, fan_on: false }));
+        assert_eq!(post.step(&OK, Some(false), 3000), Some(ActuatorCmd { servo_deg: 100, fan_on: false }));
+
+        let t = run(&mut post, OK, Some);
+        assert!(post.is_done() && t > 3 * STROKE_DWELL_MS + 2 * FAN_PULSE_MS);
+        assert!(post.report().iter().all(|(c, o)| o == if c == Check::Servo { Outcome::Unverified } else { Outcome::Pass }));
+        assert_eq!(post.report().blocking(&PLAN), None);
+    }
+
+    #[test]
+    fn missing_or_stuck_feedback_fails_fan() {
+        let mut post = Post::new(PLAN, Outcome::Pass, &ServoCal::DEFAULT, 0);
+        run(&mut post, OK, |_| Some(false));
+        assert_eq!(post.report().get(Check::Fan), Outcome::Fail("feedback tidak datang saat ON"));
+        assert_eq!(post.report().blocking(&PLAN), Some(Check::Fan));
+
+        let mut post = Post::new(PLAN, Outcome::Pass, &ServoCal::DEFAULT, 0);
+        run(&mut post, OK, |_| Some(true));
+        assert_eq!(post.report().get(Check::Fan), Outcome::Fail("feedback tetap aktif saat OFF"));
+
+        // Tanpa input feedback: hanya dipulsa
+        let plan = PostPlan { fan_feedback: false, ..PLAN };
+        let mut post = Post::new(plan, Outcome::Pass, &ServoCal::DEFAULT, 0);
+        run(&mut post, OK, |_| None);
+        assert_eq!(post.report().get(Check::Fan), Outcome::Unverified);
+    }
+
+    #[test]
+    fn sensor_timeout_and_non_critical_config_failure() {
+        let plan = PostPlan { servo_stroke: false, fan_pulse: false, ..PLAN };
+        let mut post = Post::new(plan, Outcome::Fail("CRC"), &ServoCal::DEFAULT, 0);
+        let t = run(&mut post, BAD, |_| None);
+        assert_eq!(t, SENSOR_TIMEOUT_MS);
+        assert_eq!(post.report().get(Check::Sensor), Outcome::Fail("tidak menjawab"));
+        assert_eq!((post.report().get(Check::Servo), post.report().get(Check::Fan)), (Outcome::Skipped, Outcome::Skipped));
+        assert_eq!(post.report().failures(), 2);
+        // Config tidak kritis di plan ini → yang memblokir sensor
+        assert_eq!(post.report().blocking(&plan), Some(Check::Sensor));
+
+        let mut post = Post::new(plan, Outcome::Pass, &ServoCal::DEFAULT, 0);
+        run(&mut post, Measurement { rh: Some(120.0), temp: Some(25.0) }, |_| None);
+        assert_eq!(post.report().get(Check::Sensor), Outcome::Fail("nilai di luar rentang SHT20"));
+    }
+}
@@ -0,0 +1,102 @@
+//! I/O jarak jauh lewat Modbus: kanal DO/DI yang titiknya coil / discrete
+//! input modul relay RS485, bukan GPIO di board.
+//!
+//! `dout`/`din` tetap bicara ke pin mentah. Di firmware, pin remote hanya
+//! menulis/membaca image di RAM; task pemilik bus menyamakan image itu dengan
+//! modul lewat `sync_coils` / `poll_inputs`.
+
+use core::fmt;
+
+use crate::hal::{Clock, ModbusPort};
+use crate::modbus::{Master, FC_READ_COILS, FC_READ_DISCRETE};
+
+/// Gagal baca berturut-turut sebelum level discrete input remote dibuang
+/// (3 x `REMOTE_IO_PERIOD_MS` di firmware ≈ 750 ms).
+pub const INPUT_STALE_FAILS: u8 = 3;
+
+/// Lokasi fisik satu kanal I/O (tabel `DO_CHANNELS` / `DI_CHANNELS`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IoPoint {
+    /// Pin GPIO di board.
+    Gpio,
+    /// Coil (output: FC01 + FC05) atau discrete input (input: FC02) di slave `sid`.
+    Modbus { sid: u8, addr: u16 },
+}
+
+impl IoPoint {
+    pub fn is_remote(&self) -> bool {
+        matches!(self, IoPoint::Modbus { .. })
+    }
+}
+
+impl fmt::Display for IoPoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IoPoint::Gpio => f.write_str("GPIO"),
+            IoPoint::Modbus { sid, addr } => write!(f, "Modbus ID {} #{}", sid, addr),
+        }
+    }
+}
+
+/// Image satu coil remote.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CoilImage {
+    /// Level yang diminta `dout` (true = coil ON).
+    pub desired: bool,
+    /// Level coil di modul (terbaca / terkonfirmasi); None = belum tahu atau transaksi terakhir gagal.
+    pub actual:  Option<bool>,
+    /// Transaksi gagal berturut-turut.
+    pub fails:   u8,
+}
+
+impl CoilImage {
+    pub const OFF: Self = Self { desired: false, actual: None, fails: 0 };
+}
+
+/// Image satu discrete input remote.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InputImage {
+    /// Level terakhir yang terbaca; ditahan sampai `INPUT_STALE_FAILS` kali gagal,
+    /// setelah itu None (tidak diketahui) seperti sebelum pernah terbaca.
+    pub level: Option<bool>,
+    /// Transaksi gagal berturut-turut.
+    pub fails: u8,
+}
+
+impl InputImage {
+    pub const UNKNOWN: Self = Self { level: None, fails: 0 };
+}
+
+/// Baca balik tiap coil remote (FC01) lalu tulis (FC05) yang belum sama dengan
+/// `desired`, jadi modul yang sempat reset / mati listrik ikut dipulihkan.
+/// Kanal `Gpio` dilewati.
+pub async fn sync_coils<P: ModbusPort, C: Clock>(master: &mut Master<P, C>, points: &[IoPoint], image: &mut [CoilImage]) {
+    for (point, img) in points.iter().zip(image.iter_mut()) {
+        let IoPoint::Modbus { sid, addr } = *point else { continue };
+        let result = match master.read_bit(sid, FC_READ_COILS, addr).await {
+            Ok(level) if level == img.desired => Ok(level),
+            Ok(_) => master.write_coil(sid, addr, img.desired).await.map(|_| img.desired),
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(level) => { img.actual = Some(level); img.fails = 0; }
+            Err(_) => { img.actual = None; img.fails = img.fails.saturating_add(1); }
+        }
+    }
+}
+
+/// Baca tiap discrete input remote (FC02). Kanal `Gpio` dilewati. Gangguan
+/// singkat menahan level terakhir; modul yang tidak menjawab `INPUT_STALE_FAILS`
+/// kali berturut-turut membuat level tidak diketahui (pemakai jatuh ke level idle).
+pub async fn poll_inputs<P: ModbusPort, C: Clock>(master: &mut Master<P, C>, points: &[IoPoint], image: &mut [InputImage]) {
+    for (point, img) in points.iter().zip(image.iter_mut()) {
+        let IoPoint::Modbus { sid, addr } = *point else { continue };
+        match master.read_bit(sid, FC_READ_DISCRETE, addr).await {
+            Ok(level) => { img.level = Some(level); img.fails = 0; }
+            Err(_) => {
+                img.fails = img.fails.saturating_add(1);
+                if img.fails >= INPUT_STALE_FAILS { img.level = None; }
+            }
+        }
+    }
+}
@@ -0,0 +1,260 @@
+//! Snapshot state untuk warm restart (mis. setelah reset watchdog): output
+//! terakhir, fase unit, state controller, dan generator setpoint (termasuk
+//! profil + posisi segmen). Firmware menyimpannya di RTC RAM yang bertahan
+//! melewati reset non-power-on; setelah power-on semuanya mulai dari default.
+//!
+//! Layout (little-endian): `"KRTN" | versi u8 | len u16 | payload[len] | crc16`.
+//! Blob dianggap valid hanya kalau magic, versi, panjang, CRC, dan isi
+//! (rentang nilai, posisi profil) semuanya cocok.
+//!
+//! Reset berulang tak lama setelah boot (boot loop) tidak dilanjutkan: state
+//! yang dipulihkan bisa jadi penyebabnya, jadi setelah `MAX_QUICK_RESTARTS`
+//! firmware kembali cold start.
+
+use crate::codec::{Reader, Writer};
+use crate::control::ActuatorCmd;
+use crate::lifecycle::Phase;
+use crate::modbus::crc16;
+use crate::setpoint::{End, ProfileState, Segment, SetpointGen, MAX_SEGMENTS};
+
+const MAGIC:      [u8; 4] = *b"KRTN";
+const VERSION:    u8 = 2;
+const HEADER_LEN: usize = 7;
+/// Ukuran blob tetap (cukup untuk profil penuh).
+pub const RETAIN_LEN: usize = 256;
+/// Reset sebelum jalan selama ini dihitung sebagai restart cepat.
+pub const QUICK_RESTART_MS: u64 = 60_000;
+/// Restart cepat berturut-turut yang masih dilanjutkan warm.
+pub const MAX_QUICK_RESTARTS: u32 = 3;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Snapshot {
+    pub phase:      Phase,
+    /// Output terakhir yang di-drive.
+    pub cmd:        ActuatorCmd,
+    /// State internal controller (`Controller::cmd`).
+    pub controller: ActuatorCmd,
+    pub setpoint:   SetpointGen,
+    /// Uptime saat snapshot (info: lama jalan sebelum reset).
+    pub uptime_ms:  u64,
+    /// Jumlah warm restart berturut-turut sejak power-on.
+    pub restarts:   u32,
+    /// Berapa dari warm restart itu yang terjadi < `QUICK_RESTART_MS` setelah boot, berturut-turut.
+    pub quick:      u32,
+}
+
+fn phase_code(p: Phase) -> u8 {
+    match p {
+        Phase::Init     => 0,
+        Phase::SelfTest => 1,
+        Phase::Standby  => 2,
+        Phase::Run      => 3,
+        Phase::Shutdown => 4,
+        Phase::Fault    => 5,
+    }
+}
+
+fn phase_from(code: u8) -> Option<Phase> {
+    Some(match code {
+        0 => Phase::Init,
+        1 => Phase::SelfTest,
+        2 => Phase::Standby,
+        3 => Phase::Run,
+        4 => Phase::Shutdown,
+        5 => Phase::Fault,
+        _ => return None,
+    })
+}
+
+fn put_cmd(w: &mut Writer, c: &ActuatorCmd) {
+    w.put(&(c.servo_deg as i16).to_le_bytes());
+    w.put(&[c.fan_on as u8]);
+}
+
+fn take_cmd(r: &mut Reader) -> Option<ActuatorCmd> {
+    let servo_deg = i16::from_le_bytes(r.take()?) as i32;
+    let fan_on = match r.take::<1>()?[0] { 0 => false, 1 => true, _ => return None };
+    (0..=180).contains(&servo_deg).then_some(ActuatorCmd { servo_deg, fan_on })
+}
+
+impl Snapshot {
+    /// Fase yang dilanjutkan langsung setelah warm restart (lihat `Lifecycle::warm_start`);
+    /// fase lain boot normal lewat SELF-TEST dengan output aman.
+    pub fn resumes(&self) -> bool {
+        matches!(self.phase, Phase::Standby | Phase::Run)
+    }
+
+    /// Nilai `quick` untuk boot yang memakai snapshot ini.
+    pub fn next_quick(&self) -> u32 {
+        if self.uptime_ms < QUICK_RESTART_MS { self.quick.saturating_add(1) } else { 0 }
+    }
+
+    /// Terlalu banyak restart cepat berturut-turut: boot ini harus cold.
+    pub fn boot_loop(&self) -> bool {
+        self.next_quick() > MAX_QUICK_RESTARTS
+    }
+
+    /// Blob lengkap; sisa buffer diisi 0.
+    pub fn encode(&self, out: &mut [u8; RETAIN_LEN]) {
+        out.fill(0);
+        let len = {
+            let mut w = Writer::new(&mut out[HEADER_LEN..RETAIN_LEN - 2]);
+            w.put(&[phase_code(self.phase)]);
+            put_cmd(&mut w, &self.cmd);
+            put_cmd(&mut w, &self.controller);
+            w.put(&self.uptime_ms.to_le_bytes());
+            w.put(&self.restarts.to_le_bytes());
+            w.put(&self.quick.to_le_bytes());
+
+            let g = &self.setpoint;
+            for v in [g.target(), g.rate, g.value()] { w.f32(v); }
+            let (tag, segment, elapsed_ms, from, cycle) = match g.state() {
+                ProfileState::Idle => (0u8, 0, 0, 0.0, 0),
+                ProfileState::Running { segment, elapsed_ms, from, cycle } => (1, segment, elapsed_ms, from, cycle),
+                ProfileState::Paused { segment, elapsed_ms, from, cycle } => (2, segment, elapsed_ms, from, cycle),
+                ProfileState::Done => (3, 0, 0, 0.0, 0),
+            };
+            w.put(&[tag, segment as u8]);
+            w.put(&elapsed_ms.to_le_bytes());
+            w.f32(from);
+            w.put(&cycle.to_le_bytes());
+
+            let p = &g.profile;
+            w.put(&[(p.end == End::Loop) as u8, p.segments().len() as u8]);
+            for s in p.segments() {
+                w.put(&s.duration_ms.to_le_bytes());
+                w.f32(s.target);
+            }
+            w.pos()
+        };
+        out[0..4].copy_from_slice(&MAGIC);
+        out[4] = VERSION;
+        out[5..7].copy_from_slice(&(len as u16).to_le_bytes());
+        let end = HEADER_LEN + len;
+        let crc = crc16(&out[..end]);
+        out[end..end + 2].copy_from_slice(&crc.to_le_bytes());
+    }
+
+    /// None kalau blob tidak valid (power-on, korup, atau versi lain).
+    pub fn decode(buf: &[u8; RETAIN_LEN]) -> Option<Self> {
+        if buf[0..4] != MAGIC || buf[4] != VERSION { return None; }
+        let len = u16::from_le_bytes([buf[5], buf[6]]) as usize;
+        let end = HEADER_LEN + len;
+        if end + 2 > RETAIN_LEN { return None; }
+        if crc16(&buf[..end]) != u16::from_le_bytes([buf[end], buf[end + 1]]) { return None; }
+
+        let mut r = Reader::new(&buf[HEADER_LEN..end]);
+        let phase = phase_from(r.take::<1>()?[0])?;
+        let cmd = take_cmd(&mut r)?;
+        let controller = take_cmd(&mut r)?;
+        let uptime_ms = u64::from_le_bytes(r.take()?);
+        let restarts = u32::from_le_bytes(r.take()?);
+        let quick = u32::from_le_bytes(r.take()?);
+
+        let (target, rate, value) = (r.f32()?, r.f32()?, r.f32()?);
+        let [tag, segment] = r.take::<2>()?;
+        let (segment, elapsed_ms, from, cycle) = (segment as usize, u64::from_le_bytes(r.take()?), r.f32()?, u32::from_le_bytes(r.take()?));
+        let state = match tag {
+            0 => ProfileState::Idle,
+            1 => ProfileState::Running { segment, elapsed_ms, from, cycle },
+            2 => ProfileState::Paused { segment, elapsed_ms, from, cycle },
+            3 => ProfileState::Done,
+            _ => return None,
+        };
+
+        let mut setpoint = SetpointGen::default();
+        let [end_loop, n] = r.take::<2>()?;
+        if n as usize > MAX_SEGMENTS { return None; }
+        setpoint.profile.end = if end_loop != 0 { End::Loop } else { End::Hold };
+        for _ in 0..n {
+            let duration_ms = u64::from_le_bytes(r.take()?);
+            setpoint.profile.push(Segment { duration_ms, target: r.f32()? });
+        }
+        setpoint.rate = rate;
+        if !setpoint.restore(target, value, state) { return None; }
+
+        Some(Self { phase, cmd, controller, setpoint, uptime_ms, restarts, quick })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot() -> Snapshot {
+        let mut g = SetpointGen::new(60.0);
+        g.rate = 3.0;
+        g.profile.push(Segment { duration_ms: 60_000, target: 70.0 });
+        g.profile.push(Segment { duration_ms: 0, target: 50.0 });
+        g.profile.end = End::Loop;
+        g.update(0);
+        g.start().unwrap();
+        g.update(30_000);
+        Snapshot {
+            phase: Phase::Run,
+            cmd: ActuatorCmd { servo_deg: 120, fan_on: true },
+            controller: ActuatorCmd { servo_deg: 120, fan_on: true },
+            setpoint: g,
+            uptime_ms: 3_600_000,
+            restarts: 2,
+            quick: 0,
+        }
+    }
+
+    #[test]
+    fn roundtrip_keeps_outputs_and_profile_position() {
+        let mut b = [0u8; RETAIN_LEN];
+        snapshot().encode(&mut b);
+        let s = Snapshot::decode(&b).unwrap();
+        assert_eq!((s.phase, s.cmd, s.uptime_ms, s.restarts), (Phase::Run, ActuatorCmd { servo_deg: 120, fan_on: true }, 3_600_000, 2));
+        assert_eq!(s.setpoint.profile, snapshot().setpoint.profile);
+        assert_eq!(s.setpoint.state(), ProfileState::Running { segment: 0, elapsed_ms: 30_000, from: 60.0, cycle: 0 });
+
+        // Lanjut dari posisi segmen yang sama
+        let mut g = s.setpoint;
+        g.update(100);
+        assert!((g.update(15_100) - 67.5).abs() < 1e-3);
+    }
+
+    #[test]
+    fn quick_restarts_are_bounded() {
+        let s = snapshot();
+        assert_eq!((s.next_quick(), s.boot_loop()), (0, false));
+
+        // Reset 5 s setelah boot, berulang
+        let mut s = Snapshot { uptime_ms: 5_000, ..s };
+        for n in 1..=MAX_QUICK_RESTARTS {
+            assert!(!s.boot_loop());
+            assert_eq!(s.next_quick(), n);
+            s.quick = n;
+        }
+        assert!(s.boot_loop());
+
+        // Jalan cukup lama sebelum reset: hitungan mulai lagi
+        assert!(!Snapshot { uptime_ms: QUICK_RESTART_MS, ..s }.boot_loop());
+
+        let mut b = [0u8; RETAIN_LEN];
+        s.encode(&mut b);
+        assert_eq!(Snapshot::decode(&b).unwrap().quick, MAX_QUICK_RESTARTS);
+    }
+
+    #[test]
+    fn power_on_garbage_and_corruption_are_rejected() {
+        // RTC RAM persistent diisi 0 saat power-on
+        assert!(Snapshot::decode(&[0; RETAIN_LEN]).is_none());
+        assert!(Snapshot::decode(&[0xA5; RETAIN_LEN]).is_none());
+
+        let mut b = [0u8; RETAIN_LEN];
+        snapshot().encode(&mut b);
+        b[HEADER_LEN + 1] ^= 0x40;
+        assert!(Snapshot::decode(&b).is_none());
+
+        // CRC benar tapi isi tidak konsisten (segmen di luar profil)
+        let mut s = snapshot();
+        s.setpoint.profile.clear();
+        s.setpoint.profile.push(Segment { duration_ms: 10_000, target: 70.0 });
+        let mut b = [0u8; RETAIN_LEN];
+        s.encode(&mut b);
+        assert!(Snapshot::decode(&b).is_none());
+    }
+}
@@ -0,0 +1,65 @@
+//! Kontrol arah transceiver RS485 manual (MAX485 & sejenisnya: DE dan /RE
+//! biasanya dijumper jadi satu pin).
+
+/// Konfigurasi pin DE/RE.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DirectionConfig {
+    /// true = pin HIGH saat transmit (MAX485 standar). false untuk modul
+    /// yang jalurnya dibalik (mis. lewat transistor/optocoupler).
+    pub active_high: bool,
+    /// Tahan driver setelah UART lapor TX selesai (stop bit terakhir keluar),
+    /// dalam µs. Dibatasi `guard_us_for()` supaya tidak menabrak reply slave.
+    pub guard_us: u32,
+}
+
+impl Default for DirectionConfig {
+    fn default() -> Self {
+        Self { active_high: true, guard_us: 100 }
+    }
+}
+
+impl DirectionConfig {
+    /// Level pin (true = HIGH) untuk mode transmit/receive.
+    pub const fn level(&self, transmit: bool) -> bool {
+        transmit == self.active_high
+    }
+
+    /// Guard time efektif: maksimal setengah t3.5, karena slave boleh mulai
+    /// menjawab setelah bus diam 3.5 karakter.
+    pub fn guard_us_for(&self, baud: u32) -> u32 {
+        self.guard_us.min(t3_5_us(baud) / 2)
+    }
+}
+
+/// Waktu 1 karakter RTU (11 bit: start + 8 data + parity/stop + stop) dalam µs.
+pub const fn char_time_us(baud: u32) -> u32 {
+    11_000_000u32.div_ceil(baud)
+}
+
+/// Silent interval antar frame (3.5 karakter; tetap 1750 µs di atas 19200 bps).
+pub const fn t3_5_us(baud: u32) -> u32 {
+    if baud > 19_200 { 1_750 } else { (7 * char_time_us(baud)).div_ceil(2) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t3_5_formula_up_to_19200_then_fixed() {
+        assert_eq!(char_time_us(9_600), 1_146);
+        assert_eq!(t3_5_us(9_600), 4_011);
+        assert_eq!(t3_5_us(19_200), 2_006);
+        assert_eq!(t3_5_us(38_400), 1_750);
+        assert_eq!(t3_5_us(115_200), 1_750);
+    }
+
+    #[test]
+    fn guard_capped_at_half_t3_5() {
+        let cfg = DirectionConfig { active_high: true, guard_us: 1_500 };
+        assert_eq!(cfg.guard_us_for(9_600), 1_500);
+        assert_eq!(cfg.guard_us_for(19_200), 1_003);
+        assert_eq!(cfg.guard_us_for(115_200), 875);
+        assert_eq!(DirectionConfig::default().guard_us_for(115_200), 100);
+    }
+}
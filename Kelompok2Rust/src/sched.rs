@@ -0,0 +1,176 @@
+//! Scheduler kooperatif fixed-rate: tiap job terdaftar dengan periode dan
+//! offset fase, dirilis pada grid `start + offset + k * periode` (tanpa drift
+//! walau job sebelumnya telat). Rilis yang terlewat ≥ satu periode dihitung
+//! overrun dan dilompati, bukan dikejar beruntun.
+//!
+//! Tidak tahu soal waktu sungguhan: pemanggil memberi `now_ms` (Instant di
+//! firmware, jam palsu di test/sim) dan menunggu sampai `next_release()`.
+
+use core::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JobId(usize);
+
+impl JobId {
+    pub fn index(&self) -> usize {
+        self.0
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchedError {
+    /// Tabel job penuh.
+    Full,
+    /// Periode 0 atau offset ≥ periode.
+    Timing,
+}
+
+/// Counter per job.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct JobStats {
+    pub runs:        u32,
+    /// Rilis yang telat ≥ satu periode (job sebelumnya / loop terlalu lama).
+    pub overruns:    u32,
+    /// Jumlah rilis yang dilompati karena overrun.
+    pub skipped:     u32,
+    /// Keterlambatan rilis terbesar (ms).
+    pub max_late_ms: u64,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Job {
+    name:      &'static str,
+    period_ms: u64,
+    offset_ms: u64,
+    next_ms:   u64,
+    stats:     JobStats,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Scheduler<const N: usize> {
+    start_ms: u64,
+    jobs:     [Option<Job>; N],
+}
+
+impl<const N: usize> Scheduler<N> {
+    /// `start_ms` = titik nol grid fase.
+    pub const fn new(start_ms: u64) -> Self {
+        Self { start_ms, jobs: [None; N] }
+    }
+
+    /// Urutan daftar = prioritas kalau beberapa job jatuh tempo bersamaan.
+    pub fn register(&mut self, name: &'static str, period_ms: u64, offset_ms: u64) -> Result<JobId, SchedError> {
+        if period_ms == 0 || offset_ms >= period_ms { return Err(SchedError::Timing); }
+        let idx = self.jobs.iter().position(|j| j.is_none()).ok_or(SchedError::Full)?;
+        let next_ms = self.start_ms + offset_ms;
+        self.jobs[idx] = Some(Job { name, period_ms, offset_ms, next_ms, stats: JobStats::default() });
+        Ok(JobId(idx))
+    }
+
+    /// Job berikutnya yang sudah jatuh tempo (rilis paling awal dulu, lalu
+    /// urutan daftar). Panggil berulang sampai None; tiap job dirilis sekali
+    /// per periode.
+    pub fn poll(&mut self, now_ms: u64) -> Option<JobId> {
+        let (idx, job) = self.jobs.iter_mut().enumerate()
+            .filter_map(|(i, j)| j.as_mut().map(|j| (i, j)))
+            .filter(|(_, j)| j.next_ms <= now_ms)
+            .min_by_key(|(i, j)| (j.next_ms, *i))?;
+
+        let late = now_ms - job.next_ms;
+        let missed = late / job.period_ms;
+        job.stats.runs = job.stats.runs.saturating_add(1);
+        job.stats.max_late_ms = job.stats.max_late_ms.max(late);
+        if missed > 0 {
+            job.stats.overruns = job.stats.overruns.saturating_add(1);
+            job.stats.skipped = job.stats.skipped.saturating_add(missed.min(u32::MAX as u64) as u32);
+        }
+        job.next_ms += (missed + 1) * job.period_ms;
+        Some(JobId(idx))
+    }
+
+    /// Waktu rilis terdekat (untuk `Timer::at` / maju jam simulasi).
+    pub fn next_release(&self) -> Option<u64> {
+        self.jobs.iter().flatten().map(|j| j.next_ms).min()
+    }
+
+    pub fn name(&self, id: JobId) -> &'static str {
+        self.jobs[id.0].map_or("-", |j| j.name)
+    }
+
+    pub fn stats(&self, id: JobId) -> JobStats {
+        self.jobs[id.0].map(|j| j.stats).unwrap_or_default()
+    }
+
+    pub fn reset_stats(&mut self) {
+        for j in self.jobs.iter_mut().flatten() { j.stats = JobStats::default(); }
+    }
+
+    /// (id, nama, periode, offset, counter) tiap job terdaftar.
+    pub fn iter(&self) -> impl Iterator<Item = (JobId, &'static str, u64, u64, JobStats)> + '_ {
+        self.jobs.iter().enumerate()
+            .filter_map(|(i, j)| j.map(|j| (JobId(i), j.name, j.period_ms, j.offset_ms, j.stats)))
+    }
+}
+
+impl fmt::Display for SchedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SchedError::Full   => "tabel job penuh",
+            SchedError::Timing => "periode harus > 0 dan offset < periode",
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Jalankan jam palsu per 1 ms, catat (waktu, job) tiap rilis.
+    fn run(s: &mut Scheduler<4>, from: u64, to: u64) -> Vec<(u64, usize)> {
+        let mut out = Vec::new();
+        for now in from..=to {
+            while let Some(id) = s.poll(now) { out.push((now, id.index())); }
+        }
+        out
+    }
+
+    #[test]
+    fn releases_follow_period_and_phase_offset() {
+        let mut s = Scheduler::<4>::new(1_000);
+        let fast = s.register("fast", 100, 0).unwrap();
+        let slow = s.register("slow", 250, 50).unwrap();
+        assert_eq!(s.register("bad", 100, 100), Err(SchedError::Timing));
+        assert_eq!(s.next_release(), Some(1_000));
+
+        let log = run(&mut s, 0, 1_500);
+        let at = |id: JobId| log.iter().filter(|(_, i)| *i == id.index()).map(|(t, _)| *t).collect::<Vec<_>>();
+        assert_eq!(at(fast), vec![1_000, 1_100, 1_200, 1_300, 1_400, 1_500]);
+        assert_eq!(at(slow), vec![1_050, 1_300]);
+        // Rilis bersamaan di 1300: urutan daftar
+        let i = log.iter().position(|e| *e == (1_300, fast.index())).unwrap();
+        assert_eq!(log[i + 1], (1_300, slow.index()));
+        assert_eq!(s.stats(fast).overruns, 0);
+        assert_eq!(s.next_release(), Some(1_550));
+    }
+
+    #[test]
+    fn late_poll_counts_overrun_and_keeps_grid() {
+        let mut s = Scheduler::<4>::new(0);
+        let a = s.register("a", 100, 10).unwrap();
+        assert_eq!(s.poll(10), Some(a));
+        // Loop macet 350 ms: rilis 110, 210, 310 terlewat → satu kali jalan, lalu kembali ke grid
+        assert_eq!(s.poll(360), Some(a));
+        assert_eq!(s.poll(360), None);
+        assert_eq!(s.next_release(), Some(410));
+        // Telat kurang dari satu periode bukan overrun
+        assert_eq!(s.poll(480), Some(a));
+        let st = s.stats(a);
+        assert_eq!((st.runs, st.overruns, st.skipped, st.max_late_ms), (3, 1, 2, 250));
+
+        s.reset_stats();
+        assert_eq!(s.stats(a), JobStats::default());
+        let mut full = Scheduler::<1>::new(0);
+        full.register("x", 10, 0).unwrap();
+        assert_eq!(full.register("y", 10, 0), Err(SchedError::Full));
+    }
+}
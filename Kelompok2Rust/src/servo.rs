@@ -0,0 +1,204 @@
+//! Konversi sudut servo → lebar pulsa, dengan kalibrasi runtime.
+//!
+//! `ServoCal` menyimpan titik ujung mekanis damper (µs), sudut tempuh, arah
+//! (inversi), batas tempuh lunak, dan posisi park. Sudut perintah selalu
+//! 0..range (0 = damper tertutup); batas lunak dan inversi diterapkan di sini.
+
+use core::fmt;
+
+pub const SERVO_PERIOD_US: u32 = 20_000;   // 50 Hz = 20 ms
+pub const SERVO_MIN_US:    u32 = 500;      // ~0° (bawaan)
+pub const SERVO_MAX_US:    u32 = 2500;     // ~120° (bawaan)
+/// Batas keras lebar pulsa; di luar ini servo hobby umumnya menabrak stop internal.
+pub const HARD_MIN_US:     u32 = 400;
+pub const HARD_MAX_US:     u32 = 2600;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ServoCal {
+    /// Pulsa di ujung 0° (atau ujung `range_deg` kalau `inverted`).
+    pub min_us:    u32,
+    pub max_us:    u32,
+    /// Sudut tempuh antara dua ujung.
+    pub range_deg: i32,
+    /// true = 0° ada di `max_us` (servo dipasang terbalik).
+    pub inverted:  bool,
+    /// Batas tempuh lunak (sudut perintah di-clamp ke sini).
+    pub limit_lo:  i32,
+    pub limit_hi:  i32,
+    /// Posisi di fase aman (SELF-TEST/STANDBY/FAULT) dan saat boot.
+    pub park_deg:  i32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServoCalError {
+    /// Ujung di luar `HARD_MIN_US..=HARD_MAX_US` atau min ≥ max.
+    Endpoints,
+    /// Sudut tempuh harus 1..=180°.
+    Range,
+    /// Batas lunak / park di luar 0..range atau lo > hi.
+    Limits,
+}
+
+impl Default for ServoCal {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl ServoCal {
+    /// Perilaku lama: 0..120° linear di 500..2500 µs, park 0°.
+    pub const DEFAULT: Self = Self {
+        min_us: SERVO_MIN_US, max_us: SERVO_MAX_US, range_deg: 120, inverted: false,
+        limit_lo: 0, limit_hi: 120, park_deg: 0,
+    };
+
+    pub fn validate(&self) -> Result<(), ServoCalError> {
+        if self.min_us < HARD_MIN_US || self.max_us > HARD_MAX_US || self.min_us >= self.max_us {
+            return Err(ServoCalError::Endpoints);
+        }
+        if !(1..=180).contains(&self.range_deg) { return Err(ServoCalError::Range); }
+        let in_range = |d: i32| (0..=self.range_deg).contains(&d);
+        if !in_range(self.limit_lo) || !in_range(self.limit_hi) || self.limit_lo > self.limit_hi
+            || !(self.limit_lo..=self.limit_hi).contains(&self.park_deg) {
+            return Err(ServoCalError::Limits);
+        }
+        Ok(())
+    }
+
+    /// Sudut perintah setelah batas lunak.
+    pub fn clamp(&self, deg: i32) -> i32 {
+        deg.clamp(self.limit_lo, self.limit_hi)
+    }
+
+    pub fn pulse_us(&self, deg: i32) -> u32 {
+        let d = self.clamp(deg);
+        let d = if self.inverted { self.range_deg - d } else { d } as u32;
+        self.min_us + d * (self.max_us - self.min_us) / self.range_deg as u32
+    }
+}
+
+/// Sudut → pulsa dengan kalibrasi bawaan.
+pub fn deg_to_pulse_us(deg: i32) -> u32 {
+    ServoCal::DEFAULT.pulse_us(deg)
+}
+
+/// Langkah rutin kalibrasi ujung.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CalStep {
+    /// Geser sampai damper tepat tertutup, lalu `mark`.
+    Closed,
+    /// Geser sampai damper terbuka penuh, lalu `mark`.
+    Open { closed_us: u32 },
+    Done,
+}
+
+/// Rutin kalibrasi ujung mekanis: operator menggeser pulsa mentah (`jog`)
+/// sampai damper tepat di ujung lalu `mark`, pertama tertutup, lalu terbuka.
+/// Arah (inversi) diambil dari urutan kedua titik.
+#[derive(Debug, Clone, Copy)]
+pub struct CalRoutine {
+    base:     ServoCal,
+    pulse_us: u32,
+    step:     CalStep,
+}
+
+impl CalRoutine {
+    /// Mulai dari posisi 0° kalibrasi sekarang.
+    pub fn new(base: ServoCal) -> Self {
+        Self { base, pulse_us: base.pulse_us(0), step: CalStep::Closed }
+    }
+
+    pub fn step(&self) -> CalStep {
+        self.step
+    }
+
+    /// Pulsa mentah yang harus di-drive selama kalibrasi.
+    pub fn pulse_us(&self) -> u32 {
+        self.pulse_us
+    }
+
+    pub fn jog(&mut self, delta_us: i32) -> u32 {
+        self.pulse_us = (self.pulse_us as i32).saturating_add(delta_us).clamp(HARD_MIN_US as i32, HARD_MAX_US as i32) as u32;
+        self.pulse_us
+    }
+
+    /// Catat ujung sekarang. Setelah titik kedua: kalibrasi baru (tempuh,
+    /// batas lunak & park diambil dari kalibrasi lama).
+    pub fn mark(&mut self) -> Option<Result<ServoCal, ServoCalError>> {
+        match self.step {
+            CalStep::Closed => {
+                self.step = CalStep::Open { closed_us: self.pulse_us };
+                None
+            }
+            CalStep::Open { closed_us } => {
+                self.step = CalStep::Done;
+                let open_us = self.pulse_us;
+                let cal = ServoCal {
+                    min_us: closed_us.min(open_us),
+                    max_us: closed_us.max(open_us),
+                    inverted: closed_us > open_us,
+                    ..self.base
+                };
+                Some(cal.validate().map(|_| cal))
+            }
+            CalStep::Done => None,
+        }
+    }
+}
+
+impl fmt::Display for ServoCalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ServoCalError::Endpoints => "ujung pulsa tidak valid (400..2600 µs, min < max)",
+            ServoCalError::Range     => "sudut tempuh harus 1..180°",
+            ServoCalError::Limits    => "batas/park di luar 0..tempuh",
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_matches_old_mapping() {
+        assert_eq!(deg_to_pulse_us(0), 500);
+        assert_eq!(deg_to_pulse_us(60), 1500);
+        assert_eq!(deg_to_pulse_us(120), 2500);
+        assert_eq!(deg_to_pulse_us(200), 2500);
+        assert_eq!(ServoCal::DEFAULT.validate(), Ok(()));
+    }
+
+    #[test]
+    fn inversion_and_soft_limits() {
+        let cal = ServoCal { min_us: 1000, max_us: 2000, range_deg: 90, inverted: true, limit_lo: 10, limit_hi: 80, park_deg: 10 };
+        assert_eq!(cal.validate(), Ok(()));
+        assert_eq!(cal.pulse_us(0), cal.pulse_us(10));
+        assert_eq!(cal.pulse_us(90), 1000 + 10 * 1000 / 90);
+        assert!(cal.pulse_us(10) > cal.pulse_us(80));
+
+        assert_eq!(ServoCal { park_deg: 5, ..cal }.validate(), Err(ServoCalError::Limits));
+        assert_eq!(ServoCal { min_us: 300, ..cal }.validate(), Err(ServoCalError::Endpoints));
+        assert_eq!(ServoCal { range_deg: 0, ..cal }.validate(), Err(ServoCalError::Range));
+    }
+
+    #[test]
+    fn routine_finds_endpoints_and_direction() {
+        let mut r = CalRoutine::new(ServoCal::DEFAULT);
+        assert_eq!(r.pulse_us(), 500);
+        r.jog(-200); // mentok di batas keras
+        assert_eq!(r.pulse_us(), HARD_MIN_US);
+        assert_eq!(r.jog(i32::MIN), HARD_MIN_US);
+        assert_eq!(r.jog(i32::MAX), HARD_MAX_US);
+        r.jog(i32::MIN);
+        r.jog(1800);
+        assert_eq!(r.mark(), None);
+        assert_eq!(r.step(), CalStep::Open { closed_us: 2200 });
+        r.jog(-1500);
+        let cal = r.mark().unwrap().unwrap();
+        assert_eq!((cal.min_us, cal.max_us, cal.inverted), (700, 2200, true));
+        assert_eq!(cal.pulse_us(0), 2200);
+        assert_eq!(r.step(), CalStep::Done);
+        assert_eq!(r.mark(), None);
+    }
+}
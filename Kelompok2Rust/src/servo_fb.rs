@@ -0,0 +1,249 @@
+//! Feedback posisi servo: potensiometer internal servo dibaca lewat ADC,
+//! dipetakan ke sudut dengan kalibrasi dua titik, lalu dibandingkan dengan
+//! sudut perintah. Deviasi yang bertahan setelah waktu tempuh habis = servo
+//! macet / damper terhalang.
+
+use core::fmt;
+
+/// Selisih ADC minimal antara dua titik kalibrasi (ADC 12-bit).
+pub const MIN_RAW_SPAN: u16 = 200;
+/// Selisih sudut minimal antara dua titik kalibrasi.
+pub const MIN_DEG_SPAN: i32 = 30;
+/// Bobot sample baru di filter EMA (50 Hz → konstanta waktu ±100 ms).
+const FILTER_ALPHA: f32 = 0.2;
+
+/// Kalibrasi dua titik: nilai ADC di dua sudut perintah (frame sudut `ServoCal`).
+/// ADC boleh naik atau turun terhadap sudut (potensiometer terbalik).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FbCal {
+    pub deg_a: i32,
+    pub raw_a: u16,
+    pub deg_b: i32,
+    pub raw_b: u16,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FbCalError {
+    /// Dua titik terlalu dekat (sudut < `MIN_DEG_SPAN` atau ADC < `MIN_RAW_SPAN`).
+    Span,
+}
+
+impl FbCal {
+    pub fn new(a: (i32, u16), b: (i32, u16)) -> Result<Self, FbCalError> {
+        let cal = Self { deg_a: a.0, raw_a: a.1, deg_b: b.0, raw_b: b.1 };
+        cal.validate().map(|_| cal)
+    }
+
+    pub fn validate(&self) -> Result<(), FbCalError> {
+        if (self.deg_b - self.deg_a).abs() < MIN_DEG_SPAN || self.raw_a.abs_diff(self.raw_b) < MIN_RAW_SPAN {
+            return Err(FbCalError::Span);
+        }
+        Ok(())
+    }
+
+    /// ADC → sudut (linear, boleh ekstrapolasi sedikit di luar kedua titik).
+    pub fn deg(&self, raw: f32) -> f32 {
+        let (ra, rb) = (self.raw_a as f32, self.raw_b as f32);
+        self.deg_a as f32 + (raw - ra) * (self.deg_b - self.deg_a) as f32 / (rb - ra)
+    }
+}
+
+/// Ambang deteksi stall.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StallConfig {
+    /// Deviasi |aktual − perintah| yang masih dianggap sampai.
+    pub tolerance_deg: f32,
+    /// Waktu tempuh setelah perintah berubah; selama ini deviasi diabaikan.
+    pub travel_ms:     u32,
+    /// Deviasi harus bertahan selama ini (setelah waktu tempuh) sebelum stall.
+    pub persist_ms:    u32,
+}
+
+impl StallConfig {
+    pub const DEFAULT: Self = Self { tolerance_deg: 10.0, travel_ms: 2000, persist_ms: 1000 };
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StallEvent {
+    /// Posisi tidak mengikuti perintah.
+    Stalled { cmd_deg: i32, actual_deg: f32 },
+    /// Posisi kembali dalam toleransi.
+    Cleared,
+}
+
+/// Filter ADC + pembanding posisi aktual vs perintah.
+#[derive(Debug, Clone, Copy)]
+pub struct PositionMonitor {
+    cfg:        StallConfig,
+    cal:        Option<FbCal>,
+    raw:        Option<f32>,
+    cmd_deg:    Option<i32>,
+    moved_ms:   u64,
+    over_since: Option<u64>,
+    stalled:    bool,
+}
+
+impl PositionMonitor {
+    pub const fn new(cfg: StallConfig) -> Self {
+        Self { cfg, cal: None, raw: None, cmd_deg: None, moved_ms: 0, over_since: None, stalled: false }
+    }
+
+    /// Kalibrasi baru (None = belum dikalibrasi: ADC tetap difilter, stall tidak dievaluasi).
+    pub fn set_cal(&mut self, cal: Option<FbCal>) {
+        if cal == self.cal { return; }
+        self.cal = cal;
+        self.over_since = None;
+        self.stalled = false;
+    }
+
+    pub fn cal(&self) -> Option<FbCal> {
+        self.cal
+    }
+
+    /// Satu sample ADC. `cmd_deg` = sudut perintah setelah clamp `ServoCal`;
+    /// None = servo di-drive pulsa mentah (kalibrasi), deviasi tidak dievaluasi.
+    pub fn update(&mut self, raw: u16, cmd_deg: Option<i32>, now_ms: u64) -> Option<StallEvent> {
+        let filtered = match self.raw {
+            Some(f) => f + FILTER_ALPHA * (raw as f32 - f),
+            None => raw as f32,
+        };
+        self.raw = Some(filtered);
+        if cmd_deg != self.cmd_deg {
+            self.cmd_deg = cmd_deg;
+            self.moved_ms = now_ms;
+            self.over_since = None;
+        }
+
+        let (Some(cmd), Some(dev)) = (cmd_deg, self.deviation()) else { return None };
+        if now_ms.saturating_sub(self.moved_ms) < self.cfg.travel_ms as u64 { return None; }
+        if dev.abs() <= self.cfg.tolerance_deg {
+            self.over_since = None;
+            return core::mem::take(&mut self.stalled).then_some(StallEvent::Cleared);
+        }
+        let since = *self.over_since.get_or_insert(now_ms);
+        if self.stalled || now_ms - since < self.cfg.persist_ms as u64 { return None; }
+        self.stalled = true;
+        Some(StallEvent::Stalled { cmd_deg: cmd, actual_deg: cmd as f32 + dev })
+    }
+
+    /// ADC terfilter.
+    pub fn raw(&self) -> Option<f32> {
+        self.raw
+    }
+
+    /// Sudut perintah terakhir (None = pulsa mentah).
+    pub fn cmd_deg(&self) -> Option<i32> {
+        self.cmd_deg
+    }
+
+    /// Sudut aktual (butuh kalibrasi).
+    pub fn actual_deg(&self) -> Option<f32> {
+        Some(self.cal?.deg(self.raw?))
+    }
+
+    /// Aktual − perintah.
+    pub fn deviation(&self) -> Option<f32> {
+        Some(self.actual_deg()? - self.cmd_deg? as f32)
+    }
+
+    pub fn stalled(&self) -> bool {
+        self.stalled
+    }
+}
+
+/// Rutin kalibrasi feedback: servo di-drive ke sudut pertama, operator menunggu
+/// servo diam lalu `mark`; servo pindah ke sudut kedua, `mark` lagi.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FbRoutine {
+    points: [i32; 2],
+    first:  Option<u16>,
+}
+
+impl FbRoutine {
+    pub fn new(deg_a: i32, deg_b: i32) -> Result<Self, FbCalError> {
+        if (deg_b - deg_a).abs() < MIN_DEG_SPAN { return Err(FbCalError::Span); }
+        Ok(Self { points: [deg_a, deg_b], first: None })
+    }
+
+    /// Titik ke berapa (1/2) yang sedang ditunggu.
+    pub fn step(&self) -> usize {
+        if self.first.is_some() { 2 } else { 1 }
+    }
+
+    /// Sudut yang harus di-drive sekarang.
+    pub fn target_deg(&self) -> i32 {
+        self.points[self.step() - 1]
+    }
+
+    /// Catat ADC di titik sekarang; setelah titik kedua: hasil kalibrasi.
+    pub fn mark(&mut self, raw: u16) -> Option<Result<FbCal, FbCalError>> {
+        match self.first {
+            None => { self.first = Some(raw); None }
+            Some(first) => Some(FbCal::new((self.points[0], first), (self.points[1], raw))),
+        }
+    }
+}
+
+impl fmt::Display for FbCalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            FbCalError::Span => "titik terlalu dekat (sudut ≥ 30°, ADC ≥ 200) atau potensiometer tidak terbaca",
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn routine_builds_two_point_cal_including_reversed_pot() {
+        let mut r = FbRoutine::new(0, 120).unwrap();
+        assert_eq!((r.step(), r.target_deg()), (1, 0));
+        assert_eq!(r.mark(3600), None);
+        assert_eq!((r.step(), r.target_deg()), (2, 120));
+        let cal = r.mark(600).unwrap().unwrap();
+        assert_eq!(cal.deg(3600.0), 0.0);
+        assert_eq!(cal.deg(2100.0), 60.0);
+        assert_eq!(cal.deg(600.0), 120.0);
+
+        assert_eq!(FbRoutine::new(10, 20), Err(FbCalError::Span));
+        // Potensiometer tidak tersambung: ADC sama di kedua titik
+        let mut r = FbRoutine::new(0, 120).unwrap();
+        r.mark(4095);
+        assert_eq!(r.mark(4095), Some(Err(FbCalError::Span)));
+    }
+
+    #[test]
+    fn stall_only_after_travel_time_and_persistence() {
+        let cal = FbCal::new((0, 500), (120, 2900)).unwrap(); // 20 ADC per derajat
+        let mut m = PositionMonitor::new(StallConfig::DEFAULT);
+        m.set_cal(Some(cal));
+        let raw = |deg: i32| (500 + deg * 20) as u16;
+
+        // Diam di 0°, lalu perintah 120° tapi damper tertahan di 40°
+        assert_eq!(m.update(raw(0), Some(0), 0), None);
+        let mut t = 0;
+        let mut event = None;
+        while event.is_none() && t < 10_000 {
+            t += 20;
+            event = m.update(raw(40), Some(120), t);
+        }
+        // Waktu tempuh (2 s) + persist (1 s) setelah perintah berubah di t = 20
+        assert_eq!(t, 3020);
+        let Some(StallEvent::Stalled { cmd_deg: 120, actual_deg }) = event else { panic!("{:?}", event) };
+        assert!((actual_deg - 40.0).abs() < 1.0);
+        assert!(m.stalled());
+        // Tidak dilaporkan ulang selama masih macet
+        assert_eq!(m.update(raw(40), Some(120), t + 20), None);
+
+        // Halangan hilang: filter mengejar lalu stall clear
+        let mut cleared = false;
+        for i in 0..50 { cleared |= m.update(raw(120), Some(120), t + 40 + i * 20) == Some(StallEvent::Cleared); }
+        assert!(cleared && !m.stalled());
+        assert!(m.deviation().unwrap().abs() < 1.0);
+
+        // Mode pulsa mentah: tidak dievaluasi
+        for i in 0..500 { assert_eq!(m.update(raw(0), None, 20_000 + i * 20), None); }
+    }
+}
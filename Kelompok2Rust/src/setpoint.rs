@@ -0,0 +1,371 @@
+//! Generator setpoint RH: ramp dengan laju terbatas ke target manual, atau
+//! profil waktu berupa segmen (durasi, target) untuk eksperimen step/ramp.
+//!
+//! Dalam satu segmen setpoint bergerak linear dari nilai awal segmen ke target
+//! selama durasinya (durasi 0 = step). Segmen dengan target sama = tahan (soak).
+//! Setelah segmen terakhir: `End::Hold` menahan target terakhir, `End::Loop`
+//! mengulang dari segmen pertama.
+
+use core::fmt;
+
+use crate::control::DEFAULT_SETPOINT;
+
+pub const MAX_SEGMENTS: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Segment {
+    pub duration_ms: u64,
+    pub target:      f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum End {
+    Hold,
+    Loop,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Profile {
+    segments: [Segment; MAX_SEGMENTS],
+    len:      usize,
+    pub end:  End,
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Profile {
+    pub const fn new() -> Self {
+        Self { segments: [Segment { duration_ms: 0, target: 0.0 }; MAX_SEGMENTS], len: 0, end: End::Hold }
+    }
+
+    /// false kalau profil sudah penuh.
+    pub fn push(&mut self, seg: Segment) -> bool {
+        if self.len == MAX_SEGMENTS { return false; }
+        self.segments[self.len] = Segment { target: seg.target.clamp(0.0, 100.0), ..seg };
+        self.len += 1;
+        true
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    pub fn segments(&self) -> &[Segment] {
+        &self.segments[..self.len]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn total_ms(&self) -> u64 {
+        self.segments().iter().fold(0, |acc, s| acc.saturating_add(s.duration_ms))
+    }
+}
+
+/// Posisi eksekusi profil.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProfileState {
+    Idle,
+    /// `from` = setpoint saat segmen dimulai.
+    Running { segment: usize, elapsed_ms: u64, from: f32, cycle: u32 },
+    Paused { segment: usize, elapsed_ms: u64, from: f32, cycle: u32 },
+    /// Selesai dengan `End::Hold`.
+    Done,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProfileError {
+    Empty,
+    NotRunning,
+    NotPaused,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SetpointGen {
+    /// Target manual (`sp <nilai>`).
+    target:   f32,
+    /// Laju ramp manual, %RH per menit (0 = langsung; NaN/inf juga langsung).
+    pub rate: f32,
+    value:    f32,
+    pub profile: Profile,
+    state:    ProfileState,
+    last_ms:  Option<u64>,
+}
+
+impl Default for SetpointGen {
+    fn default() -> Self {
+        Self::new(DEFAULT_SETPOINT)
+    }
+}
+
+impl SetpointGen {
+    pub const fn new(sp: f32) -> Self {
+        Self { target: sp, rate: 0.0, value: sp, profile: Profile::new(), state: ProfileState::Idle, last_ms: None }
+    }
+
+    /// Setpoint yang berlaku sekarang.
+    pub fn value(&self) -> f32 {
+        self.value
+    }
+
+    pub fn target(&self) -> f32 {
+        self.target
+    }
+
+    pub fn state(&self) -> ProfileState {
+        self.state
+    }
+
+    /// Target manual baru; didekati dengan laju `rate` (profil yang berjalan tetap diutamakan).
+    pub fn set_target(&mut self, sp: f32) {
+        self.target = sp.clamp(0.0, 100.0);
+    }
+
+    pub fn start(&mut self) -> Result<(), ProfileError> {
+        if self.profile.is_empty() { return Err(ProfileError::Empty); }
+        self.state = ProfileState::Running { segment: 0, elapsed_ms: 0, from: self.value, cycle: 0 };
+        Ok(())
+    }
+
+    pub fn pause(&mut self) -> Result<(), ProfileError> {
+        let ProfileState::Running { segment, elapsed_ms, from, cycle } = self.state else { return Err(ProfileError::NotRunning) };
+        self.state = ProfileState::Paused { segment, elapsed_ms, from, cycle };
+        Ok(())
+    }
+
+    pub fn resume(&mut self) -> Result<(), ProfileError> {
+        let ProfileState::Paused { segment, elapsed_ms, from, cycle } = self.state else { return Err(ProfileError::NotPaused) };
+        self.state = ProfileState::Running { segment, elapsed_ms, from, cycle };
+        Ok(())
+    }
+
+    /// Hentikan profil; setpoint kembali ke target manual dengan laju `rate`.
+    pub fn abort(&mut self) -> Result<(), ProfileError> {
+        if matches!(self.state, ProfileState::Idle) { return Err(ProfileError::NotRunning); }
+        self.state = ProfileState::Idle;
+        Ok(())
+    }
+
+    /// Sisa waktu segmen aktif (None kalau tidak ada profil berjalan/pause).
+    pub fn remaining_ms(&self) -> Option<u64> {
+        match self.state {
+            ProfileState::Running { segment, elapsed_ms, .. } | ProfileState::Paused { segment, elapsed_ms, .. } => {
+                Some(self.profile.segments().get(segment)?.duration_ms.saturating_sub(elapsed_ms))
+            }
+            _ => None,
+        }
+    }
+
+    /// Pulihkan state dari snapshot warm restart. Waktu selama reset tidak
+    /// dihitung (segmen lanjut dari `elapsed_ms` yang tersimpan). false kalau
+    /// isinya tidak konsisten; generator tidak diubah.
+    pub fn restore(&mut self, target: f32, value: f32, state: ProfileState) -> bool {
+        let sp_ok = |v: f32| (0.0..=100.0).contains(&v);
+        let pos_ok = |segment: usize, elapsed_ms: u64, from: f32| {
+            self.profile.segments().get(segment).is_some_and(|s| elapsed_ms <= s.duration_ms) && sp_ok(from)
+        };
+        let ok = sp_ok(target) && sp_ok(value) && self.rate.is_finite() && self.rate >= 0.0 && match state {
+            ProfileState::Running { segment, elapsed_ms, from, .. }
+            | ProfileState::Paused { segment, elapsed_ms, from, .. } => pos_ok(segment, elapsed_ms, from),
+            ProfileState::Idle | ProfileState::Done => true,
+        };
+        if ok {
+            self.target = target;
+            self.value = value;
+            self.state = state;
+            self.last_ms = None;
+        }
+        ok
+    }
+
+    /// Majukan ke waktu monotonic `now_ms`; return setpoint baru.
+    pub fn update(&mut self, now_ms: u64) -> f32 {
+        let dt = self.last_ms.map_or(0, |last| now_ms.saturating_sub(last));
+        self.last_ms = Some(now_ms);
+
+        match self.state {
+            ProfileState::Running { .. } => self.run_profile(dt),
+            ProfileState::Paused { .. } | ProfileState::Done => {}
+            ProfileState::Idle => self.ramp_to_target(dt),
+        }
+        self.value
+    }
+
+    fn ramp_to_target(&mut self, dt: u64) {
+        if !(self.rate > 0.0 && self.rate.is_finite()) { self.value = self.target; return; }
+        let step = self.rate * dt as f32 / 60_000.0;
+        let diff = self.target - self.value;
+        self.value += diff.clamp(-step, step);
+    }
+
+    fn run_profile(&mut self, mut dt: u64) {
+        let ProfileState::Running { mut segment, mut elapsed_ms, mut from, mut cycle } = self.state else { return };
+        // Sisa dt dibawa ke segmen berikutnya (mis. beberapa segmen step sekaligus)
+        loop {
+            // Profil diubah saat berjalan (segmen hilang): anggap selesai di nilai sekarang
+            let Some(&seg) = self.profile.segments().get(segment) else {
+                self.state = ProfileState::Done;
+                return;
+            };
+            let left = seg.duration_ms.saturating_sub(elapsed_ms);
+            if dt < left {
+                elapsed_ms += dt;
+                let frac = elapsed_ms as f32 / seg.duration_ms as f32;
+                self.value = from + (seg.target - from) * frac;
+                break;
+            }
+            dt -= left;
+            self.value = seg.target;
+            from = seg.target;
+            elapsed_ms = 0;
+            segment += 1;
+            if segment >= self.profile.len {
+                if self.profile.end == End::Hold {
+                    // Target manual ikut, supaya setelah selesai tidak ditarik kembali
+                    self.target = seg.target;
+                    self.state = ProfileState::Done;
+                    return;
+                }
+                segment = 0;
+                cycle += 1;
+                // Profil loop tanpa durasi sama sekali: berhenti di sini, tidak berputar terus
+                if self.profile.total_ms() == 0 { break; }
+            }
+        }
+        self.state = ProfileState::Running { segment, elapsed_ms, from, cycle };
+    }
+}
+
+impl fmt::Display for ProfileState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProfileState::Idle => f.write_str("manual"),
+            ProfileState::Running { segment, cycle, .. } => write!(f, "profil seg {} siklus {}", segment + 1, cycle + 1),
+            ProfileState::Paused { segment, .. } => write!(f, "profil PAUSE seg {}", segment + 1),
+            ProfileState::Done => f.write_str("profil selesai"),
+        }
+    }
+}
+
+impl fmt::Display for ProfileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ProfileError::Empty      => "profil kosong",
+            ProfileError::NotRunning => "profil tidak berjalan",
+            ProfileError::NotPaused  => "profil tidak di-pause",
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-3
+    }
+
+    fn gen_with(segs: &[(u64, f32)], end: End) -> SetpointGen {
+        let mut g = SetpointGen::new(50.0);
+        for &(duration_ms, target) in segs { g.profile.push(Segment { duration_ms, target }); }
+        g.profile.end = end;
+        g.update(0);
+        g
+    }
+
+    #[test]
+    fn manual_target_ramps_at_limited_rate() {
+        let mut g = SetpointGen::new(50.0);
+        g.rate = 6.0; // %RH per menit
+        g.update(0);
+        g.set_target(60.0);
+        assert!(close(g.update(30_000), 53.0));
+        assert!(close(g.update(60_000), 56.0));
+        assert!(close(g.update(600_000), 60.0));
+
+        // rate 0 = step
+        g.rate = 0.0;
+        g.set_target(40.0);
+        assert_eq!(g.update(601_000), 40.0);
+    }
+
+    #[test]
+    fn profile_ramps_holds_and_steps() {
+        // ramp 50→70 dalam 20 s, tahan 10 s, step ke 40
+        let mut g = gen_with(&[(20_000, 70.0), (10_000, 70.0), (0, 40.0)], End::Hold);
+        g.start().unwrap();
+        assert!(close(g.update(10_000), 60.0));
+        assert_eq!(g.remaining_ms(), Some(10_000));
+        assert!(close(g.update(25_000), 70.0));
+        assert_eq!(g.update(30_000), 40.0);
+        assert_eq!(g.state(), ProfileState::Done);
+
+        // Hold: tetap di target terakhir, tidak ditarik ke target manual lama
+        assert_eq!(g.update(100_000), 40.0);
+        g.abort().unwrap();
+        assert_eq!(g.update(101_000), 40.0);
+    }
+
+    #[test]
+    fn loop_restarts_from_first_segment() {
+        let mut g = gen_with(&[(10_000, 60.0), (10_000, 50.0)], End::Loop);
+        g.start().unwrap();
+        assert!(close(g.update(20_000), 50.0));
+        assert!(close(g.update(25_000), 55.0));
+        assert!(matches!(g.state(), ProfileState::Running { segment: 0, cycle: 1, .. }));
+    }
+
+    #[test]
+    fn pause_freezes_and_abort_returns_to_manual() {
+        let mut g = gen_with(&[(20_000, 70.0)], End::Hold);
+        g.rate = 60.0;
+        g.start().unwrap();
+        assert!(close(g.update(10_000), 60.0));
+        g.pause().unwrap();
+        assert!(close(g.update(50_000), 60.0));
+        g.resume().unwrap();
+        assert!(close(g.update(55_000), 65.0));
+
+        // Abort: kembali ke target manual 50 dengan 60 %/menit = 1 %/s
+        g.abort().unwrap();
+        assert!(close(g.update(60_000), 60.0));
+        assert!(close(g.update(80_000), 50.0));
+        assert_eq!(g.abort(), Err(ProfileError::NotRunning));
+    }
+
+    #[test]
+    fn profile_cleared_while_running_ends_without_panic() {
+        let mut g = gen_with(&[(20_000, 70.0), (20_000, 40.0)], End::Loop);
+        g.start().unwrap();
+        assert!(close(g.update(10_000), 60.0));
+        g.profile.clear();
+        assert!(close(g.update(11_000), 60.0));
+        assert_eq!(g.state(), ProfileState::Done);
+    }
+
+    #[test]
+    fn non_finite_rate_steps_instead_of_panicking() {
+        for rate in [f32::NAN, f32::INFINITY] {
+            let mut g = SetpointGen::new(50.0);
+            g.rate = rate;
+            g.set_target(60.0);
+            // Update pertama: dt = 0
+            assert_eq!(g.update(0), 60.0);
+            g.set_target(40.0);
+            assert_eq!(g.update(1000), 40.0);
+        }
+    }
+
+    #[test]
+    fn empty_profile_cannot_start() {
+        let mut g = SetpointGen::new(50.0);
+        assert_eq!(g.start(), Err(ProfileError::Empty));
+        assert_eq!(g.pause(), Err(ProfileError::NotRunning));
+        assert_eq!(g.resume(), Err(ProfileError::NotPaused));
+    }
+}
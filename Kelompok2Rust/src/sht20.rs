@@ -0,0 +1,34 @@
+//! SHT20 lewat modul RS485/Modbus (FC04, skala x10).
+
+use crate::hal::{Clock, ModbusPort};
+use crate::modbus::{Master, FC_READ_INPUT};
+
+// Register SHT20 (skala x10)
+pub const REG_TEMP: u16 = 0x0001;
+pub const REG_RH:   u16 = 0x0002;
+
+/// Hasil satu kali polling sensor (None = tidak ada/invalid reply).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Measurement {
+    pub rh:   Option<f32>,
+    pub temp: Option<f32>,
+}
+
+/// Alamat SHT20 di bus; transaksinya lewat `Master` milik pemanggil.
+#[derive(Debug, Clone, Copy)]
+pub struct Sht20 {
+    sid: u8,
+}
+
+impl Sht20 {
+    pub const fn new(sid: u8) -> Self {
+        Self { sid }
+    }
+
+    /// Baca RH lalu T. Register yang gagal dibaca jadi None.
+    pub async fn read<P: ModbusPort, C: Clock>(&self, bus: &mut Master<P, C>) -> Measurement {
+        let rh   = bus.read_u16(self.sid, FC_READ_INPUT, REG_RH).await.ok().map(|raw| raw as f32 / 10.0);
+        let temp = bus.read_u16(self.sid, FC_READ_INPUT, REG_TEMP).await.ok().map(|raw| raw as f32 / 10.0);
+        Measurement { rh, temp }
+    }
+}
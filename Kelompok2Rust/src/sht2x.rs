@@ -0,0 +1,277 @@
+//! Driver SHT2x (SHT20/21/25) native lewat I2C, di atas trait embedded-hal 1.0.
+//! Alternatif `sht20` (modul RS485/Modbus) kalau sensor disambung langsung.
+//!
+//! Pengukuran mode "no hold master": `start` kirim perintah, pemanggil
+//! menunggu `measurement_ms` (Timer async di firmware, `DelayNs` lewat
+//! `measure`), lalu `read` ambil hasil + cek CRC-8. Bus tidak dikunci selama
+//! sensor mengukur (sampai 85 ms untuk T 14 bit).
+
+use embedded_hal::delay::DelayNs;
+use embedded_hal::i2c::I2c;
+
+use crate::sht20::Measurement;
+
+/// Alamat I2C tetap (7 bit).
+pub const ADDRESS: u8 = 0x40;
+
+const CMD_TRIGGER_T:  u8 = 0xF3; // no hold master
+const CMD_TRIGGER_RH: u8 = 0xF5;
+const CMD_WRITE_USER: u8 = 0xE6;
+const CMD_READ_USER:  u8 = 0xE7;
+const CMD_SOFT_RESET: u8 = 0xFE;
+
+/// Tunggu setelah `soft_reset` sebelum perintah berikutnya (datasheet: < 15 ms).
+pub const SOFT_RESET_MS: u32 = 15;
+
+// User register: bit 7 + bit 0 = resolusi, bit 2 = heater; bit lain (OTP, status baterai, reserved) dipertahankan
+const USER_RES_MASK: u8 = 0x81;
+const USER_HEATER:   u8 = 0x04;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Humidity,
+    Temperature,
+}
+
+/// Resolusi RH / T (bit pengukuran). Lebih rendah = lebih cepat.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    Rh12T14,
+    Rh8T12,
+    Rh10T13,
+    Rh11T11,
+}
+
+impl Resolution {
+    const fn bits(self) -> u8 {
+        match self {
+            Resolution::Rh12T14 => 0x00,
+            Resolution::Rh8T12  => 0x01,
+            Resolution::Rh10T13 => 0x80,
+            Resolution::Rh11T11 => 0x81,
+        }
+    }
+
+    fn from_user(reg: u8) -> Self {
+        match reg & USER_RES_MASK {
+            0x00 => Resolution::Rh12T14,
+            0x01 => Resolution::Rh8T12,
+            0x80 => Resolution::Rh10T13,
+            _    => Resolution::Rh11T11,
+        }
+    }
+
+    /// Waktu ukur maksimum (datasheet, dibulatkan ke atas).
+    pub const fn measurement_ms(self, kind: Kind) -> u32 {
+        match (kind, self) {
+            (Kind::Humidity, Resolution::Rh12T14)    => 29,
+            (Kind::Humidity, Resolution::Rh11T11)    => 15,
+            (Kind::Humidity, Resolution::Rh10T13)    => 9,
+            (Kind::Humidity, Resolution::Rh8T12)     => 4,
+            (Kind::Temperature, Resolution::Rh12T14) => 85,
+            (Kind::Temperature, Resolution::Rh10T13) => 43,
+            (Kind::Temperature, Resolution::Rh8T12)  => 22,
+            (Kind::Temperature, Resolution::Rh11T11) => 11,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error<E> {
+    /// Error bus (termasuk NACK kalau hasil diminta sebelum pengukuran selesai).
+    I2c(E),
+    /// CRC-8 hasil pengukuran tidak cocok.
+    Crc,
+    /// Bit status hasil bukan jenis pengukuran yang diminta.
+    WrongKind,
+}
+
+/// CRC-8 SHT2x: polinomial x^8 + x^5 + x^4 + 1 (0x31), init 0x00.
+pub fn crc8(data: &[u8]) -> u8 {
+    let mut crc = 0u8;
+    for &b in data {
+        crc ^= b;
+        for _ in 0..8 {
+            crc = if crc & 0x80 != 0 { (crc << 1) ^ 0x31 } else { crc << 1 };
+        }
+    }
+    crc
+}
+
+/// Nilai mentah 16 bit (bit status sudah dibuang) → %RH / °C.
+pub fn convert(kind: Kind, raw: u16) -> f32 {
+    let s = (raw & !0x0003) as f32 / 65_536.0;
+    match kind {
+        Kind::Humidity    => -6.0 + 125.0 * s,
+        Kind::Temperature => -46.85 + 175.72 * s,
+    }
+}
+
+pub struct Sht2x<I> {
+    i2c: I,
+    res: Resolution,
+}
+
+impl<I: I2c> Sht2x<I> {
+    /// Resolusi dianggap default pabrik sampai `set_resolution` / `resolution_from_device`.
+    pub fn new(i2c: I) -> Self {
+        Self { i2c, res: Resolution::Rh12T14 }
+    }
+
+    pub fn release(self) -> I {
+        self.i2c
+    }
+
+    pub fn resolution(&self) -> Resolution {
+        self.res
+    }
+
+    /// Reset sensor (user register kembali default kecuali heater mati).
+    /// Tunggu `SOFT_RESET_MS` sebelum perintah berikutnya.
+    pub fn soft_reset(&mut self) -> Result<(), Error<I::Error>> {
+        self.i2c.write(ADDRESS, &[CMD_SOFT_RESET]).map_err(Error::I2c)?;
+        self.res = Resolution::Rh12T14;
+        Ok(())
+    }
+
+    pub fn user_register(&mut self) -> Result<u8, Error<I::Error>> {
+        let mut reg = [0u8; 1];
+        self.i2c.write_read(ADDRESS, &[CMD_READ_USER], &mut reg).map_err(Error::I2c)?;
+        Ok(reg[0])
+    }
+
+    /// Baca-ubah-tulis user register (bit reserved tidak boleh diubah).
+    fn update_user(&mut self, mask: u8, bits: u8) -> Result<u8, Error<I::Error>> {
+        let reg = (self.user_register()? & !mask) | bits;
+        self.i2c.write(ADDRESS, &[CMD_WRITE_USER, reg]).map_err(Error::I2c)?;
+        Ok(reg)
+    }
+
+    /// Sinkronkan resolusi yang dipakai `measurement_ms` dengan isi sensor.
+    pub fn resolution_from_device(&mut self) -> Result<Resolution, Error<I::Error>> {
+        self.res = Resolution::from_user(self.user_register()?);
+        Ok(self.res)
+    }
+
+    pub fn set_resolution(&mut self, res: Resolution) -> Result<(), Error<I::Error>> {
+        self.update_user(USER_RES_MASK, res.bits())?;
+        self.res = res;
+        Ok(())
+    }
+
+    /// Heater internal (diagnosa / menghilangkan embun); menaikkan T beberapa °C.
+    pub fn set_heater(&mut self, on: bool) -> Result<(), Error<I::Error>> {
+        self.update_user(USER_HEATER, if on { USER_HEATER } else { 0 }).map(|_| ())
+    }
+
+    /// Waktu tunggu antara `start` dan `read` untuk resolusi sekarang.
+    pub fn measurement_ms(&self, kind: Kind) -> u32 {
+        self.res.measurement_ms(kind)
+    }
+
+    pub fn start(&mut self, kind: Kind) -> Result<(), Error<I::Error>> {
+        let cmd = match kind {
+            Kind::Humidity    => CMD_TRIGGER_RH,
+            Kind::Temperature => CMD_TRIGGER_T,
+        };
+        self.i2c.write(ADDRESS, &[cmd]).map_err(Error::I2c)
+    }
+
+    /// Hasil pengukuran yang di-`start` sebelumnya: [MSB][LSB + status][CRC].
+    pub fn read(&mut self, kind: Kind) -> Result<f32, Error<I::Error>> {
+        let mut buf = [0u8; 3];
+        self.i2c.read(ADDRESS, &mut buf).map_err(Error::I2c)?;
+        if crc8(&buf[..2]) != buf[2] { return Err(Error::Crc); }
+        // Bit 1 status: 0 = temperatur, 1 = RH
+        let is_rh = buf[1] & 0x02 != 0;
+        if is_rh != (kind == Kind::Humidity) { return Err(Error::WrongKind); }
+        Ok(convert(kind, u16::from_be_bytes([buf[0], buf[1]])))
+    }
+
+    /// `start` + tunggu + `read` (blocking).
+    pub fn measure(&mut self, kind: Kind, delay: &mut impl DelayNs) -> Result<f32, Error<I::Error>> {
+        self.start(kind)?;
+        delay.delay_ms(self.measurement_ms(kind));
+        self.read(kind)
+    }
+
+    /// RH lalu T, bentuk sama dengan pembacaan lewat Modbus; yang gagal jadi None.
+    pub fn measure_both(&mut self, delay: &mut impl DelayNs) -> Measurement {
+        let rh   = self.measure(Kind::Humidity, delay).ok();
+        let temp = self.measure(Kind::Temperature, delay).ok();
+        Measurement { rh, temp }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use embedded_hal::i2c::ErrorKind;
+    use embedded_hal_mock::eh1::delay::NoopDelay;
+    use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+
+    /// Frame hasil dengan CRC benar.
+    fn result(raw: u16) -> Vec<u8> {
+        let b = raw.to_be_bytes();
+        vec![b[0], b[1], crc8(&b)]
+    }
+
+    #[test]
+    fn measures_rh_and_t_with_crc_and_status_check() {
+        // Contoh datasheet: CRC 0x68 0x3A → 0x7C
+        assert_eq!(crc8(&[0x68, 0x3A]), 0x7C);
+
+        let rh_raw = 0x7C80 | 0x02; // ≈ 54.8 %RH
+        let t_raw  = 0x6420;        // ≈ 21.9 °C
+        let mut bad = result(t_raw);
+        bad[2] ^= 0xFF;
+        let expected = [
+            Transaction::write(ADDRESS, vec![CMD_TRIGGER_RH]),
+            Transaction::read(ADDRESS, result(rh_raw)),
+            Transaction::write(ADDRESS, vec![CMD_TRIGGER_T]),
+            Transaction::read(ADDRESS, result(t_raw)),
+            // CRC rusak, lalu hasil RH saat minta T, lalu NACK (belum selesai)
+            Transaction::write(ADDRESS, vec![CMD_TRIGGER_T]),
+            Transaction::read(ADDRESS, bad),
+            Transaction::write(ADDRESS, vec![CMD_TRIGGER_T]),
+            Transaction::read(ADDRESS, result(rh_raw)),
+            Transaction::write(ADDRESS, vec![CMD_TRIGGER_T]),
+            Transaction::read(ADDRESS, vec![0; 3]).with_error(ErrorKind::Other),
+        ];
+        let mut i2c = Mock::new(&expected);
+        let mut sht = Sht2x::new(i2c.clone());
+
+        let m = sht.measure_both(&mut NoopDelay::new());
+        assert!((m.rh.unwrap() - 54.8).abs() < 0.1);
+        assert!((m.temp.unwrap() - 21.9).abs() < 0.1);
+        assert_eq!(sht.measure(Kind::Temperature, &mut NoopDelay::new()), Err(Error::Crc));
+        assert_eq!(sht.measure(Kind::Temperature, &mut NoopDelay::new()), Err(Error::WrongKind));
+        assert_eq!(sht.measure(Kind::Temperature, &mut NoopDelay::new()), Err(Error::I2c(ErrorKind::Other)));
+        i2c.done();
+    }
+
+    #[test]
+    fn user_register_keeps_reserved_bits() {
+        let expected = [
+            // Default pabrik 0x3A (reserved bit 3..5 + OTP reload off)
+            Transaction::write_read(ADDRESS, vec![CMD_READ_USER], vec![0x3A]),
+            Transaction::write(ADDRESS, vec![CMD_WRITE_USER, 0xBB]),
+            Transaction::write_read(ADDRESS, vec![CMD_READ_USER], vec![0xBB]),
+            Transaction::write(ADDRESS, vec![CMD_WRITE_USER, 0xBF]),
+            Transaction::write_read(ADDRESS, vec![CMD_READ_USER], vec![0xBF]),
+            Transaction::write(ADDRESS, vec![CMD_SOFT_RESET]),
+            Transaction::write_read(ADDRESS, vec![CMD_READ_USER], vec![0x3A]),
+        ];
+        let mut i2c = Mock::new(&expected);
+        let mut sht = Sht2x::new(i2c.clone());
+
+        sht.set_resolution(Resolution::Rh11T11).unwrap();
+        assert_eq!(sht.measurement_ms(Kind::Temperature), 11);
+        sht.set_heater(true).unwrap();
+        assert_eq!(sht.user_register().unwrap() & USER_HEATER, USER_HEATER);
+        sht.soft_reset().unwrap();
+        assert_eq!(sht.resolution(), Resolution::Rh12T14);
+        assert_eq!(sht.resolution_from_device(), Ok(Resolution::Rh12T14));
+        i2c.done();
+    }
+}
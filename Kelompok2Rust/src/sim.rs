@@ -0,0 +1,348 @@
+//! Simulasi untuk host: SHT20 dan modul relay Modbus palsu di atas model
+//! ruangan sederhana, plus
+//! implementasi trait `hal` yang membaca/menulis state `SimWorld`.
+//!
+//! Semua state pakai `Cell`/`RefCell` supaya satu `SimWorld` bisa dipinjam
+//! bersamaan oleh port, output, dan jam yang diberikan ke `App`.
+
+use core::cell::{Cell, RefCell};
+use core::future::Future;
+use core::task::{Context, Poll, Waker};
+
+use crate::hal::{Clock, DigitalOutput, ModbusPort, PortError, Servo, SetBaud};
+use crate::modbus::{self, FC_READ_COILS, FC_READ_DISCRETE, FC_READ_HOLDING, FC_READ_INPUT, FC_WRITE_COIL, FC_WRITE_COILS};
+use crate::servo::{SERVO_MAX_US, SERVO_MIN_US};
+use crate::sht20::{REG_RH, REG_TEMP};
+
+// Langkah integrasi model ruangan
+const STEP_MS: u64 = 100;
+/// Baudrate bawaan modul sensor & port simulasi (sama dengan firmware).
+pub const SIM_BAUD: u32 = 9_600;
+/// Jumlah relay modul Modbus palsu; relay 0 menggerakkan kipas.
+pub const RELAY_CHANNELS: u16 = 8;
+
+/// Model ruangan orde-1: sumber uap air menaikkan RH, ventilasi (kebocoran,
+/// kipas, bukaan damper/servo) menariknya kembali ke RH udara luar.
+#[derive(Debug, Clone, Copy)]
+pub struct Room {
+    pub rh:          f32, // %RH
+    pub temp:        f32, // °C
+    pub ambient_rh:  f32, // %RH udara luar
+    pub moisture_in: f32, // %RH/s dari sumber uap (humidifier, orang, dsb)
+    pub leak_rate:   f32, // 1/s ventilasi alami
+    pub fan_rate:    f32, // 1/s tambahan saat kipas ON
+    pub damper_rate: f32, // 1/s tambahan saat damper terbuka penuh (120°)
+}
+
+impl Default for Room {
+    fn default() -> Self {
+        Self {
+            rh: 55.0,
+            temp: 27.0,
+            ambient_rh: 50.0,
+            moisture_in: 0.0,
+            leak_rate: 0.002,
+            fan_rate: 0.05,
+            damper_rate: 0.01,
+        }
+    }
+}
+
+impl Room {
+    /// Integrasi Euler selama `dt_s` detik; `damper` = bukaan 0.0..=1.0.
+    pub fn step(&mut self, dt_s: f32, fan_on: bool, damper: f32) {
+        let vent = self.leak_rate
+            + if fan_on { self.fan_rate } else { 0.0 }
+            + damper.clamp(0.0, 1.0) * self.damper_rate;
+        let drh = self.moisture_in - vent * (self.rh - self.ambient_rh);
+        self.rh = (self.rh + drh * dt_s).clamp(0.0, 100.0);
+    }
+}
+
+/// Gangguan yang bisa disuntikkan ke SHT20 simulasi.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SensorFault {
+    None,
+    /// Kabel putus / sensor mati: tidak ada reply.
+    Offline,
+    /// Reply dengan CRC rusak.
+    BadCrc,
+    /// Reply valid tapi dengan slave ID lain.
+    WrongSlaveId,
+    /// Reply terpotong 2 byte.
+    Truncated,
+}
+
+/// State dunia simulasi yang dibagi ke semua komponen `Sim*`.
+pub struct SimWorld {
+    pub room:     RefCell<Room>,
+    now_ms:       Cell<u64>,
+    fan_on:       Cell<bool>,
+    servo_us:     Cell<u32>,
+    sensor_fault: Cell<SensorFault>,
+    /// Error RH sensor: terbaca = gain * RH ruangan + offset.
+    rh_error:     Cell<(f32, f32)>,
+    /// Baudrate modul sensor; port dengan baudrate lain tidak dapat jawaban.
+    sensor_baud:  Cell<u32>,
+    /// Coil relay 1..7 (bit per coil); coil 0 = `fan_on`.
+    relay_coils:  Cell<u8>,
+    relay_online: Cell<bool>,
+    /// Waktu jawab SHT20 (jam simulasi maju sebelum reply dikembalikan).
+    reply_ms:     Cell<u64>,
+}
+
+impl SimWorld {
+    pub fn new(room: Room) -> Self {
+        Self {
+            room: RefCell::new(room),
+            now_ms: Cell::new(0),
+            fan_on: Cell::new(false),
+            servo_us: Cell::new(SERVO_MIN_US),
+            sensor_fault: Cell::new(SensorFault::None),
+            rh_error: Cell::new((1.0, 0.0)),
+            sensor_baud: Cell::new(SIM_BAUD),
+            relay_coils: Cell::new(0),
+            relay_online: Cell::new(true),
+            reply_ms: Cell::new(0),
+        }
+    }
+
+    /// Majukan waktu simulasi, model ruangan diintegrasi per `STEP_MS`.
+    pub fn advance(&self, ms: u64) {
+        let mut left = ms;
+        while left > 0 {
+            let dt = left.min(STEP_MS);
+            self.room.borrow_mut().step(dt as f32 / 1000.0, self.fan_on.get(), self.damper());
+            self.now_ms.set(self.now_ms.get() + dt);
+            left -= dt;
+        }
+    }
+
+    pub fn now_ms(&self) -> u64 { self.now_ms.get() }
+    pub fn fan_on(&self) -> bool { self.fan_on.get() }
+    pub fn servo_us(&self) -> u32 { self.servo_us.get() }
+
+    /// Bukaan damper dari lebar pulsa servo (MIN → 0.0, MAX → 1.0).
+    pub fn damper(&self) -> f32 {
+        let us = self.servo_us.get().clamp(SERVO_MIN_US, SERVO_MAX_US);
+        (us - SERVO_MIN_US) as f32 / (SERVO_MAX_US - SERVO_MIN_US) as f32
+    }
+
+    pub fn set_sensor_fault(&self, fault: SensorFault) { self.sensor_fault.set(fault); }
+    pub fn set_sensor_rh_error(&self, gain: f32, offset: f32) { self.rh_error.set((gain, offset)); }
+
+    pub fn set_sensor_baud(&self, baud: u32) { self.sensor_baud.set(baud); }
+    pub fn set_sensor_reply_ms(&self, ms: u64) { self.reply_ms.set(ms); }
+    pub fn sensor(&self, sid: u8) -> SimSht20<'_> { SimSht20 { world: self, sid, baud: SIM_BAUD } }
+    pub fn fan(&self) -> SimFan<'_> { SimFan(self) }
+
+    pub fn set_relay_online(&self, online: bool) { self.relay_online.set(online); }
+    /// Modul relay kehilangan daya sesaat: semua coil kembali OFF.
+    pub fn relay_power_cycle(&self) {
+        self.relay_coils.set(0);
+        self.fan_on.set(false);
+    }
+    /// Modul relay Modbus: coil 0 = kontaktor kipas, discrete input 0 = saklar arus kipas.
+    pub fn relay(&self, sid: u8) -> SimRelay<'_> { SimRelay { world: self, sid } }
+    pub fn servo(&self) -> SimServo<'_> { SimServo(self) }
+    pub fn clock(&self) -> SimClock<'_> { SimClock(self) }
+}
+
+/// SHT20 palsu: jawab FC03/FC04 untuk register T (0x0001) dan RH (0x0002).
+pub struct SimSht20<'a> {
+    world: &'a SimWorld,
+    sid:   u8,
+    /// Baudrate sisi master.
+    baud:  u32,
+}
+
+impl SimSht20<'_> {
+    fn register(&self, addr: u16) -> Option<u16> {
+        let room = self.world.room.borrow();
+        match addr {
+            REG_TEMP => Some(((room.temp * 10.0) as i16) as u16),
+            REG_RH   => {
+                let (gain, offset) = self.world.rh_error.get();
+                Some(((room.rh * gain + offset).clamp(0.0, 100.0) * 10.0) as u16)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl ModbusPort for SimSht20<'_> {
+    async fn transact(&mut self, request: &[u8], response: &mut [u8]) -> Result<usize, PortError> {
+        // Slave diam kalau offline, frame rusak, atau bukan ID-nya
+        let fault = self.world.sensor_fault.get();
+        if fault == SensorFault::Offline || request.len() != 8 || !modbus::check_crc(request) {
+            return Err(PortError::Timeout);
+        }
+        if request[0] != self.sid || self.baud != self.world.sensor_baud.get() { return Err(PortError::Timeout); }
+
+        let fc    = request[1];
+        let addr  = u16::from_be_bytes([request[2], request[3]]);
+        let count = u16::from_be_bytes([request[4], request[5]]);
+
+        let mut frame = [0u8; 64];
+        frame[0] = if fault == SensorFault::WrongSlaveId { self.sid.wrapping_add(1) } else { self.sid };
+        let mut n = if fc != FC_READ_INPUT && fc != FC_READ_HOLDING {
+            frame[1] = fc | 0x80;
+            frame[2] = 0x01; // illegal function
+            3
+        } else if count == 0 || modbus::read_response_len(count) > frame.len() {
+            frame[1] = fc | 0x80;
+            frame[2] = 0x03; // illegal data value
+            3
+        } else {
+            frame[1] = fc;
+            frame[2] = (count * 2) as u8;
+            let mut n = 3;
+            for i in 0..count {
+                let Some(v) = self.register(addr.wrapping_add(i)) else {
+                    frame[1] = fc | 0x80;
+                    frame[2] = 0x02; // illegal data address
+                    n = 3;
+                    break;
+                };
+                frame[n..n + 2].copy_from_slice(&v.to_be_bytes());
+                n += 2;
+            }
+            n
+        };
+        let crc = modbus::crc16(&frame[..n]);
+        frame[n] = (crc & 0xFF) as u8;
+        frame[n + 1] = (crc >> 8) as u8;
+        n += 2;
+
+        match fault {
+            SensorFault::BadCrc => frame[n - 1] ^= 0xFF,
+            SensorFault::Truncated => n -= 2,
+            _ => {}
+        }
+
+        self.world.advance(self.world.reply_ms.get());
+        let len = n.min(response.len());
+        response[..len].copy_from_slice(&frame[..len]);
+        Ok(len)
+    }
+}
+
+impl SetBaud for SimSht20<'_> {
+    fn set_baud(&mut self, baud: u32) -> Result<(), PortError> {
+        self.baud = baud;
+        Ok(())
+    }
+}
+
+/// Modul relay RS485 palsu: FC01/FC02/FC05/FC0F untuk `RELAY_CHANNELS` coil
+/// dan discrete input. Selalu di `SIM_BAUD`.
+pub struct SimRelay<'a> {
+    world: &'a SimWorld,
+    sid:   u8,
+}
+
+impl SimRelay<'_> {
+    fn coil(&self, i: u16) -> bool {
+        if i == 0 { self.world.fan_on.get() } else { self.world.relay_coils.get() & (1 << i) != 0 }
+    }
+
+    fn set_coil(&self, i: u16, on: bool) {
+        if i == 0 { return self.world.fan_on.set(on); }
+        let mask = 1u8 << i;
+        let c = self.world.relay_coils.get();
+        self.world.relay_coils.set(if on { c | mask } else { c & !mask });
+    }
+
+    /// Discrete input 0 = kipas jalan; sisanya tidak terhubung.
+    fn input(&self, i: u16) -> bool {
+        i == 0 && self.world.fan_on.get()
+    }
+
+    /// PDU jawaban (tanpa ID & CRC) ke `out`, return panjangnya.
+    fn reply(&self, request: &[u8], out: &mut [u8]) -> usize {
+        let fc    = request[1];
+        let addr  = u16::from_be_bytes([request[2], request[3]]);
+        let value = u16::from_be_bytes([request[4], request[5]]);
+        let in_range = |count: u16| count > 0 && addr.checked_add(count).is_some_and(|end| end <= RELAY_CHANNELS);
+        let exception = |out: &mut [u8], code: u8| { out[0] = fc | 0x80; out[1] = code; 2 };
+
+        out[0] = fc;
+        match fc {
+            FC_READ_COILS | FC_READ_DISCRETE if request.len() == 8 => {
+                if !in_range(value) { return exception(out, 0x02); }
+                let bc = (value as usize).div_ceil(8);
+                out[1] = bc as u8;
+                out[2..2 + bc].fill(0);
+                for i in 0..value {
+                    let on = if fc == FC_READ_COILS { self.coil(addr + i) } else { self.input(addr + i) };
+                    if on { out[2 + i as usize / 8] |= 1 << (i % 8); }
+                }
+                2 + bc
+            }
+            FC_WRITE_COIL if request.len() == 8 => {
+                if !in_range(1) { return exception(out, 0x02); }
+                match value {
+                    modbus::COIL_ON | modbus::COIL_OFF => self.set_coil(addr, value == modbus::COIL_ON),
+                    _ => return exception(out, 0x03),
+                }
+                out[1..5].copy_from_slice(&request[2..6]);
+                5
+            }
+            FC_WRITE_COILS if request.len() > 9 => {
+                if !in_range(value) || request[6] as usize != (value as usize).div_ceil(8) { return exception(out, 0x03); }
+                for i in 0..value { self.set_coil(addr + i, request[7 + i as usize / 8] & (1 << (i % 8)) != 0); }
+                out[1..5].copy_from_slice(&request[2..6]);
+                5
+            }
+            _ => exception(out, 0x01),
+        }
+    }
+}
+
+impl ModbusPort for SimRelay<'_> {
+    async fn transact(&mut self, request: &[u8], response: &mut [u8]) -> Result<usize, PortError> {
+        if !self.world.relay_online.get() || request.len() < 8 || !modbus::check_crc(request) || request[0] != self.sid {
+            return Err(PortError::Timeout);
+        }
+        let mut frame = [0u8; 16];
+        frame[0] = self.sid;
+        let mut n = 1 + self.reply(request, &mut frame[1..]);
+        let crc = modbus::crc16(&frame[..n]);
+        frame[n] = (crc & 0xFF) as u8;
+        frame[n + 1] = (crc >> 8) as u8;
+        n += 2;
+
+        let len = n.min(response.len());
+        response[..len].copy_from_slice(&frame[..len]);
+        Ok(len)
+    }
+}
+
+pub struct SimFan<'a>(&'a SimWorld);
+
+impl DigitalOutput for SimFan<'_> {
+    fn set(&mut self, on: bool) { self.0.fan_on.set(on); }
+}
+
+pub struct SimServo<'a>(&'a SimWorld);
+
+impl Servo for SimServo<'_> {
+    fn set_pulse_us(&mut self, us: u32) { self.0.servo_us.set(us); }
+}
+
+#[derive(Clone, Copy)]
+pub struct SimClock<'a>(&'a SimWorld);
+
+impl Clock for SimClock<'_> {
+    fn now_us(&self) -> u64 { self.0.now_ms.get() * 1000 }
+}
+
+/// Jalankan future sampai selesai. Komponen `Sim*` tidak pernah `Pending`,
+/// jadi cukup di-poll berulang dengan waker kosong.
+pub fn block_on<F: Future>(fut: F) -> F::Output {
+    let mut fut = core::pin::pin!(fut);
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(v) = fut.as_mut().poll(&mut cx) { return v; }
+    }
+}
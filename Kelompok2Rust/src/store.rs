@@ -0,0 +1,587 @@
+//! Store-and-forward sample: tiap sample diberi nomor urut (seq) dan disimpan
+//! di ring buffer RAM sampai host mengirim `ack <seq>`. Kalau RAM penuh, sample
+//! tertua dipindah ke `Spill` (mis. partisi flash) sebelum dibuang.
+//!
+//! Protokol host:
+//!   `listen`    → host siap; semua sample yang belum di-ack dikirim ulang berurutan
+//!   `ack <seq>` → sample sampai `seq` sudah diterima, boleh dihapus
+//! Tanpa ack selama `ACK_TIMEOUT_MS` host dianggap putus: pengiriman berhenti,
+//! sample tetap dikumpulkan sampai `listen` berikutnya.
+//!
+//! Isi spill flash bertahan saat reset: record yang belum di-ack dikirim lagi
+//! dan seq dilanjutkan dari seq tertinggi di flash.
+
+use core::fmt;
+
+use embedded_storage::nor_flash::MultiwriteNorFlash;
+
+use crate::modbus::crc16;
+use crate::sht20::Measurement;
+use crate::timesync::SyncQuality;
+
+/// Host dianggap putus kalau ada sample terkirim tapi tidak di-ack selama ini.
+pub const ACK_TIMEOUT_MS: u64 = 10_000;
+/// Ukuran satu record di flash (kelipatan 4 byte, word write ESP32).
+pub const RECORD_LEN: usize = 28;
+
+/// Satu sample yang disimpan.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Record {
+    pub seq:         u32,
+    /// Waktu akuisisi, monotonic (ms sejak boot).
+    pub t_ms:        u64,
+    /// Waktu akuisisi dalam epoch host (None kalau belum sync).
+    pub epoch_ms:    Option<u64>,
+    pub sync:        SyncQuality,
+    pub measurement: Measurement,
+}
+
+const EMPTY: Record = Record {
+    seq: 0, t_ms: 0, epoch_ms: None, sync: SyncQuality::Unsynced, measurement: Measurement { rh: None, temp: None },
+};
+const QUALITY: [SyncQuality; 4] = [SyncQuality::Unsynced, SyncQuality::Coarse, SyncQuality::Fine, SyncQuality::Stale];
+/// Penanda nilai kosong di format biner (RH/T x10, epoch).
+const NO_VALUE: i16 = i16::MIN;
+/// Byte terakhir record: 0xFF = belum di-ack, 0x00 = sudah (ditimpa tanpa erase).
+const LIVE: u8 = 0xFF;
+const ACKED: u8 = 0x00;
+
+impl Record {
+    /// `seq u32 | t_ms u64 | epoch u64 (0 = none) | rh i16 x10 | t i16 x10 | sync u8 | crc16 | flag`,
+    /// little-endian. `flag` di luar CRC supaya bisa ditandai `ACKED` di tempat.
+    pub fn encode(&self) -> [u8; RECORD_LEN] {
+        // Dibulatkan, bukan dipotong: replay dari flash = replay dari RAM (`{:.1}`)
+        let tenths = |v: Option<f32>| v.map_or(NO_VALUE, |v| libm::roundf(v * 10.0) as i16);
+        let mut b = [0u8; RECORD_LEN];
+        b[0..4].copy_from_slice(&self.seq.to_le_bytes());
+        b[4..12].copy_from_slice(&self.t_ms.to_le_bytes());
+        b[12..20].copy_from_slice(&self.epoch_ms.unwrap_or(0).to_le_bytes());
+        b[20..22].copy_from_slice(&tenths(self.measurement.rh).to_le_bytes());
+        b[22..24].copy_from_slice(&tenths(self.measurement.temp).to_le_bytes());
+        b[24] = QUALITY.iter().position(|&q| q == self.sync).unwrap_or(0) as u8;
+        let crc = crc16(&b[..25]);
+        b[25..27].copy_from_slice(&crc.to_le_bytes());
+        b[27] = LIVE;
+        b
+    }
+
+    /// None kalau CRC salah (mis. slot flash kosong 0xFF atau tulisan terputus).
+    pub fn decode(b: &[u8; RECORD_LEN]) -> Option<Self> {
+        if crc16(&b[..25]) != u16::from_le_bytes([b[25], b[26]]) { return None; }
+        let value = |i: usize| match i16::from_le_bytes([b[i], b[i + 1]]) {
+            NO_VALUE => None,
+            v => Some(v as f32 / 10.0),
+        };
+        let epoch = u64::from_le_bytes(b[12..20].try_into().ok()?);
+        Some(Self {
+            seq: u32::from_le_bytes(b[0..4].try_into().ok()?),
+            t_ms: u64::from_le_bytes(b[4..12].try_into().ok()?),
+            epoch_ms: (epoch != 0).then_some(epoch),
+            sync: *QUALITY.get(b[24] as usize)?,
+            measurement: Measurement { rh: value(20), temp: value(22) },
+        })
+    }
+}
+
+/// Format baris ke host: `seq 12 ts 1700000000000 up 12345 rh 55.0 t 27.0 sync fine`
+/// (`-` untuk nilai kosong).
+impl fmt::Display for Record {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "seq {} ts ", self.seq)?;
+        match self.epoch_ms { Some(ts) => write!(f, "{}", ts)?, None => f.write_str("-")? }
+        write!(f, " up {}", self.t_ms)?;
+        for (name, v) in [(" rh ", self.measurement.rh), (" t ", self.measurement.temp)] {
+            f.write_str(name)?;
+            match v { Some(v) => write!(f, "{:.1}", v)?, None => f.write_str("-")? }
+        }
+        write!(f, " sync {}", self.sync)
+    }
+}
+
+/// Penyimpanan lanjutan (FIFO) untuk sample tertua saat RAM penuh.
+pub trait Spill {
+    /// Tambah di ujung; false kalau penuh atau gagal tulis.
+    fn push(&mut self, rec: &Record) -> bool;
+    /// Record ke-`idx` dari yang tertua.
+    fn get(&mut self, idx: usize) -> Option<Record>;
+    fn len(&self) -> usize;
+    /// Buang `n` record tertua.
+    fn drop_front(&mut self, n: usize);
+    /// Seq tertinggi yang pernah disimpan (dipulihkan setelah reset).
+    fn last_seq(&self) -> Option<u32> {
+        None
+    }
+    /// Ada pekerjaan lambat (erase) yang sebaiknya dijalankan di luar lock.
+    fn erase_due(&self) -> bool {
+        false
+    }
+    fn erase_ahead(&mut self) {}
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Tanpa spill: `None`. Dengan spill: `Some(flash)`.
+impl<S: Spill> Spill for Option<S> {
+    fn push(&mut self, rec: &Record) -> bool {
+        self.as_mut().is_some_and(|s| s.push(rec))
+    }
+
+    fn get(&mut self, idx: usize) -> Option<Record> {
+        self.as_mut()?.get(idx)
+    }
+
+    fn len(&self) -> usize {
+        self.as_ref().map_or(0, |s| s.len())
+    }
+
+    fn drop_front(&mut self, n: usize) {
+        if let Some(s) = self { s.drop_front(n); }
+    }
+
+    fn last_seq(&self) -> Option<u32> {
+        self.as_ref()?.last_seq()
+    }
+
+    fn erase_due(&self) -> bool {
+        self.as_ref().is_some_and(|s| s.erase_due())
+    }
+
+    fn erase_ahead(&mut self) {
+        if let Some(s) = self { s.erase_ahead(); }
+    }
+}
+
+/// FIFO record di region flash `[base, base + size)`; `size` kelipatan sektor.
+/// Satu sektor selalu dibiarkan kosong supaya bisa dihapus sebelum ditulis.
+/// Record yang di-ack ditandai `ACKED`, jadi isi bisa dipulihkan setelah reset.
+pub struct FlashSpill<F> {
+    flash:     F,
+    base:      u32,
+    slots:     usize,
+    head:      usize,
+    tail:      usize,
+    len:       usize,
+    last_seq:  Option<u32>,
+    /// Sektor di `head` belum dihapus.
+    erase_due: bool,
+}
+
+impl<F: MultiwriteNorFlash> FlashSpill<F> {
+    const PER_SECTOR: usize = F::ERASE_SIZE / RECORD_LEN;
+
+    /// None kalau region kurang dari 2 sektor atau tidak sejajar sektor.
+    /// Isi region di-scan: record yang belum di-ack dipulihkan berurutan.
+    pub fn new(flash: F, base: u32, size: u32) -> Option<Self> {
+        let erase = F::ERASE_SIZE as u32;
+        if !base.is_multiple_of(erase) || !size.is_multiple_of(erase) || size < 2 * erase { return None; }
+        if !RECORD_LEN.is_multiple_of(F::WRITE_SIZE) || !(RECORD_LEN - 4).is_multiple_of(F::WRITE_SIZE) { return None; }
+        let slots = (size / erase) as usize * Self::PER_SECTOR;
+        let mut spill = Self { flash, base, slots, head: 0, tail: 0, len: 0, last_seq: None, erase_due: false };
+        spill.recover();
+        spill.erase_due = spill.head.is_multiple_of(Self::PER_SECTOR);
+        spill.erase_ahead();
+        Some(spill)
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.slots - Self::PER_SECTOR
+    }
+
+    fn addr(&self, slot: usize) -> u32 {
+        let sector = slot / Self::PER_SECTOR;
+        self.base + (sector * F::ERASE_SIZE + (slot % Self::PER_SECTOR) * RECORD_LEN) as u32
+    }
+
+    /// Slot → (record valid, sudah di-ack).
+    fn read_slot(&mut self, slot: usize) -> Option<(Record, bool)> {
+        let mut b = [0u8; RECORD_LEN];
+        self.flash.read(self.addr(slot), &mut b).ok()?;
+        Some((Record::decode(&b)?, b[RECORD_LEN - 1] != LIVE))
+    }
+
+    /// Head = setelah record dengan seq tertinggi; tail = mundur selama record
+    /// masih belum di-ack dan seq-nya turun.
+    fn recover(&mut self) {
+        let mut newest: Option<(usize, Record, bool)> = None;
+        for slot in 0..self.slots {
+            let Some((rec, acked)) = self.read_slot(slot) else { continue };
+            if newest.is_none_or(|(_, n, _)| rec.seq > n.seq) { newest = Some((slot, rec, acked)); }
+        }
+        let Some((slot, rec, acked)) = newest else { return };
+        self.last_seq = Some(rec.seq);
+        self.head = (slot + 1) % self.slots;
+        if acked { self.tail = self.head; return; }
+
+        let (mut len, mut seq) = (1, rec.seq);
+        while len < self.capacity() {
+            let prev = (slot + self.slots - len) % self.slots;
+            match self.read_slot(prev) {
+                Some((r, false)) if r.seq < seq => { seq = r.seq; len += 1; }
+                _ => break,
+            }
+        }
+        self.len = len;
+        self.tail = (self.head + self.slots - len) % self.slots;
+    }
+}
+
+impl<F: MultiwriteNorFlash> Spill for FlashSpill<F> {
+    fn push(&mut self, rec: &Record) -> bool {
+        if self.len >= self.capacity() { return false; }
+        // Normalnya sudah dihapus lewat `erase_ahead` di luar lock; ini cadangan
+        self.erase_ahead();
+        if self.erase_due { return false; }
+        if self.flash.write(self.addr(self.head), &rec.encode()).is_err() { return false; }
+        self.head = (self.head + 1) % self.slots;
+        self.len += 1;
+        self.last_seq = Some(rec.seq);
+        self.erase_due = self.head.is_multiple_of(Self::PER_SECTOR);
+        true
+    }
+
+    fn get(&mut self, idx: usize) -> Option<Record> {
+        if idx >= self.len { return None; }
+        let mut b = [0u8; RECORD_LEN];
+        self.flash.read(self.addr((self.tail + idx) % self.slots), &mut b).ok()?;
+        Record::decode(&b)
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn drop_front(&mut self, n: usize) {
+        for _ in 0..n.min(self.len) {
+            // Tandai di flash supaya tidak dikirim ulang setelah reset
+            let flag = [0xFF, 0xFF, 0xFF, ACKED];
+            let _ = self.flash.write(self.addr(self.tail) + (RECORD_LEN - 4) as u32, &flag);
+            self.tail = (self.tail + 1) % self.slots;
+            self.len -= 1;
+        }
+    }
+
+    fn last_seq(&self) -> Option<u32> {
+        self.last_seq
+    }
+
+    fn erase_due(&self) -> bool {
+        self.erase_due
+    }
+
+    /// Hapus sektor di `head` (±50 ms di ESP32).
+    fn erase_ahead(&mut self) {
+        if !self.erase_due { return; }
+        let from = self.addr(self.head);
+        self.erase_due = self.flash.erase(from, from + F::ERASE_SIZE as u32).is_err();
+    }
+}
+
+/// Antrian sample yang belum di-ack: [spill (tertua)] + [ring RAM (terbaru)].
+pub struct SampleBuffer<S, const N: usize> {
+    ram:       [Record; N],
+    /// Index record tertua di `ram`.
+    head:      usize,
+    len:       usize,
+    spill:     S,
+    next_seq:  u32,
+    /// Jumlah record di depan antrian yang sudah dikirim sejak `listen`.
+    sent:      usize,
+    listening: bool,
+    last_ack:  u64,
+    /// Sample yang terpaksa dibuang (RAM dan spill penuh).
+    pub dropped: u32,
+}
+
+impl<S: Spill, const N: usize> SampleBuffer<S, N> {
+    pub const fn new(spill: S) -> Self {
+        Self { ram: [EMPTY; N], head: 0, len: 0, spill, next_seq: 1, sent: 0, listening: false, last_ack: 0, dropped: 0 }
+    }
+
+    /// Seperti `new`, tapi seq dilanjutkan dari isi spill yang dipulihkan.
+    pub fn resume(spill: S) -> Self {
+        let next_seq = spill.last_seq().map_or(1, |s| s.wrapping_add(1));
+        Self { next_seq, ..Self::new(spill) }
+    }
+
+    /// Simpan sample baru; return seq-nya.
+    pub fn push(&mut self, t_ms: u64, epoch_ms: Option<u64>, sync: SyncQuality, measurement: Measurement) -> u32 {
+        let seq = self.next_seq;
+        self.next_seq = self.next_seq.wrapping_add(1);
+
+        if self.len == N {
+            // RAM penuh: yang tertua pindah ke spill, atau dibuang
+            let oldest = self.ram[self.head];
+            if !self.spill.push(&oldest) {
+                self.dropped = self.dropped.wrapping_add(1);
+                // Yang dibuang ada di belakang isi spill; kalau sudah terkirim, kursor ikut mundur
+                if self.sent > self.spill.len() { self.sent -= 1; }
+            }
+            self.head = (self.head + 1) % N;
+            self.len -= 1;
+        }
+        self.ram[(self.head + self.len) % N] = Record { seq, t_ms, epoch_ms, sync, measurement };
+        self.len += 1;
+        seq
+    }
+
+    /// Host siap menerima: kirim ulang semua yang belum di-ack.
+    pub fn listen(&mut self, now_ms: u64) {
+        self.listening = true;
+        self.sent = 0;
+        self.last_ack = now_ms;
+    }
+
+    /// Hapus semua record dengan seq ≤ `seq`; return jumlah yang dihapus.
+    pub fn ack(&mut self, seq: u32, now_ms: u64) -> usize {
+        self.last_ack = now_ms;
+        let mut removed = 0;
+        while !self.spill.is_empty() {
+            // Record spill yang rusak (CRC) tidak bisa dikirim lagi: ikut dibuang
+            if self.spill.get(0).is_some_and(|r| r.seq > seq) { break; }
+            self.spill.drop_front(1);
+            removed += 1;
+        }
+        if self.spill.is_empty() {
+            while self.len > 0 && self.ram[self.head].seq <= seq {
+                self.head = (self.head + 1) % N;
+                self.len -= 1;
+                removed += 1;
+            }
+        }
+        self.sent = self.sent.saturating_sub(removed);
+        removed
+    }
+
+    /// Record berikutnya untuk dikirim; None kalau host tidak mendengarkan
+    /// atau semua sudah terkirim.
+    pub fn next_to_send(&mut self, now_ms: u64) -> Option<Record> {
+        if !self.listening { return None; }
+        if self.sent > 0 && now_ms.saturating_sub(self.last_ack) > ACK_TIMEOUT_MS {
+            self.listening = false;
+            self.sent = 0;
+            return None;
+        }
+        let rec = self.get(self.sent)?;
+        self.sent += 1;
+        Some(rec)
+    }
+
+    fn get(&mut self, idx: usize) -> Option<Record> {
+        let spilled = self.spill.len();
+        if idx < spilled { return self.spill.get(idx); }
+        let idx = idx - spilled;
+        (idx < self.len).then(|| self.ram[(self.head + idx) % N])
+    }
+
+    pub fn is_listening(&self) -> bool {
+        self.listening
+    }
+
+    /// Jumlah record belum di-ack (RAM + spill).
+    pub fn pending(&self) -> usize {
+        self.len + self.spill.len()
+    }
+
+    pub fn spilled(&self) -> usize {
+        self.spill.len()
+    }
+
+    /// Spill perlu erase; jalankan lewat `take_spill` → `erase_ahead` → `restore_spill`
+    /// supaya erase tidak terjadi di dalam lock pemilik buffer.
+    pub fn spill_erase_due(&self) -> bool {
+        self.spill.erase_due()
+    }
+
+    /// Pinjam spill sementara; selama dipinjam jangan push/ack/kirim.
+    pub fn take_spill(&mut self) -> S
+    where
+        S: Default,
+    {
+        core::mem::take(&mut self.spill)
+    }
+
+    pub fn restore_spill(&mut self, spill: S) {
+        self.spill = spill;
+    }
+
+    /// Seq terakhir yang sudah dibuat (0 kalau belum ada sample).
+    pub fn last_seq(&self) -> u32 {
+        self.next_seq.wrapping_sub(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::RamFlash;
+
+    fn m(rh: f32) -> Measurement {
+        Measurement { rh: Some(rh), temp: Some(25.0) }
+    }
+
+    fn drain<S: Spill, const N: usize>(buf: &mut SampleBuffer<S, N>, now: u64) -> Vec<u32> {
+        core::iter::from_fn(|| buf.next_to_send(now)).map(|r| r.seq).collect()
+    }
+
+    #[test]
+    fn record_roundtrip_and_crc() {
+        let rec = Record { seq: 42, t_ms: 123_456, epoch_ms: Some(1_700_000_000_000), sync: SyncQuality::Fine,
+                           measurement: Measurement { rh: Some(55.3), temp: Some(-4.2) } };
+        assert_eq!(Record::decode(&rec.encode()), Some(rec));
+
+        let none = Record { measurement: Measurement::default(), epoch_ms: None, sync: SyncQuality::Unsynced, ..rec };
+        assert_eq!(Record::decode(&none.encode()), Some(none));
+
+        assert_eq!(rec.to_string(), "seq 42 ts 1700000000000 up 123456 rh 55.3 t -4.2 sync fine");
+        assert_eq!(none.to_string(), "seq 42 ts - up 123456 rh - t - sync unsynced");
+
+        let mut bad = rec.encode();
+        bad[21] ^= 1;
+        assert_eq!(Record::decode(&bad), None);
+        assert_eq!(Record::decode(&[0xFF; RECORD_LEN]), None);
+
+        // Bukan kelipatan 0.1 persis: record dari flash tercetak sama dengan dari RAM
+        let odd = Record { measurement: Measurement { rh: Some(55.38), temp: Some(-4.26) }, ..rec };
+        let spilled = Record::decode(&odd.encode()).unwrap();
+        assert_eq!(spilled.to_string(), odd.to_string());
+        assert_eq!(spilled.to_string(), "seq 42 ts 1700000000000 up 123456 rh 55.4 t -4.3 sync fine");
+    }
+
+    #[test]
+    fn nothing_is_sent_until_host_listens() {
+        let mut buf: SampleBuffer<Option<FlashSpill<RamFlash>>, 8> = SampleBuffer::new(None);
+        assert_eq!(buf.push(0, None, SyncQuality::Unsynced, m(50.0)), 1);
+        assert_eq!(buf.push(1000, None, SyncQuality::Unsynced, m(51.0)), 2);
+        assert_eq!(buf.next_to_send(1000), None);
+
+        buf.listen(2000);
+        assert_eq!(drain(&mut buf, 2000), [1, 2]);
+        buf.push(3000, None, SyncQuality::Unsynced, m(52.0));
+        assert_eq!(drain(&mut buf, 3000), [3]);
+    }
+
+    #[test]
+    fn ack_removes_and_relisten_replays_unacked_in_order() {
+        let mut buf: SampleBuffer<Option<FlashSpill<RamFlash>>, 8> = SampleBuffer::new(None);
+        for i in 0..5 { buf.push(i * 1000, None, SyncQuality::Unsynced, m(50.0)); }
+        buf.listen(5000);
+        assert_eq!(drain(&mut buf, 5000), [1, 2, 3, 4, 5]);
+
+        assert_eq!(buf.ack(3, 6000), 3);
+        assert_eq!(buf.pending(), 2);
+        // Host putus lalu tersambung lagi: 4 dan 5 dikirim ulang
+        buf.listen(7000);
+        assert_eq!(drain(&mut buf, 7000), [4, 5]);
+    }
+
+    #[test]
+    fn missing_ack_stops_streaming() {
+        let mut buf: SampleBuffer<Option<FlashSpill<RamFlash>>, 8> = SampleBuffer::new(None);
+        buf.listen(0);
+        buf.push(0, None, SyncQuality::Unsynced, m(50.0));
+        assert_eq!(drain(&mut buf, 0), [1]);
+
+        buf.push(1000, None, SyncQuality::Unsynced, m(50.0));
+        assert_eq!(buf.next_to_send(ACK_TIMEOUT_MS + 1), None);
+        assert!(!buf.is_listening());
+        buf.push(20_000, None, SyncQuality::Unsynced, m(50.0));
+        assert_eq!(buf.pending(), 3);
+    }
+
+    #[test]
+    fn full_ram_without_spill_drops_oldest() {
+        let mut buf: SampleBuffer<Option<FlashSpill<RamFlash>>, 4> = SampleBuffer::new(None);
+        for i in 0..6 { buf.push(i, None, SyncQuality::Unsynced, m(50.0)); }
+        assert_eq!(buf.dropped, 2);
+        buf.listen(10);
+        assert_eq!(drain(&mut buf, 10), [3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn full_ram_spills_to_flash_and_replays_in_order() {
+        let spill = FlashSpill::new(RamFlash::new(), 0, 768).unwrap();
+        assert_eq!(spill.capacity(), 2 * (256 / RECORD_LEN));
+        let mut buf: SampleBuffer<_, 4> = SampleBuffer::new(Some(spill));
+
+        for i in 0..20u64 { buf.push(i * 1000, Some(1_700_000_000_000 + i * 1000), SyncQuality::Fine, m(40.0 + i as f32)); }
+        assert_eq!(buf.spilled(), 16);
+        assert_eq!(buf.dropped, 0);
+
+        buf.listen(20_000);
+        let recs: Vec<Record> = core::iter::from_fn(|| buf.next_to_send(20_000)).collect();
+        assert_eq!(recs.iter().map(|r| r.seq).collect::<Vec<_>>(), (1..=20).collect::<Vec<_>>());
+        assert_eq!(recs[3].measurement.rh, Some(43.0));
+        assert_eq!(recs[3].epoch_ms, Some(1_700_000_003_000));
+
+        assert_eq!(buf.ack(18, 21_000), 18);
+        assert_eq!(buf.spilled(), 0);
+        assert_eq!(buf.pending(), 2);
+    }
+
+    #[test]
+    fn flash_spill_wraps_and_erases_sector_before_reuse() {
+        let mut spill = FlashSpill::new(RamFlash::new(), 0, 768).unwrap();
+        let cap = spill.capacity();
+        let rec = |seq| Record { seq, t_ms: 0, epoch_ms: None, sync: SyncQuality::Unsynced, measurement: m(50.0) };
+
+        for s in 0..cap as u32 { assert!(spill.push(&rec(s))); }
+        assert!(!spill.push(&rec(999)), "spill penuh harus menolak");
+
+        // Kosongkan separuh lalu isi lagi melewati ujung region
+        spill.drop_front(cap / 2);
+        for s in 0..(cap / 2) as u32 { assert!(spill.push(&rec(1000 + s))); }
+        assert_eq!(spill.get(0).unwrap().seq, (cap / 2) as u32);
+        assert_eq!(spill.get(cap - 1).unwrap().seq, 1000 + (cap / 2) as u32 - 1);
+        assert!(spill.flash.erases >= 3);
+    }
+
+    #[test]
+    fn flash_spill_recovers_unacked_records_and_seq_after_reset() {
+        let spill = FlashSpill::new(RamFlash::new(), 0, 768).unwrap();
+        let mut buf: SampleBuffer<_, 2> = SampleBuffer::resume(Some(spill));
+        for i in 0..12u64 { buf.push(i * 1000, None, SyncQuality::Unsynced, m(40.0 + i as f32)); }
+        assert_eq!(buf.spilled(), 10);
+        buf.listen(12_000);
+        assert_eq!(buf.ack(4, 12_000), 4);
+
+        // Reset: RAM hilang, flash tetap
+        let flash = buf.take_spill().unwrap().flash;
+        let spill = FlashSpill::new(flash, 0, 768).unwrap();
+        assert_eq!((spill.len(), spill.last_seq()), (6, Some(10)));
+        let mut buf: SampleBuffer<_, 2> = SampleBuffer::resume(Some(spill));
+        assert_eq!(buf.push(20_000, None, SyncQuality::Unsynced, m(60.0)), 11);
+        buf.listen(20_000);
+        assert_eq!(drain(&mut buf, 20_000), [5, 6, 7, 8, 9, 10, 11]);
+
+        // Semua di-ack: setelah reset tidak ada yang dikirim ulang, seq tetap lanjut
+        buf.ack(11, 21_000);
+        let flash = buf.take_spill().unwrap().flash;
+        let mut buf: SampleBuffer<_, 2> = SampleBuffer::resume(FlashSpill::new(flash, 0, 768));
+        assert_eq!(buf.pending(), 0);
+        assert_eq!(buf.push(22_000, None, SyncQuality::Unsynced, m(60.0)), 11);
+    }
+
+    #[test]
+    fn flash_spill_erases_next_sector_ahead_of_push() {
+        let mut spill = FlashSpill::new(RamFlash::new(), 0, 768).unwrap();
+        let rec = |seq| Record { seq, t_ms: 0, epoch_ms: None, sync: SyncQuality::Unsynced, measurement: m(50.0) };
+        assert_eq!((spill.flash.erases, spill.erase_due()), (1, false));
+
+        let per_sector = 256 / RECORD_LEN;
+        for s in 0..per_sector as u32 { assert!(spill.push(&rec(s))); }
+        assert!(spill.erase_due());
+        spill.erase_ahead();
+        assert_eq!((spill.flash.erases, spill.erase_due()), (2, false));
+        // Push berikutnya tidak perlu erase lagi
+        assert!(spill.push(&rec(100)));
+        assert_eq!(spill.flash.erases, 2);
+    }
+
+    #[test]
+    fn flash_spill_rejects_unaligned_region() {
+        assert!(FlashSpill::new(RamFlash::new(), 4, 512).is_none());
+        assert!(FlashSpill::new(RamFlash::new(), 0, 256).is_none());
+    }
+}
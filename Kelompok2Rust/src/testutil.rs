@@ -0,0 +1,54 @@
+//! Mock hardware untuk unit test lib.
+
+use embedded_storage::nor_flash::{ErrorType, MultiwriteNorFlash, NorFlash, NorFlashErrorKind, ReadNorFlash};
+
+/// Flash RAM: 3 sektor @ 256 byte, tulis hanya boleh 1 → 0 seperti NOR asli.
+pub struct RamFlash {
+    pub mem:    [u8; 768],
+    pub erases: u32,
+}
+
+impl RamFlash {
+    pub fn new() -> Self {
+        Self { mem: [0xAA; 768], erases: 0 }
+    }
+}
+
+impl ErrorType for RamFlash {
+    type Error = NorFlashErrorKind;
+}
+
+impl ReadNorFlash for RamFlash {
+    const READ_SIZE: usize = 1;
+
+    fn read(&mut self, offset: u32, bytes: &mut [u8]) -> Result<(), Self::Error> {
+        let o = offset as usize;
+        bytes.copy_from_slice(self.mem.get(o..o + bytes.len()).ok_or(NorFlashErrorKind::OutOfBounds)?);
+        Ok(())
+    }
+
+    fn capacity(&self) -> usize {
+        self.mem.len()
+    }
+}
+
+impl NorFlash for RamFlash {
+    const WRITE_SIZE: usize = 4;
+    const ERASE_SIZE: usize = 256;
+
+    fn erase(&mut self, from: u32, to: u32) -> Result<(), Self::Error> {
+        self.mem[from as usize..to as usize].fill(0xFF);
+        self.erases += 1;
+        Ok(())
+    }
+
+    fn write(&mut self, offset: u32, bytes: &[u8]) -> Result<(), Self::Error> {
+        for (m, b) in self.mem[offset as usize..].iter_mut().zip(bytes) {
+            *m &= *b;
+        }
+        Ok(())
+    }
+}
+
+// Tulis ulang tanpa erase aman: bit hanya bisa 1 → 0
+impl MultiwriteNorFlash for RamFlash {}
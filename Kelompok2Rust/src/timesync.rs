@@ -0,0 +1,207 @@
+//! Sinkronisasi waktu dari host: host mengirim epoch (ms), firmware menyimpan
+//! offset epoch ↔ monotonic dan mengestimasi drift kristal antar sync, lalu
+//! tiap sample dicap epoch saat akuisisi + indikator kualitas sync.
+
+use core::fmt;
+
+/// Jarak minimal antar sync supaya drift dihitung (jitter serial ~ms tidak dominan).
+pub const MIN_DRIFT_WINDOW_MS: u64 = 30_000;
+/// Drift di atas ini dianggap jam host lompat (bukan drift kristal) → diabaikan.
+pub const MAX_DRIFT_PPM:       f32 = 1000.0;
+/// Tanpa sync selama ini → kualitas `Stale`.
+pub const SYNC_STALE_MS:       u64 = 600_000;
+/// Bobot estimasi drift terbaru (rata-rata eksponensial).
+const DRIFT_ALPHA: f32 = 0.25;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncQuality {
+    /// Belum pernah sync: epoch tidak tersedia.
+    Unsynced,
+    /// Baru offset, drift belum diketahui.
+    Coarse,
+    /// Offset + drift terkoreksi, sync terakhir masih baru.
+    Fine,
+    /// Sync terakhir lebih lama dari `SYNC_STALE_MS`.
+    Stale,
+}
+
+/// Hasil satu sync.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SyncReport {
+    /// Epoch host dikurangi prediksi firmware sebelum sync (None pada sync pertama).
+    pub error_ms:  Option<i64>,
+    pub drift_ppm: Option<f32>,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TimeSync {
+    /// (monotonic ms, epoch ms) sync terakhir: dasar konversi epoch.
+    last:      Option<(u64, u64)>,
+    /// Awal jendela estimasi drift (berpindah tiap drift dihitung).
+    window:    Option<(u64, u64)>,
+    drift_ppm: Option<f32>,
+    syncs:     u32,
+}
+
+impl TimeSync {
+    pub const fn new() -> Self {
+        Self { last: None, window: None, drift_ppm: None, syncs: 0 }
+    }
+
+    /// Host melaporkan `epoch_ms`; `mono_ms` = jam monotonic saat pesan diterima.
+    pub fn sync(&mut self, epoch_ms: u64, mono_ms: u64) -> SyncReport {
+        let error_ms = self.epoch_ms(mono_ms).map(|p| epoch_ms as i64 - p as i64);
+
+        match self.window {
+            Some((m0, e0)) if mono_ms.saturating_sub(m0) >= MIN_DRIFT_WINDOW_MS => {
+                let dm = mono_ms - m0;
+                let de = epoch_ms as i64 - e0 as i64;
+                let ppm = (de - dm as i64) as f32 * 1e6 / dm as f32;
+                if ppm.abs() <= MAX_DRIFT_PPM {
+                    self.drift_ppm = Some(match self.drift_ppm {
+                        Some(d) => d + DRIFT_ALPHA * (ppm - d),
+                        None => ppm,
+                    });
+                }
+                self.window = Some((mono_ms, epoch_ms));
+            }
+            Some(_) => {} // terlalu rapat: jendela drift diteruskan
+            None => self.window = Some((mono_ms, epoch_ms)),
+        }
+        self.last = Some((mono_ms, epoch_ms));
+        self.syncs = self.syncs.wrapping_add(1);
+        SyncReport { error_ms, drift_ppm: self.drift_ppm }
+    }
+
+    /// Epoch (ms) untuk waktu monotonic `mono_ms`; None sebelum sync pertama.
+    pub fn epoch_ms(&self, mono_ms: u64) -> Option<u64> {
+        let (m0, e0) = self.last?;
+        let dm = mono_ms as i64 - m0 as i64;
+        let corr = self.drift_ppm.map_or(0, |ppm| (dm as f32 * ppm / 1e6) as i64);
+        Some((e0 as i64 + dm + corr).max(0) as u64)
+    }
+
+    /// Offset epoch − monotonic saat sync terakhir.
+    pub fn offset_ms(&self) -> Option<i64> {
+        self.last.map(|(m0, e0)| e0 as i64 - m0 as i64)
+    }
+
+    pub fn drift_ppm(&self) -> Option<f32> {
+        self.drift_ppm
+    }
+
+    pub fn syncs(&self) -> u32 {
+        self.syncs
+    }
+
+    pub fn age_ms(&self, mono_ms: u64) -> Option<u64> {
+        self.last.map(|(m0, _)| mono_ms.saturating_sub(m0))
+    }
+
+    pub fn quality(&self, mono_ms: u64) -> SyncQuality {
+        match (self.age_ms(mono_ms), self.drift_ppm) {
+            (None, _) => SyncQuality::Unsynced,
+            (Some(age), _) if age > SYNC_STALE_MS => SyncQuality::Stale,
+            (Some(_), None) => SyncQuality::Coarse,
+            (Some(_), Some(_)) => SyncQuality::Fine,
+        }
+    }
+}
+
+/// Tanggal UTC (tahun, bulan, hari) dari epoch ms (algoritma civil_from_days).
+pub fn civil_date(epoch_ms: u64) -> (u32, u8, u8) {
+    let z = (epoch_ms / 86_400_000) as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let y = yoe + era * 400 + i64::from(m <= 2);
+    (y as u32, m as u8, d as u8)
+}
+
+impl fmt::Display for SyncQuality {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SyncQuality::Unsynced => "unsynced",
+            SyncQuality::Coarse   => "coarse",
+            SyncQuality::Fine     => "fine",
+            SyncQuality::Stale    => "stale",
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPOCH: u64 = 1_700_000_000_000;
+
+    #[test]
+    fn unsynced_has_no_epoch() {
+        let ts = TimeSync::new();
+        assert_eq!(ts.epoch_ms(1234), None);
+        assert_eq!(ts.quality(1234), SyncQuality::Unsynced);
+    }
+
+    #[test]
+    fn first_sync_sets_offset() {
+        let mut ts = TimeSync::new();
+        let r = ts.sync(EPOCH, 5_000);
+        assert_eq!(r, SyncReport { error_ms: None, drift_ppm: None });
+        assert_eq!(ts.offset_ms(), Some(EPOCH as i64 - 5_000));
+        assert_eq!(ts.epoch_ms(6_500), Some(EPOCH + 1_500));
+        assert_eq!(ts.quality(6_500), SyncQuality::Coarse);
+    }
+
+    #[test]
+    fn drift_is_estimated_and_corrected() {
+        // Jam lokal 100 ppm lebih lambat dari host
+        let mut ts = TimeSync::new();
+        ts.sync(EPOCH, 0);
+        let r = ts.sync(EPOCH + 60_006, 60_000);
+        assert_eq!(r.error_ms, Some(6));
+        assert!((r.drift_ppm.unwrap() - 100.0).abs() < 1.0);
+        assert_eq!(ts.quality(60_000), SyncQuality::Fine);
+
+        // Prediksi berikutnya sudah memperhitungkan drift
+        assert_eq!(ts.epoch_ms(120_000), Some(EPOCH + 120_012));
+        let r = ts.sync(EPOCH + 120_012, 120_000);
+        assert_eq!(r.error_ms, Some(0));
+    }
+
+    #[test]
+    fn close_syncs_do_not_estimate_drift() {
+        let mut ts = TimeSync::new();
+        ts.sync(EPOCH, 0);
+        ts.sync(EPOCH + 1_050, 1_000); // latency host 50 ms
+        assert_eq!(ts.drift_ppm(), None);
+        assert_eq!(ts.epoch_ms(2_000), Some(EPOCH + 2_050));
+    }
+
+    #[test]
+    fn host_clock_jump_is_not_taken_as_drift() {
+        let mut ts = TimeSync::new();
+        ts.sync(EPOCH, 0);
+        ts.sync(EPOCH + 3_660_000, 60_000); // jam host maju 1 jam
+        assert_eq!(ts.drift_ppm(), None);
+        assert_eq!(ts.epoch_ms(60_000), Some(EPOCH + 3_660_000));
+    }
+
+    #[test]
+    fn civil_date_from_epoch() {
+        assert_eq!(civil_date(0), (1970, 1, 1));
+        assert_eq!(civil_date(EPOCH), (2023, 11, 14));
+        assert_eq!(civil_date(1_709_164_800_000), (2024, 2, 29));
+    }
+
+    #[test]
+    fn quality_goes_stale() {
+        let mut ts = TimeSync::new();
+        ts.sync(EPOCH, 0);
+        assert_eq!(ts.quality(SYNC_STALE_MS), SyncQuality::Coarse);
+        assert_eq!(ts.quality(SYNC_STALE_MS + 1), SyncQuality::Stale);
+    }
+}
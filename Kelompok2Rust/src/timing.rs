@@ -0,0 +1,100 @@
+//! Statistik waktu: durasi scan (min/avg/max, overrun, histogram) dan
+//! periode loop berkala (jitter). Semua dalam µs, tanpa alokasi.
+
+pub const BUCKETS: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimingStats {
+    /// Sample di atas ini dihitung overrun.
+    budget_us: u32,
+    /// Batas atas bucket histogram (bucket terakhir = sisanya).
+    edges:     [u32; BUCKETS - 1],
+    hist:      [u32; BUCKETS],
+    min_us:    u32,
+    max_us:    u32,
+    sum_us:    u64,
+    n:         u32,
+    overruns:  u32,
+}
+
+impl TimingStats {
+    /// `edges` harus naik.
+    pub const fn new(budget_us: u32, edges: [u32; BUCKETS - 1]) -> Self {
+        Self { budget_us, edges, hist: [0; BUCKETS], min_us: 0, max_us: 0, sum_us: 0, n: 0, overruns: 0 }
+    }
+
+    /// Catat satu sample; true kalau overrun.
+    pub fn record(&mut self, us: u32) -> bool {
+        if self.n == 0 || us < self.min_us { self.min_us = us; }
+        if us > self.max_us { self.max_us = us; }
+        self.sum_us += us as u64;
+        self.n = self.n.saturating_add(1);
+        let bucket = self.edges.iter().position(|&e| us < e).unwrap_or(BUCKETS - 1);
+        self.hist[bucket] = self.hist[bucket].saturating_add(1);
+        let over = us > self.budget_us;
+        if over { self.overruns = self.overruns.saturating_add(1); }
+        over
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::new(self.budget_us, self.edges);
+    }
+
+    pub fn count(&self) -> u32 {
+        self.n
+    }
+
+    pub fn overruns(&self) -> u32 {
+        self.overruns
+    }
+
+    pub fn budget_us(&self) -> u32 {
+        self.budget_us
+    }
+
+    pub fn min_us(&self) -> Option<u32> {
+        (self.n > 0).then_some(self.min_us)
+    }
+
+    pub fn max_us(&self) -> Option<u32> {
+        (self.n > 0).then_some(self.max_us)
+    }
+
+    pub fn avg_us(&self) -> Option<u32> {
+        (self.n > 0).then(|| (self.sum_us / self.n as u64) as u32)
+    }
+
+    /// Jitter puncak-ke-puncak (max − min).
+    pub fn spread_us(&self) -> Option<u32> {
+        (self.n > 0).then_some(self.max_us - self.min_us)
+    }
+
+    /// (batas atas bucket, jumlah); batas None = bucket terakhir (≥ edge terakhir).
+    pub fn histogram(&self) -> impl Iterator<Item = (Option<u32>, u32)> + '_ {
+        self.hist.iter().enumerate().map(|(i, &c)| (self.edges.get(i).copied(), c))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn min_avg_max_overrun_and_buckets() {
+        let mut s = TimingStats::new(50, [10, 20, 30, 40, 50, 60, 70]);
+        assert_eq!((s.min_us(), s.avg_us(), s.spread_us()), (None, None, None));
+        for us in [5, 15, 15, 55, 100] { s.record(us); }
+        assert_eq!((s.min_us(), s.avg_us(), s.max_us()), (Some(5), Some(38), Some(100)));
+        assert_eq!((s.count(), s.overruns(), s.spread_us()), (5, 2, Some(95)));
+
+        let hist: Vec<_> = s.histogram().collect();
+        assert_eq!(hist[0], (Some(10), 1));
+        assert_eq!(hist[1], (Some(20), 2));
+        assert_eq!(hist[5], (Some(60), 1));
+        assert_eq!(hist[7], (None, 1));
+
+        s.reset();
+        assert_eq!((s.count(), s.budget_us()), (0, 50));
+        assert!(s.histogram().all(|(_, c)| c == 0));
+    }
+}
@@ -0,0 +1,431 @@
+//! Skenario lengkap di host: App + SHT20 simulasi + model ruangan.
+
+use hello_rust::app::{App, Sample};
+use hello_rust::control::ActuatorCmd;
+use hello_rust::hal::{Clock, DigitalOutput, ModbusPort, Servo};
+use hello_rust::lifecycle::{Command, FaultCause, Phase, Rejected, PURGE_OUTPUTS, SAFE_OUTPUTS, SENSOR_LOSS_MS};
+use hello_rust::modbus::discover::{scan, ScanConfig, ScanEvent};
+use hello_rust::modbus::{Master, ModbusError, FC_READ_COILS, FC_READ_DISCRETE, FC_READ_INPUT};
+use hello_rust::remote::{poll_inputs, sync_coils, CoilImage, InputImage, IoPoint, INPUT_STALE_FAILS};
+use hello_rust::servo::deg_to_pulse_us;
+use hello_rust::sim::{block_on, Room, SensorFault, SimWorld};
+use hello_rust::timesync::SyncQuality;
+
+const SID: u8 = 1;
+
+/// Jalankan sampai `stop` true atau `max_s` habis; return semua sample.
+fn run<P, F, S, C>(
+    app: &mut App<P, F, S, C>,
+    world: &SimWorld,
+    max_s: u64,
+    mut stop: impl FnMut(&Sample) -> bool,
+) -> Vec<Sample>
+where
+    P: ModbusPort,
+    F: DigitalOutput,
+    S: Servo,
+    C: Clock + Clone,
+{
+    let mut samples = Vec::new();
+    while world.now_ms() <= max_s * 1000 {
+        if let Some(s) = block_on(app.step()) {
+            samples.push(s);
+            if stop(&s) { break; }
+        }
+        world.advance(100);
+    }
+    samples
+}
+
+#[test]
+fn rh_rising_past_80_turns_fan_on() {
+    let world = SimWorld::new(Room { moisture_in: 0.5, ..Room::default() });
+    let mut app = App::new(world.sensor(SID), SID, world.fan(), world.servo(), world.clock());
+
+    let samples = run(&mut app, &world, 600, |s| s.cmd.fan_on);
+    let last = samples.last().unwrap();
+
+    assert!(last.cmd.fan_on, "kipas tidak pernah ON");
+    assert!(last.measurement.rh.unwrap() > 80.0);
+    assert!(world.fan_on());
+    assert_eq!(world.servo_us(), deg_to_pulse_us(120));
+    // Sebelum lewat 80 %, kipas tetap OFF
+    assert!(samples[..samples.len() - 1].iter().all(|s| !s.cmd.fan_on));
+}
+
+#[test]
+fn fan_pulls_rh_back_down() {
+    let world = SimWorld::new(Room { moisture_in: 0.5, ..Room::default() });
+    let mut app = App::new(world.sensor(SID), SID, world.fan(), world.servo(), world.clock());
+
+    run(&mut app, &world, 600, |s| s.cmd.fan_on);
+    let samples = run(&mut app, &world, 900, |s| !s.cmd.fan_on);
+
+    assert!(!samples.last().unwrap().cmd.fan_on);
+    assert!(samples.last().unwrap().measurement.rh.unwrap() < 80.0);
+}
+
+#[test]
+fn dry_room_keeps_servo_closed_and_fan_off() {
+    let world = SimWorld::new(Room { rh: 45.0, ..Room::default() });
+    let mut app = App::new(world.sensor(SID), SID, world.fan(), world.servo(), world.clock());
+
+    let samples = run(&mut app, &world, 60, |_| false);
+
+    assert!(samples.len() >= 60);
+    assert!(samples.iter().all(|s| s.cmd.servo_deg == 0 && !s.cmd.fan_on));
+    assert_eq!(world.servo_us(), deg_to_pulse_us(0));
+}
+
+#[test]
+fn sensor_offline_holds_last_outputs_then_faults() {
+    let world = SimWorld::new(Room { moisture_in: 0.5, ..Room::default() });
+    let mut app = App::new(world.sensor(SID), SID, world.fan(), world.servo(), world.clock());
+
+    let last_ok_ms = run(&mut app, &world, 600, |s| s.cmd.fan_on).last().unwrap().t_ms;
+    world.set_sensor_fault(SensorFault::Offline);
+    let samples = run(&mut app, &world, 700, |_| false);
+    assert!(samples.iter().all(|s| s.measurement.rh.is_none() && s.measurement.temp.is_none()));
+
+    // Gangguan singkat: output terakhir ditahan
+    let (held, faulted): (Vec<&Sample>, Vec<&Sample>) = samples.iter()
+        .partition(|s| s.t_ms < last_ok_ms + SENSOR_LOSS_MS);
+    assert!(!held.is_empty() && !faulted.is_empty());
+    assert!(held.iter().all(|s| s.phase == Phase::Run && s.cmd.fan_on && s.cmd.servo_deg == 120));
+    // Lebih lama: FAULT dengan output aman
+    assert!(faulted.iter().all(|s| s.phase == Phase::Fault && s.cmd == SAFE_OUTPUTS));
+    assert_eq!(app.lifecycle().fault(), Some(FaultCause::SensorLost));
+    assert!(!world.fan_on());
+}
+
+#[test]
+fn stop_purges_then_returns_to_standby() {
+    let world = SimWorld::new(Room { rh: 65.0, ..Room::default() });
+    let mut app = App::new(world.sensor(SID), SID, world.fan(), world.servo(), world.clock());
+
+    let samples = run(&mut app, &world, 10, |_| false);
+    assert_eq!(samples[0].phase, Phase::Standby);
+    assert_eq!(samples.last().unwrap().phase, Phase::Run);
+    assert_eq!(samples.last().unwrap().cmd.servo_deg, 60);
+
+    assert_eq!(app.command(Command::Stop).map(|t| t.to), Ok(Phase::Shutdown));
+    let samples = run(&mut app, &world, 30, |s| s.phase != Phase::Shutdown);
+    assert!(samples[..samples.len() - 1].iter().all(|s| s.cmd == PURGE_OUTPUTS));
+    assert_eq!(samples.last().unwrap().phase, Phase::Standby);
+    assert_eq!(app.command(Command::Reset), Err(Rejected::WrongPhase(Phase::Standby)));
+
+    // Walau auto-start aktif, unit tetap STANDBY sampai operator `start`
+    let samples = run(&mut app, &world, 60, |_| false);
+    assert!(samples.len() > 30);
+    assert!(samples.iter().all(|s| s.phase == Phase::Standby && s.cmd == SAFE_OUTPUTS));
+    assert_eq!(app.command(Command::Start).map(|t| t.to), Ok(Phase::Run));
+}
+
+#[test]
+fn over_temperature_interlock_overrides_purge() {
+    let world = SimWorld::new(Room { rh: 65.0, temp: 47.0, ..Room::default() });
+    let mut app = App::new(world.sensor(SID), SID, world.fan(), world.servo(), world.clock());
+
+    let samples = run(&mut app, &world, 10, |s| s.phase == Phase::Run);
+    assert_eq!(samples.last().unwrap().phase, Phase::Run);
+
+    // Purge minta kipas ON + damper buka, tapi `fan off if t > 45` tetap berlaku
+    app.command(Command::Stop).unwrap();
+    let samples = run(&mut app, &world, 30, |s| s.phase != Phase::Shutdown);
+    let purge = &samples[..samples.len() - 1];
+    assert!(!purge.is_empty());
+    assert!(purge.iter().all(|s| s.cmd == ActuatorCmd { fan_on: false, ..PURGE_OUTPUTS }));
+    assert!(!world.fan_on());
+}
+
+#[test]
+fn wrong_slave_id_gets_no_reply() {
+    let world = SimWorld::new(Room::default());
+    let mut app = App::new(world.sensor(SID + 1), SID, world.fan(), world.servo(), world.clock());
+
+    let s = block_on(app.step()).unwrap();
+    assert_eq!(s.measurement.rh, None);
+    assert_eq!(app.bus_diag().slave(SID).unwrap().timeouts, 2);
+}
+
+#[test]
+fn bus_diagnostics_classify_each_fault() {
+    let world = SimWorld::new(Room::default());
+    let mut app = App::new(world.sensor(SID), SID, world.fan(), world.servo(), world.clock());
+
+    // Tiap step = 2 request (RH + T); slave menjawab 3 ms setelah request
+    world.set_sensor_reply_ms(3);
+    for fault in [SensorFault::None, SensorFault::Offline, SensorFault::BadCrc,
+                  SensorFault::WrongSlaveId, SensorFault::Truncated] {
+        world.set_sensor_fault(fault);
+        block_on(app.step()).unwrap();
+        world.advance(1000);
+    }
+    // Slave melambat: 8 ms
+    world.set_sensor_fault(SensorFault::None);
+    world.set_sensor_reply_ms(8);
+    block_on(app.step()).unwrap();
+
+    let c = app.bus_diag().slave(SID).unwrap();
+    assert_eq!(c.requests, 12);
+    assert_eq!(c.ok, 4);
+    assert_eq!(c.timeouts, 2);
+    assert_eq!(c.crc_errors, 2);
+    assert_eq!(c.unexpected_id, 2);
+    assert_eq!(c.short_frames, 2);
+    // Latency hanya dari 4 reply valid (CRC rusak / ID lain / terpotong tidak dihitung)
+    assert_eq!(c.latency_min_us(), Some(3_000));
+    assert_eq!(c.latency_max_us(), Some(8_000));
+    assert_eq!(c.latency_avg_us(), Some(5_500));
+
+    // Jumlah register di luar batas: ditolak tanpa transaksi
+    let mut bus = Master::new(world.sensor(SID), world.clock());
+    let mut regs = [0u16; 17];
+    assert_eq!(block_on(bus.read_registers(SID, FC_READ_INPUT, 0, &mut regs)), Err(ModbusError::InvalidRequest));
+    assert_eq!(block_on(bus.read_registers(SID, FC_READ_INPUT, 0, &mut [])), Err(ModbusError::InvalidRequest));
+    assert_eq!(bus.diag().slave(SID), None);
+}
+
+#[test]
+fn over_temperature_interlock_overrides_controller() {
+    let world = SimWorld::new(Room { moisture_in: 0.5, temp: 47.0, ..Room::default() });
+    let mut app = App::new(world.sensor(SID), SID, world.fan(), world.servo(), world.clock());
+
+    // Controller ingin kipas ON di atas 80 %, tapi aturan bawaan `fan off if t > 45` menang
+    let samples = run(&mut app, &world, 600, |s| s.measurement.rh.is_some_and(|rh| rh > 85.0));
+    assert!(samples.last().unwrap().measurement.rh.unwrap() > 85.0);
+    assert!(samples.iter().all(|s| !s.cmd.fan_on));
+    assert!(!world.fan_on());
+    assert!(app.interlocks().state(0).active);
+
+    app.interlocks_mut().get_mut(0).unwrap().bypassed = true;
+    run(&mut app, &world, 620, |s| s.cmd.fan_on);
+    assert!(world.fan_on());
+}
+
+#[test]
+fn samples_are_stamped_with_host_epoch() {
+    const EPOCH: u64 = 1_700_000_000_000;
+    let world = SimWorld::new(Room::default());
+    let mut app = App::new(world.sensor(SID), SID, world.fan(), world.servo(), world.clock());
+
+    let s = run(&mut app, &world, 2, |_| false);
+    assert!(s.iter().all(|s| s.epoch_ms.is_none() && s.sync == SyncQuality::Unsynced));
+
+    // Host sync tiap 60 s; jam host 200 ppm lebih cepat dari jam simulasi
+    let mut samples = Vec::new();
+    for k in 1..=3u64 {
+        let mono = world.now_ms();
+        app.sync_time(EPOCH + mono + mono / 5_000);
+        samples = run(&mut app, &world, 2 + k * 60, |_| false);
+        assert!(samples.iter().all(|s| s.epoch_ms.is_some()));
+    }
+    let last = samples.last().unwrap();
+    assert_eq!(last.sync, SyncQuality::Fine);
+    let expected = EPOCH + last.t_ms + last.t_ms / 5_000;
+    assert!(last.epoch_ms.unwrap().abs_diff(expected) <= 2, "{:?} vs {}", last.epoch_ms, expected);
+}
+
+#[test]
+fn two_point_calibration_corrects_biased_sensor() {
+    use hello_rust::calib::{Calibration, Channel};
+
+    // Sensor membaca RH terlalu rendah: 0.9 * RH + 2 (85 % terbaca 78.5 %)
+    let world = SimWorld::new(Room { rh: 50.0, ambient_rh: 50.0, leak_rate: 0.0, ..Room::default() });
+    world.set_sensor_rh_error(0.9, 2.0);
+    let mut app = App::new(world.sensor(SID), SID, world.fan(), world.servo(), world.clock());
+
+    let low = run(&mut app, &world, 5, |_| false).last().unwrap().raw.rh.unwrap();
+    world.room.borrow_mut().rh = 85.0;
+    world.room.borrow_mut().ambient_rh = 85.0;
+    let s = *run(&mut app, &world, 10, |_| false).last().unwrap();
+    assert!(!s.cmd.fan_on, "tanpa kalibrasi 78.5 % belum memicu kipas");
+
+    let cal = Calibration::two_point(Channel::Rh, (low, 50.0), (s.raw.rh.unwrap(), 85.0), 0).unwrap();
+    app.calibration_mut().rh = cal;
+    let s = *run(&mut app, &world, 15, |_| false).last().unwrap();
+
+    assert!((s.measurement.rh.unwrap() - 85.0).abs() < 0.2);
+    assert!((s.raw.rh.unwrap() - 78.5).abs() < 0.2);
+    assert!(s.cmd.fan_on);
+}
+
+#[test]
+fn setpoint_profile_step_turns_fan_on_then_abort_restores() {
+    use hello_rust::setpoint::{Segment, End};
+
+    // RH ruangan tetap 62 %: setpoint bawaan 65 → servo 60°, kipas OFF
+    let world = SimWorld::new(Room { rh: 62.0, ambient_rh: 62.0, leak_rate: 0.0, ..Room::default() });
+    let mut app = App::new(world.sensor(SID), SID, world.fan(), world.servo(), world.clock());
+    let s = *run(&mut app, &world, 5, |_| false).last().unwrap();
+    assert_eq!((s.cmd.servo_deg, s.cmd.fan_on), (60, false));
+
+    // Profil: tahan 10 s, lalu step ke 40 % (kipas ON di atas 55 %) selama 30 s
+    let sp = app.setpoint_mut();
+    sp.profile.push(Segment { duration_ms: 10_000, target: 65.0 });
+    sp.profile.push(Segment { duration_ms: 0, target: 40.0 });
+    sp.profile.push(Segment { duration_ms: 30_000, target: 40.0 });
+    sp.profile.end = End::Hold;
+    sp.start().unwrap();
+
+    let samples = run(&mut app, &world, 30, |_| false);
+    let first_on = samples.iter().find(|s| s.cmd.fan_on).expect("kipas tidak pernah ON");
+    assert_eq!(first_on.setpoint, 40.0);
+    assert!(first_on.t_ms >= 15_000);
+    assert!(samples.iter().filter(|s| s.t_ms < 15_000).all(|s| !s.cmd.fan_on));
+
+    app.setpoint_mut().abort().unwrap();
+    let s = *run(&mut app, &world, 35, |_| false).last().unwrap();
+    assert_eq!(s.setpoint, 65.0);
+    assert!(!s.cmd.fan_on);
+}
+
+#[test]
+fn servo_calibration_limits_travel_and_parks_in_standby() {
+    use hello_rust::servo::ServoCal;
+
+    // RH tinggi → controller minta 120°, tapi batas lunak 90°; servo terpasang terbalik
+    let world = SimWorld::new(Room { rh: 75.0, ambient_rh: 75.0, ..Room::default() });
+    let mut app = App::new(world.sensor(SID), SID, world.fan(), world.servo(), world.clock());
+    let cal = ServoCal { min_us: 600, max_us: 2400, inverted: true, limit_hi: 90, park_deg: 20, ..ServoCal::DEFAULT };
+    app.set_servo_cal(cal).unwrap();
+    assert!(app.set_servo_cal(ServoCal { park_deg: 100, ..cal }).is_err());
+
+    let s = *run(&mut app, &world, 5, |_| false).last().unwrap();
+    assert_eq!((s.phase, s.cmd.servo_deg), (Phase::Run, 120));
+    assert_eq!(world.servo_us(), cal.pulse_us(90));
+    assert_eq!(world.servo_us(), 600 + 30 * 1800 / 120);
+
+    // Stop → purge → STANDBY: servo ke posisi park
+    app.command(Command::Stop).unwrap();
+    let s = *run(&mut app, &world, 30, |s| s.phase == Phase::Standby).last().unwrap();
+    assert_eq!(s.cmd.servo_deg, 20);
+    assert_eq!(world.servo_us(), cal.pulse_us(20));
+}
+
+#[test]
+fn power_on_self_test_strokes_outputs_and_blocks_boot_on_dead_sensor() {
+    use hello_rust::post::{Check, Outcome, PostPlan};
+
+    let plan = PostPlan { servo_stroke: true, fan_pulse: true, fan_feedback: false, critical: [false, true, false, false] };
+
+    // Sensor mati saat boot: check lain tetap jalan, lalu FAULT (bukan STANDBY)
+    let world = SimWorld::new(Room::default());
+    world.set_sensor_fault(SensorFault::Offline);
+    let mut app = App::new(world.sensor(SID), SID, world.fan(), world.servo(), world.clock());
+    app.enable_post(plan, Outcome::Pass);
+    let samples = run(&mut app, &world, 60, |s| s.phase == Phase::Fault);
+    assert_eq!(app.lifecycle().fault(), Some(FaultCause::SelfTest(Check::Sensor)));
+    assert_eq!(samples.last().unwrap().cmd, SAFE_OUTPUTS);
+    assert!(!samples.iter().any(|s| s.phase == Phase::Standby));
+
+    // Sensor tersambung lagi, `reset` → POST ulang: stroke servo + pulsa kipas, lalu RUN
+    world.set_sensor_fault(SensorFault::None);
+    app.command(Command::Reset).unwrap();
+    let samples = run(&mut app, &world, 120, |s| s.phase == Phase::Run);
+    let post: Vec<&Sample> = samples.iter().filter(|s| s.phase == Phase::SelfTest).collect();
+    assert!(post.iter().any(|s| s.cmd.servo_deg == 120 && !s.cmd.fan_on));
+    assert!(post.iter().any(|s| s.cmd.fan_on));
+    assert_eq!(samples.last().unwrap().phase, Phase::Run);
+
+    let report = app.post_report().unwrap();
+    assert_eq!(report.get(Check::Sensor), Outcome::Pass);
+    assert_eq!((report.get(Check::Servo), report.get(Check::Fan)), (Outcome::Unverified, Outcome::Unverified));
+}
+
+#[test]
+fn warm_restart_resumes_outputs_and_profile_without_self_test() {
+    use hello_rust::retain::{Snapshot, RETAIN_LEN};
+    use hello_rust::setpoint::{ProfileState, Segment};
+
+    // RH tinggi → RUN dengan kipas ON, profil setpoint sedang berjalan
+    let world = SimWorld::new(Room { rh: 90.0, ambient_rh: 90.0, ..Room::default() });
+    let mut app = App::new(world.sensor(SID), SID, world.fan(), world.servo(), world.clock());
+    app.setpoint_mut().profile.push(Segment { duration_ms: 600_000, target: 60.0 });
+    app.setpoint_mut().start().unwrap();
+    let before = *run(&mut app, &world, 20, |_| false).last().unwrap();
+    assert_eq!((before.phase, before.cmd.fan_on, before.cmd.servo_deg), (Phase::Run, true, 120));
+
+    // "RTC RAM" melewati reset; App baru = firmware setelah watchdog
+    let mut rtc = [0u8; RETAIN_LEN];
+    app.snapshot().encode(&mut rtc);
+    world.fan().set(false);
+    world.servo().set_pulse_us(deg_to_pulse_us(0));
+
+    let mut app = App::new(world.sensor(SID), SID, world.fan(), world.servo(), world.clock());
+    app.warm_start(&Snapshot::decode(&rtc).unwrap());
+    // Output langsung dipulihkan, sebelum polling pertama
+    assert!(world.fan_on());
+    assert_eq!(world.servo_us(), deg_to_pulse_us(120));
+    let after = run(&mut app, &world, 25, |_| false);
+    assert!(after.iter().all(|s| s.phase == Phase::Run && s.cmd.fan_on));
+    assert!(matches!(app.setpoint().state(), ProfileState::Running { segment: 0, .. }));
+    assert!(app.setpoint().value() < 65.0);
+
+    // Power-on: RTC RAM nol → tidak ada snapshot, start dari default
+    assert!(Snapshot::decode(&[0; RETAIN_LEN]).is_none());
+}
+
+#[test]
+fn discovery_finds_module_at_unknown_id_and_baud() {
+    let world = SimWorld::new(Room::default());
+    world.set_sensor_baud(19_200);
+    let mut bus = Master::new(world.sensor(7), world.clock());
+
+    let cfg = ScanConfig::new(5, 8, 0x0000, 4).with_baud(9_600).with_baud(19_200);
+    let mut events = Vec::new();
+    block_on(scan(&mut bus, &cfg, |e| { events.push(e); async {} }));
+
+    assert_eq!(&events[..3], &[ScanEvent::Baud(9_600), ScanEvent::Baud(19_200), ScanEvent::Found { baud: 19_200, sid: 7 }]);
+    let regs: Vec<_> = events.iter().filter_map(|e| match e {
+        ScanEvent::Register { fc, addr, result, .. } => Some((*fc, *addr, result.is_ok())),
+        _ => None,
+    }).collect();
+    // SHT20: hanya 0x0001 (T) dan 0x0002 (RH), FC03 dan FC04 sama
+    assert_eq!(regs, vec![(3, 0, false), (3, 1, true), (3, 2, true), (3, 3, false), (4, 0, false), (4, 1, true), (4, 2, true), (4, 3, false)]);
+    assert!(events.contains(&ScanEvent::Register { sid: 7, fc: 4, addr: 3, result: Err(ModbusError::Exception(2)) }));
+    assert_eq!(events.last(), Some(&ScanEvent::Done { found: 1 }));
+}
+
+#[test]
+fn fan_through_modbus_relay_module_is_restored_after_power_cycle() {
+    let world = SimWorld::new(Room::default());
+    let mut bus = Master::new(world.relay(2), world.clock());
+    // Kanal 0 lokal (dilewati), kanal 1 = relay 0 modul @ID 2
+    let points = [IoPoint::Gpio, IoPoint::Modbus { sid: 2, addr: 0 }];
+    let mut coils = [CoilImage::OFF; 2];
+    let mut inputs = [InputImage::UNKNOWN; 2];
+
+    coils[1].desired = true;
+    block_on(sync_coils(&mut bus, &points, &mut coils));
+    assert!(world.fan_on());
+    assert_eq!((coils[0].actual, coils[1].actual), (None, Some(true)));
+    block_on(poll_inputs(&mut bus, &points, &mut inputs));
+    assert_eq!((inputs[0].level, inputs[1].level), (None, Some(true)));
+
+    // Modul sempat mati listrik: sync berikutnya baca balik OFF lalu tulis ulang
+    world.relay_power_cycle();
+    block_on(sync_coils(&mut bus, &points, &mut coils));
+    assert!(world.fan_on());
+
+    // Modul offline: readback hilang, input menahan level terakhir
+    world.set_relay_online(false);
+    block_on(sync_coils(&mut bus, &points, &mut coils));
+    block_on(poll_inputs(&mut bus, &points, &mut inputs));
+    assert_eq!((coils[1].actual, coils[1].fails), (None, 1));
+    assert_eq!((inputs[1].level, inputs[1].fails), (Some(true), 1));
+    // Offline terus: level lama tidak dipakai lagi
+    for _ in 1..INPUT_STALE_FAILS { block_on(poll_inputs(&mut bus, &points, &mut inputs)); }
+    assert_eq!((inputs[1].level, inputs[1].fails), (None, INPUT_STALE_FAILS));
+
+    // FC0F banyak coil sekaligus + FC01/FC02 multi-bit
+    world.set_relay_online(true);
+    block_on(bus.write_coils(2, 0, &[false, true, false, true, true, false, false, false])).unwrap();
+    let mut bits = [false; 8];
+    block_on(bus.read_bits(2, FC_READ_COILS, 0, &mut bits)).unwrap();
+    assert_eq!(bits, [false, true, false, true, true, false, false, false]);
+    assert!(!world.fan_on());
+    block_on(bus.read_bits(2, FC_READ_DISCRETE, 0, &mut bits[..2])).unwrap();
+    assert_eq!(&bits[..2], &[false, false]);
+    assert_eq!(block_on(bus.write_coil(2, 8, true)), Err(ModbusError::Exception(2)));
+}
@@ -1,10 +1,15 @@
 import os, time, csv, threading, re
+from collections import deque
 from pathlib import Path
 
 # ============ KONFIG ============
 CSV_PATH      = r"C:\DCS\sim_out.csv"   # harus sama dengan di DWSIM IronPython
 ESP32_PORT    = "COM12"                 # ganti sesuai port ESP32 S3
 ESP32_BAUD    = 115200
+SYNC_PERIOD_S = 60                      # kirim "sync <epoch_ms>" ke ESP32 tiap N detik
+ACK_PERIOD_S  = 2                       # kirim "ack <seq>" untuk sample [rec] yang sudah diterima
+LISTEN_IDLE_S = 5                       # tanpa [rec] selama ini → kirim "listen" lagi
+MAX_SKEW_MS   = 2000                    # selisih maks. ts sensor vs ts baris DWSIM
 
 # --- InfluxDB konfigurasi kamu ---
 INFLUX_URL    = "http://localhost:8086"
@@ -17,25 +22,59 @@ INFLUX_TOKEN  = "LvJKbsRaVoniO0htQf9p4jQSu221aN89dTXL6EI4HN-0PY-Q3a3gsQiijO8RHDz
 import serial
 last_temp = None
 last_rh   = None
+samples   = deque(maxlen=3600)  # (ts_ms, temp, rh, sync) dari baris [rec] firmware yang sudah sync
+last_seq  = 0                   # seq [rec] terakhir yang diterima (belum tentu sudah di-ack)
+last_rec  = 0.0                 # waktu lokal [rec] terakhir
 lock = threading.Lock()
 
+def host_link(ser):
+    # Sync waktu tiap SYNC_PERIOD_S, ack [rec] tiap ACK_PERIOD_S, dan "listen" ulang kalau
+    # aliran [rec] berhenti (ESP32 reset / sempat putus) → sample yang tertahan dikirim ulang.
+    acked, next_sync = 0, 0.0
+    while True:
+        try:
+            now = time.time()
+            if now >= next_sync:
+                ser.write(f"sync {int(now * 1000)}\n".encode())
+                next_sync = now + SYNC_PERIOD_S
+            with lock:
+                seq, idle = last_seq, now - last_rec
+            if seq != acked:
+                ser.write(f"ack {seq}\n".encode())
+                acked = seq
+            if idle > LISTEN_IDLE_S:
+                ser.write(b"listen\n")
+        except Exception as e:
+            print("[LINK] error:", e)
+        time.sleep(ACK_PERIOD_S)
+
 def serial_reader():
-    global last_temp, last_rh
+    global last_temp, last_rh, last_seq, last_rec
     try:
         ser = serial.Serial(ESP32_PORT, ESP32_BAUD, timeout=2)
         print(f"[SERIAL] connected {ESP32_PORT} @ {ESP32_BAUD}")
     except Exception as e:
         print("[SERIAL] gagal buka port:", e)
         return
+    threading.Thread(target=host_link, args=(ser,), daemon=True).start()
 
-    pat_t  = re.compile(r"T\s*=\s*([+-]?\d+(?:\.\d+)?)\s*(?:°?\s*C)?", re.IGNORECASE)
-    pat_rh = re.compile(r"RH\s*=\s*([+-]?\d+(?:\.\d+)?)\s*%?", re.IGNORECASE)
+    pat_t   = re.compile(r"T\s*=\s*([+-]?\d+(?:\.\d+)?)\s*(?:°?\s*C)?", re.IGNORECASE)
+    pat_rh  = re.compile(r"RH\s*=\s*([+-]?\d+(?:\.\d+)?)\s*%?", re.IGNORECASE)
+    pat_rec = re.compile(r"^\[rec\] seq (\d+) ts (\d+|-) up \d+ rh (\S+) t (\S+) sync (\w+)")
 
     while True:
         try:
             line = ser.readline().decode("utf-8", errors="ignore").strip()
             if not line:
                 continue
+            m0 = pat_rec.match(line)
+            if m0:
+                seq, ts, rh, t, sync = m0.groups()
+                with lock:
+                    last_seq, last_rec = int(seq), time.time()
+                    if ts != "-" and rh != "-" and t != "-":
+                        samples.append((int(ts), float(t), float(rh), sync))
+                continue
             m1 = pat_t.search(line)
             m2 = pat_rh.search(line)
             with lock:
@@ -50,10 +89,25 @@ from influxdb_client import InfluxDBClient, Point, WriteOptions
 influx = InfluxDBClient(url=INFLUX_URL, token=INFLUX_TOKEN, org=INFLUX_ORG)
 write_api = influx.write_api(write_options=WriteOptions(batch_size=1))
 
-def write_join_to_influx(row):
+def nearest_sample(ts_ms):
+    # Sample sensor dengan cap waktu terdekat; None kalau tidak ada yang cukup dekat
     with lock:
-        t = last_temp
-        h = last_rh
+        best = min(samples, key=lambda s: abs(s[0] - ts_ms), default=None)
+    if best is None or abs(best[0] - ts_ms) > MAX_SKEW_MS:
+        return None
+    return best
+
+def write_join_to_influx(row):
+    ts_ms = int(row["ts"])  # milidetik
+    s = nearest_sample(ts_ms)
+    if s:
+        sens_ts, t, h, sync = s
+    else:
+        # Fallback (firmware lama / belum sync): pasangkan dengan data terakhir yang datang
+        with lock:
+            t = last_temp
+            h = last_rh
+        sens_ts, sync = None, "unsynced"
     if t is None or h is None:
         return  # tunggu sampai data sensor ada
 
@@ -64,10 +118,10 @@ def write_join_to_influx(row):
     prod_Tc = float(row["prod_T_K"]) - 273.15
     prod_Pk = float(row["prod_P_kPa"])
     prod_F  = float(row["prod_F_kg_s"])
-    ts_ms   = int(row["ts"])  # milidetik
 
     p = (
         Point("process_join")
+        .tag("sync", sync)
         .field("sens_temp_c", float(t))
         .field("sens_rh_pct", float(h))
         .field("feed_t_c", feed_Tc)
@@ -78,8 +132,10 @@ def write_join_to_influx(row):
         .field("prod_f_kg_s", prod_F)
         .time(ts_ms, write_precision="ms")
     )
+    if sens_ts is not None:
+        p = p.field("sens_skew_ms", sens_ts - ts_ms)
     write_api.write(bucket=INFLUX_BUCKET, org=INFLUX_ORG, record=p)
-    print(f"[JOIN→Influx] sens T={t:.2f} RH={h:.2f} | feed Tc={feed_Tc:.2f} P={feed_Pk:.2f} | prod Tc={prod_Tc:.2f} P={prod_Pk:.2f}")
+    print(f"[JOIN→Influx] sens T={t:.2f} RH={h:.2f} ({sync}) | feed Tc={feed_Tc:.2f} P={feed_Pk:.2f} | prod Tc={prod_Tc:.2f} P={prod_Pk:.2f}")
 
 # ---------- CSV reader ----------
 def read_last_row(retry=5, delay=0.05):